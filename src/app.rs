@@ -1,13 +1,38 @@
 use eframe::egui;
 use image::DynamicImage;
 use rand::seq::IndexedRandom;
-use std::collections::{HashMap, HashSet};
-use std::fs::{self, File};
-use std::io::{BufRead, BufReader, Write};
-use std::path::PathBuf;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-use crate::models::{BoundingBox, Statistics};
-use crate::utils::resize_to_limit;
+use crate::autolabel::{self, ClassMapping, Detector, SharedProgress};
+use crate::dedup::{self, ImageHashes, SharedGroupProgress, SharedHashProgress};
+use crate::diff::{self, BoxDiff, DiffKind, DiffSummary};
+use crate::export::{
+    self, ArchiveFormat, ArchiveOptions, CropReport, ExportManifest, ExportReport, ExportScope,
+    FileKind, LabelTransform, ManifestEntry, PlanAction, PlannedFile, TransferMode,
+};
+use crate::keymap::{self, Action, Keymap, KeymapOverrides};
+use crate::labels::{self, ShapeConversionReport};
+use crate::merge::{self, MergeReport, MergeSource};
+use crate::metrics::{self, EvalReport};
+use crate::models::{
+    BoundingBox, FileFilter, ImageRecord, ImageStatus, Keypoint, ReviewComment, Shape,
+    Statistics, TagFilter,
+};
+use crate::project::{
+    AttributeDefinition, AttributeKind, KeypointSchema, KeypointSchemaDraft, ProjectConfig,
+    TagDefinition,
+};
+use crate::remap::{self, RemapReport};
+use crate::report;
+use crate::session::{self, ImageVisit, ProgressReport, SessionAction, SessionEntry};
+use crate::split::{self, Split, SplitInput, SplitRecord, SplitSummary};
+use crate::stats::{self, DatasetStats, SharedStatsProgress};
+use crate::status;
+use crate::tags::{self, ImageTags};
+use crate::utils::{now_secs, resize_to_limit};
 
 #[derive(Clone)]
 pub struct AnnotationApp {
@@ -16,10 +41,13 @@ pub struct AnnotationApp {
     pub current_image: Option<DynamicImage>,
     pub current_image_path: Option<PathBuf>,
     pub bounding_boxes: Vec<BoundingBox>,
+    pub saved_boxes: Vec<BoundingBox>, // 当前图片上次读取或保存时的框，用于增量更新总体统计
     pub selected_box: Option<usize>,
     pub texture: Option<egui::TextureHandle>,
     pub current_image_name: Option<String>,
-    pub modified_images: HashSet<String>,
+    pub image_records: HashMap<String, ImageRecord>, // 图片文件名 -> 状态记录
+    pub records_load_error: Option<String>, // 状态记录文件解析失败时不再保存，避免覆盖原文件
+    pub project_load_error: Option<String>, // 项目配置文件解析失败时不再保存，避免覆盖原文件
     pub cached_image_files: Vec<PathBuf>,
     pub status_message: Option<(String, f32)>,
     pub image_cache: HashMap<PathBuf, DynamicImage>,
@@ -35,6 +63,83 @@ pub struct AnnotationApp {
     pub export_result_info: String,
     pub show_labels: bool,       // 控制标签显示
     pub show_center_points: bool, // 控制中心点显示
+    pub project: ProjectConfig,
+    pub hidden_classes: HashSet<i32>, // 画布上隐藏的类别
+    pub class_colors_dirty: bool, // 类别颜色已修改，取色弹窗关闭后再保存项目
+    pub keymap: Keymap,
+    pub user_keymap: KeymapOverrides, // 用户级快捷键配置，项目配置优先于它
+    pub show_keymap_dialog: bool,
+    pub rebinding_action: Option<Action>, // 正在等待录入新按键的操作
+    pub file_filter: FileFilter,
+    pub bulk_status: ImageStatus, // 批量设置状态时选择的目标状态
+    pub review_mode: bool,
+    pub review_comment_draft: String,
+    pub review_pin_to_box: bool, // 驳回意见是否关联到选中的边界框
+    pub compare_dir: Option<PathBuf>, // 对比标签目录（如模型预测或其他标注员的结果）
+    pub compare_boxes: Vec<BoundingBox>,
+    pub box_diffs: Vec<BoxDiff>,
+    pub diff_iou_threshold: f64,
+    pub dataset_diff: Option<DiffSummary>,
+    pub show_metrics_panel: bool,
+    pub metrics_conf_threshold: f64,
+    pub eval_report: Option<EvalReport>,
+    pub confidence_threshold: f64, // 低于该置信度的预标注框视为低置信度
+    pub hide_low_confidence: bool, // 隐藏低置信度框，否则半透明显示
+    pub show_auto_label_dialog: bool,
+    pub detector: Option<Arc<Detector>>, // 已加载的自动标注模型，设置变化后需重新加载
+    pub auto_label_job: Option<SharedProgress>, // 正在后台运行的批量标注
+    pub rotating_box: bool, // 正在拖动选中旋转框的旋转手柄
+    pub polygon_draft: Vec<[f64; 2]>, // 正在绘制的多边形顶点
+    pub dragging_vertex: Option<usize>, // 正在拖动的选中多边形顶点
+    pub export_as_boxes: bool, // 导出时将多边形/旋转框转换为外接水平框
+    pub active_keypoint: Option<usize>, // 选中框上等待单击放置的关键点
+    pub dragging_keypoint: Option<usize>, // 正在拖动的选中框关键点
+    pub show_keypoint_schema_dialog: bool,
+    pub keypoint_schema_draft: KeypointSchemaDraft,
+    pub image_tags: ImageTags, // 图片文件名 -> 图片级标签
+    pub new_tag_name: String,  // 统计面板中新增标签的输入
+    pub new_tag_group: String,
+    pub show_attribute_dialog: bool,
+    pub attribute_target: Option<i32>, // 新属性添加到的类别，None 表示所有类别共用
+    pub new_attribute: AttributeDefinition,
+    pub new_attribute_options: String, // 选项类型属性的选项，逗号分隔
+    pub show_split_dialog: bool,
+    pub split_record: SplitRecord, // 已导出过的图片划分，重复导出时保持不变
+    pub split_preview: Option<SplitSummary>,
+    pub show_export_dialog: bool,
+    pub export_profile_index: usize, // 导出窗口中选中的导出方案
+    pub export_target: Option<PathBuf>,
+    pub export_preview: Option<Vec<PlannedFile>>, // 预览（不写入文件）得到的导出计划
+    pub archive_format: ArchiveFormat,
+    pub archive_split: bool, // 压缩包按训练/验证/测试划分
+    pub show_crop_dialog: bool,
+    pub show_remap_dialog: bool,
+    pub confirm_remap_apply: bool, // 直接修改标签目录前的确认
+    pub confirm_shape_convert: Option<bool>, // 数据集批量转换形状前的确认，值为是否转为旋转框
+    pub show_merge_dialog: bool,
+    pub merge_sources: Vec<MergeSource>,
+    pub merge_image_out: Option<PathBuf>,
+    pub merge_label_out: Option<PathBuf>,
+    pub merge_skip_duplicates: bool,
+    pub show_dedup_dialog: bool,
+    pub dedup_job: Option<SharedHashProgress>, // 正在后台计算的感知哈希
+    pub dedup_hashes: BTreeMap<String, ImageHashes>, // 当前文件列表中图片的哈希
+    pub dedup_groups: Vec<Vec<String>>,
+    pub dedup_group_job: Option<SharedGroupProgress>, // 正在后台重新分组
+    pub dedup_selected: Option<usize>,     // 选中的近似重复组
+    pub dedup_compare: (usize, usize),     // 并排对比的组内图片序号
+    pub dedup_textures: HashMap<String, egui::TextureHandle>, // 对比视图用的缩略图
+    pub dedup_confirm_delete: Option<String>,
+    pub show_dataset_stats: bool,
+    pub dataset_stats_job: Option<SharedStatsProgress>, // 正在后台进行的分布统计
+    pub dataset_stats: Option<DatasetStats>,
+    pub totals_job: Option<SharedStatsProgress>, // 正在后台重新统计总体类别和形状数量
+    pub totals_dirty: bool, // 重新统计期间标签有变化，结束后需要再统计一次
+    pub visit: Option<ImageVisit>, // 当前图片的停留，切换图片时写入会话日志
+    pub show_progress_dialog: bool,
+    pub progress_log: Vec<SessionEntry>,
+    pub progress_annotator: Option<String>, // 进度窗口只统计该标注员，None 为全部
+    pub progress_report: ProgressReport,
 }
 
 impl Default for AnnotationApp {
@@ -45,10 +150,13 @@ impl Default for AnnotationApp {
             current_image: None,
             current_image_path: None,
             bounding_boxes: Vec::new(),
+            saved_boxes: Vec::new(),
             selected_box: None,
             texture: None,
             current_image_name: None,
-            modified_images: HashSet::new(),
+            image_records: HashMap::new(),
+            records_load_error: None,
+            project_load_error: None,
             cached_image_files: Vec::new(),
             status_message: None,
             image_cache: HashMap::new(),
@@ -64,8 +172,86 @@ impl Default for AnnotationApp {
             export_result_info: String::new(),
             show_labels: true,           // 默认显示标签
             show_center_points: false,   // 默认不显示中心点
+            project: ProjectConfig::default(),
+            hidden_classes: HashSet::new(),
+            class_colors_dirty: false,
+            keymap: Keymap::default(),
+            user_keymap: keymap::load_user_overrides(),
+            show_keymap_dialog: false,
+            rebinding_action: None,
+            file_filter: FileFilter::default(),
+            bulk_status: ImageStatus::Labeled,
+            review_mode: false,
+            review_comment_draft: String::new(),
+            review_pin_to_box: true,
+            compare_dir: None,
+            compare_boxes: Vec::new(),
+            box_diffs: Vec::new(),
+            diff_iou_threshold: 0.5,
+            dataset_diff: None,
+            show_metrics_panel: false,
+            metrics_conf_threshold: 0.25,
+            eval_report: None,
+            confidence_threshold: 0.5,
+            hide_low_confidence: false,
+            show_auto_label_dialog: false,
+            detector: None,
+            auto_label_job: None,
+            rotating_box: false,
+            polygon_draft: Vec::new(),
+            dragging_vertex: None,
+            export_as_boxes: false,
+            active_keypoint: None,
+            dragging_keypoint: None,
+            show_keypoint_schema_dialog: false,
+            keypoint_schema_draft: KeypointSchemaDraft::default(),
+            image_tags: ImageTags::new(),
+            new_tag_name: String::new(),
+            new_tag_group: String::new(),
+            show_attribute_dialog: false,
+            attribute_target: None,
+            new_attribute: AttributeDefinition::default(),
+            new_attribute_options: String::new(),
+            show_split_dialog: false,
+            split_record: SplitRecord::new(),
+            split_preview: None,
+            show_export_dialog: false,
+            export_profile_index: 0,
+            export_target: None,
+            export_preview: None,
+            archive_format: ArchiveFormat::Zip,
+            archive_split: false,
+            show_crop_dialog: false,
+            show_remap_dialog: false,
+            confirm_remap_apply: false,
+            confirm_shape_convert: None,
+            show_merge_dialog: false,
+            merge_sources: Vec::new(),
+            merge_image_out: None,
+            merge_label_out: None,
+            merge_skip_duplicates: false,
+            show_dedup_dialog: false,
+            dedup_job: None,
+            dedup_hashes: BTreeMap::new(),
+            dedup_groups: Vec::new(),
+            dedup_group_job: None,
+            dedup_selected: None,
+            dedup_compare: (0, 1),
+            dedup_textures: HashMap::new(),
+            dedup_confirm_delete: None,
+            show_dataset_stats: false,
+            dataset_stats_job: None,
+            dataset_stats: None,
+            totals_job: None,
+            totals_dirty: false,
+            visit: None,
+            show_progress_dialog: false,
+            progress_log: Vec::new(),
+            progress_annotator: None,
+            progress_report: ProgressReport::default(),
         };
-        app.load_modified_records();
+        app.rebuild_keymap();
+        app.load_image_records();
         app
     }
 }
@@ -110,81 +296,102 @@ impl AnnotationApp {
     }
 
     pub fn load_annotations(&mut self) {
-        if let Some(image_path) = &self.current_image_path {
-            if let Some(label_dir) = &self.label_dir {
-                let label_path = label_dir
-                    .join(image_path.file_stem().unwrap())
-                    .with_extension("txt");
-
-                self.bounding_boxes.clear();
-                if let Ok(file) = File::open(label_path) {
-                    let reader = BufReader::new(file);
-                    for line in reader.lines() {
-                        if let Ok(line) = line {
-                            let parts: Vec<f64> = line
-                                .split_whitespace()
-                                .map(|s| s.parse().unwrap_or(0.0))
-                                .collect();
-
-                            if parts.len() == 5 {
-                                self.bounding_boxes.push(BoundingBox {
-                                    class: parts[0] as i32,
-                                    x: parts[1],
-                                    y: parts[2],
-                                    width: parts[3],
-                                    height: parts[4],
-                                });
-                            }
-                        }
-                    }
-                }
-            }
+        // 切换到另一张图片时记录在上一张图片上的停留
+        if self.visit.as_ref().map(|v| &v.image) != self.current_image_name.as_ref() {
+            self.finish_visit();
+        }
+        if let Some(image_path) = &self.current_image_path
+            && let Some(label_dir) = &self.label_dir
+        {
+            self.bounding_boxes = labels::read_label_file(
+                &labels::label_path(label_dir, image_path),
+                &self.project.label_layout(),
+            );
+        }
+        let suspects: BTreeSet<i32> = self
+            .bounding_boxes
+            .iter()
+            .filter(|b| labels::looks_like_keypoints(b))
+            .map(|b| b.class)
+            .collect();
+        if !suspects.is_empty() {
+            let classes: Vec<String> = suspects.iter().map(|c| c.to_string()).collect();
+            self.show_status(&format!(
+                "类别 {} 的标注像是关键点，但该类别没有关键点模板，编辑前请先添加模板",
+                classes.join(", ")
+            ));
+        }
+        self.saved_boxes = self.bounding_boxes.clone();
+        if self.visit.is_none() {
+            self.visit = self.current_image_name.clone().map(ImageVisit::new);
         }
+        self.polygon_draft.clear();
+        self.dragging_vertex = None;
+        self.active_keypoint = None;
+        self.dragging_keypoint = None;
+        self.load_compare_annotations();
         self.update_statistics();
     }
 
     pub fn save_annotations(&mut self) {
-        if let Some(image_path) = &self.current_image_path {
-            if let Some(label_dir) = &self.label_dir {
-                let label_path = label_dir
-                    .join(image_path.file_stem().unwrap())
-                    .with_extension("txt");
-
-                if let Ok(mut file) = File::create(label_path) {
-                    for bbox in &self.bounding_boxes {
-                        writeln!(
-                            file,
-                            "{} {} {} {} {}",
-                            bbox.class, bbox.x, bbox.y, bbox.width, bbox.height
-                        )
-                        .ok();
-                    }
-                    if let Some(name) = &self.current_image_name {
-                        self.modified_images.insert(name.clone());
+        if let Some(image_path) = &self.current_image_path
+            && let Some(label_dir) = &self.label_dir
+        {
+            let label_path = labels::label_path(label_dir, image_path);
+
+            let layout = self.project.label_layout();
+            if labels::write_label_file(&label_path, &self.bounding_boxes, &layout).is_ok() {
+                // 按保存前后的差异增量更新总体统计
+                let saved = std::mem::replace(&mut self.saved_boxes, self.bounding_boxes.clone());
+                let edited = saved != self.bounding_boxes;
+                if edited && let Some(visit) = &mut self.visit {
+                    visit.edited = true;
+                }
+                self.statistics.remove_boxes(&saved);
+                self.statistics.add_boxes(&self.bounding_boxes);
+                if self.totals_job.is_some() {
+                    self.totals_dirty = true;
+                }
+                // 自动保存只代表"进行中"，完成与否由用户显式设置状态；
+                // 只选中框等没有修改的保存不算编辑，不改变记录
+                if let (true, Some(name)) = (edited, self.current_image_name.clone()) {
+                    let record = self.image_records.entry(name.clone()).or_default();
+                    record.last_editor = self.project.annotator_name();
+                    record.last_edited_at = now_secs();
+                    if matches!(
+                        self.image_status(&name),
+                        ImageStatus::Todo | ImageStatus::PreLabeled
+                    ) {
+                        self.set_image_status(&name, ImageStatus::InProgress);
+                    } else {
+                        self.save_image_records();
                     }
                 }
             }
         }
+        self.update_diff();
         self.update_statistics();
     }
 
     pub fn update_file_list(&mut self) {
-        if let Some(image_dir) = &self.image_dir {
-            if let Ok(entries) = fs::read_dir(image_dir) {
-                self.cached_image_files = entries
-                    .filter_map(|entry| entry.ok())
-                    .filter(|entry| {
-                        entry
-                            .path()
-                            .extension()
-                            .map_or(false, |ext| ext == "jpg" || ext == "png")
-                    })
-                    .map(|e| e.path())
-                    .collect();
-                self.cached_image_files.sort();
-            }
+        if let Some(image_dir) = &self.image_dir
+            && let Ok(entries) = fs::read_dir(image_dir)
+        {
+            self.cached_image_files = entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| {
+                    entry
+                        .path()
+                        .extension()
+                        .is_some_and(|ext| ext == "jpg" || ext == "png")
+                })
+                .map(|e| e.path())
+                .collect();
+            self.cached_image_files.sort();
         }
         self.update_statistics();
+        // 总体统计按文件列表计算，列表重建后需要重新统计
+        self.update_total_statistics();
     }
 
     pub fn update_image_cache(&mut self, path: PathBuf, img: DynamicImage) {
@@ -193,21 +400,21 @@ impl AnnotationApp {
         if let Some(current_pos) = self.cached_image_files.iter().position(|p| p == &path) {
             if current_pos + 1 < self.cached_image_files.len() {
                 let next_path = &self.cached_image_files[current_pos + 1];
-                if !self.image_cache.contains_key(next_path) {
-                    if let Ok(img) = image::open(next_path) {
-                        let img = resize_to_limit(&img, 1920, 1080);
-                        self.image_cache.insert(next_path.clone(), img);
-                    }
+                if !self.image_cache.contains_key(next_path)
+                    && let Ok(img) = image::open(next_path)
+                {
+                    let img = resize_to_limit(&img, 1920, 1080);
+                    self.image_cache.insert(next_path.clone(), img);
                 }
             }
 
             if current_pos > 0 {
                 let prev_path = &self.cached_image_files[current_pos - 1];
-                if !self.image_cache.contains_key(prev_path) {
-                    if let Ok(img) = image::open(prev_path) {
-                        let img = resize_to_limit(&img, 1920, 1080);
-                        self.image_cache.insert(prev_path.clone(), img);
-                    }
+                if !self.image_cache.contains_key(prev_path)
+                    && let Ok(img) = image::open(prev_path)
+                {
+                    let img = resize_to_limit(&img, 1920, 1080);
+                    self.image_cache.insert(prev_path.clone(), img);
                 }
             }
         }
@@ -260,7 +467,7 @@ impl AnnotationApp {
                         .map(|s| s.to_string());
                     file_name
                         .as_ref()
-                        .map_or(false, |name| !self.modified_images.contains(name))
+                        .is_some_and(|name| !self.image_status(name).is_done())
                 })
                 .cloned()
                 .collect();
@@ -318,6 +525,22 @@ impl AnnotationApp {
             self.update_file_list();
         }
 
+        // 被驳回的图片优先返工，其次才是未完成的图片
+        let target = self
+            .find_next_image(|status| status == ImageStatus::Rejected)
+            .or_else(|| self.find_next_image(|status| !status.is_done()));
+
+        match target {
+            Some(path) => {
+                self.load_image(&path);
+                self.scroll_to_current = true;
+            }
+            None => self.show_status("没有找到未修改的图片"),
+        }
+    }
+
+    /// 从当前图片之后开始（到末尾后回到开头）查找第一张状态满足条件的图片
+    pub fn find_next_image(&self, predicate: impl Fn(ImageStatus) -> bool) -> Option<PathBuf> {
         let start_pos = if let Some(current_path) = &self.current_image_path {
             self.cached_image_files
                 .iter()
@@ -328,31 +551,34 @@ impl AnnotationApp {
             0
         };
 
-        // 从当前位置开始查找下一个未修改的图片
-        for i in start_pos..self.cached_image_files.len() {
-            let path = self.cached_image_files[i].clone();
-            if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                if !self.modified_images.contains(name) {
-                    self.load_image(&path);
-                    self.scroll_to_current = true;
-                    return;
-                }
-            }
-        }
+        let len = self.cached_image_files.len();
+        (0..len)
+            .map(|offset| &self.cached_image_files[(start_pos + offset) % len])
+            .find(|path| {
+                path.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|name| predicate(self.image_status(name)))
+            })
+            .cloned()
+    }
 
-        // 如果从当前位置到末尾没有找到，从头开始找到当前位置
-        for i in 0..start_pos {
-            let path = self.cached_image_files[i].clone();
-            if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                if !self.modified_images.contains(name) {
-                    self.load_image(&path);
-                    self.scroll_to_current = true;
-                    return;
-                }
-            }
-        }
+    /// 从当前图片之前开始（到开头后回到末尾）倒序查找第一张状态满足条件的图片
+    pub fn find_prev_image(&self, predicate: impl Fn(ImageStatus) -> bool) -> Option<PathBuf> {
+        let len = self.cached_image_files.len();
+        let current_pos = self
+            .current_image_path
+            .as_ref()
+            .and_then(|current| self.cached_image_files.iter().position(|p| p == current))
+            .unwrap_or(0);
 
-        self.show_status("没有找到未修改的图片");
+        (1..=len)
+            .map(|offset| &self.cached_image_files[(current_pos + len - offset) % len])
+            .find(|path| {
+                path.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|name| predicate(self.image_status(name)))
+            })
+            .cloned()
     }
 
     pub fn go_back(&mut self) {
@@ -362,28 +588,415 @@ impl AnnotationApp {
         }
     }
 
-    pub fn load_modified_records(&mut self) {
-        if let Some(label_dir) = &self.label_dir {
-            let record_path = label_dir.join("modified_records.txt");
-            if let Ok(file) = File::open(record_path) {
-                let reader = BufReader::new(file);
-                for line in reader.lines() {
-                    if let Ok(filename) = line {
-                        self.modified_images.insert(filename);
-                    }
+    pub fn load_image_records(&mut self) {
+        if let Some(label_dir) = self.label_dir.clone() {
+            match status::load_records(&label_dir) {
+                Ok(records) => {
+                    self.image_records = records;
+                    self.records_load_error = None;
+                }
+                Err(e) => {
+                    self.image_records.clear();
+                    self.show_status(&format!("{}，修复该文件前不会保存图片状态", e));
+                    self.records_load_error = Some(e);
                 }
             }
+            self.image_tags = tags::load_tags(&label_dir);
+            self.split_record = split::load_record(&label_dir);
         }
     }
 
-    pub fn save_modified_records(&self) {
-        if let Some(label_dir) = &self.label_dir {
-            let record_path = label_dir.join("modified_records.txt");
-            if let Ok(mut file) = File::create(record_path) {
-                for filename in &self.modified_images {
-                    writeln!(file, "{}", filename).ok();
-                }
+    pub fn save_image_records(&mut self) {
+        if let Some(e) = &self.records_load_error {
+            self.show_status(&format!("{}，未保存图片状态", e));
+            return;
+        }
+        if let Some(label_dir) = &self.label_dir
+            && let Err(e) = status::save_records(label_dir, &self.image_records)
+        {
+            self.show_status(&e);
+        }
+    }
+
+    pub fn image_status(&self, name: &str) -> ImageStatus {
+        self.image_records
+            .get(name)
+            .map(|r| r.status)
+            .unwrap_or_default()
+    }
+
+    /// 是否被人工修改过（状态不是"未开始"或"已预标注"）
+    pub fn is_modified(&self, name: &str) -> bool {
+        !matches!(
+            self.image_status(name),
+            ImageStatus::Todo | ImageStatus::PreLabeled
+        )
+    }
+
+    pub fn set_image_status(&mut self, name: &str, status: ImageStatus) {
+        let record = self.image_records.entry(name.to_string()).or_default();
+        record.status = status;
+        record.updated_at = now_secs();
+        self.save_image_records();
+        self.update_statistics();
+    }
+
+    pub fn set_current_status(&mut self, status: ImageStatus) {
+        if let Some(name) = self.current_image_name.clone() {
+            if status.is_done() && !self.image_status(&name).is_done() {
+                self.log_session(name.clone(), SessionAction::Completed, 0);
+            }
+            self.set_image_status(&name, status);
+            self.show_status(&format!("已将当前图片标记为「{}」", status.label()));
+        }
+    }
+
+    /// 修改备注，只更新内存中的记录，由调用方在编辑结束时保存
+    pub fn set_image_note(&mut self, name: &str, note: String) {
+        let record = self.image_records.entry(name.to_string()).or_default();
+        record.note = note;
+        record.updated_at = now_secs();
+    }
+
+    /// 当前图片的图片级标签
+    pub fn current_tags(&self) -> BTreeSet<String> {
+        self.current_image_name
+            .as_ref()
+            .and_then(|name| self.image_tags.get(name))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// 切换当前图片的第 index 个项目标签
+    pub fn toggle_current_tag(&mut self, index: usize) {
+        let Some(name) = self.current_image_name.clone() else {
+            return;
+        };
+        let Some(definition) = self.project.image_tags.get(index).cloned() else {
+            self.show_status(&format!("项目中没有第 {} 个图片标签", index + 1));
+            return;
+        };
+        let set = self.image_tags.entry(name).or_default();
+        let added = tags::toggle_tag(set, &self.project.image_tags, index);
+        self.save_image_tags();
+        self.show_status(&format!(
+            "{}标签「{}」",
+            if added { "已添加" } else { "已移除" },
+            definition.name
+        ));
+    }
+
+    pub fn save_image_tags(&mut self) {
+        if let Some(label_dir) = &self.label_dir
+            && let Err(e) = tags::save_tags(label_dir, &self.image_tags)
+        {
+            self.show_status(&e);
+        }
+    }
+
+    /// 新增一个项目标签定义
+    pub fn add_tag_definition(&mut self) {
+        let name = self.new_tag_name.trim().to_string();
+        if name.is_empty() {
+            return;
+        }
+        if self.project.image_tags.iter().any(|d| d.name == name) {
+            self.show_status("标签已存在");
+            return;
+        }
+        self.project.image_tags.push(TagDefinition {
+            name,
+            group: self.new_tag_group.trim().to_string(),
+        });
+        self.new_tag_name.clear();
+        self.save_project();
+    }
+
+    /// 按属性定义窗口中的输入新增框属性定义
+    pub fn add_attribute_definition(&mut self) -> Result<(), String> {
+        let mut definition = self.new_attribute.clone();
+        definition.name = definition.name.trim().to_string();
+        if definition.name.is_empty() {
+            return Err("属性名不能为空".to_string());
+        }
+        if definition.kind == AttributeKind::Choice {
+            definition.options = self
+                .new_attribute_options
+                .split([',', '，'])
+                .map(|o| o.trim().to_string())
+                .filter(|o| !o.is_empty())
+                .collect();
+            if definition.options.is_empty() {
+                return Err("选项类型的属性至少需要一个选项".to_string());
+            }
+        }
+        let list = match self.attribute_target {
+            None => &mut self.project.common_attributes,
+            Some(class) => self.project.class_attributes.entry(class).or_default(),
+        };
+        if list.iter().any(|d| d.name == definition.name) {
+            return Err("属性已存在".to_string());
+        }
+        list.push(definition);
+        self.new_attribute.name.clear();
+        self.new_attribute_options.clear();
+        Ok(())
+    }
+
+    /// 删除项目标签定义，同时从所有图片上移除该标签
+    pub fn remove_tag_definition(&mut self, index: usize) {
+        if index >= self.project.image_tags.len() {
+            return;
+        }
+        let definition = self.project.image_tags.remove(index);
+        for set in self.image_tags.values_mut() {
+            set.remove(&definition.name);
+        }
+        if self.file_filter.tag == TagFilter::Tag(definition.name.clone()) {
+            self.file_filter.tag = TagFilter::All;
+        }
+        self.save_project();
+        self.save_image_tags();
+    }
+
+    /// 所有图片的文件名，按列表顺序
+    pub fn image_names(&self) -> Vec<String> {
+        self.cached_image_files
+            .iter()
+            .filter_map(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+            .collect()
+    }
+
+    /// 导出图片标签为 CSV 或 JSON
+    pub fn export_image_tags(&mut self, path: &Path, csv: bool) -> Result<(), String> {
+        let images = self.image_names();
+        let content = if csv {
+            tags::to_csv(&self.image_tags, &images, &self.project.image_tags)
+        } else {
+            tags::to_json(&self.image_tags, &images)?
+        };
+        fs::write(path, content).map_err(|e| format!("写入文件失败: {}", e))
+    }
+
+    /// 将当前文件列表中的图片导出为 COCO JSON，返回图片数量
+    pub fn export_coco(&mut self, path: &Path) -> Result<usize, String> {
+        let items = self.export_items()?;
+        let json = export::coco::to_json(&items, &self.project)?;
+        fs::write(path, json).map_err(|e| format!("写入文件失败: {}", e))?;
+        Ok(items.len())
+    }
+
+    /// 将当前文件列表中的图片导出为 Pascal VOC XML，返回图片数量
+    pub fn export_voc(&mut self, out_dir: &Path) -> Result<usize, String> {
+        let items = self.export_items()?;
+        export::voc::write_dir(&items, &self.project, out_dir)
+    }
+
+    fn export_items(&self) -> Result<Vec<export::ExportItem>, String> {
+        let label_dir = self.label_dir.as_ref().ok_or("请先选择标签目录")?;
+        let images = self.filtered_image_files();
+        if images.is_empty() {
+            return Err("当前文件列表为空".to_string());
+        }
+        // COCO/VOC 自行处理多边形和旋转框，这里只应用类别重映射
+        let transform = LabelTransform {
+            axis_aligned: false,
+            ..self.label_transform()
+        };
+        let layout = self.project.label_layout();
+        let images = transform.filter_images(images, label_dir, &layout);
+        export::collect_items(&images, label_dir, &layout, &transform)
+    }
+
+    /// 将类别重映射直接应用到标签目录中所有图片的标签，先保存当前图片的修改
+    pub fn apply_remap_to_labels(&mut self) -> Result<RemapReport, String> {
+        let label_dir = self.label_dir.clone().ok_or("请先选择标签目录")?;
+        if self.project.remap.table.is_empty() {
+            return Err("重映射表为空".to_string());
+        }
+        self.save_annotations();
+        let report = remap::remap_label_dir(
+            &self.cached_image_files,
+            &label_dir,
+            &self.project.label_layout(),
+            &self.project.remap.table,
+        );
+        let mut comments_changed = false;
+        for (name, removed) in &report.dropped_boxes {
+            if let Some(record) = self.image_records.get_mut(name) {
+                comments_changed |= record.remove_comment_boxes(removed);
+            }
+        }
+        if comments_changed {
+            self.save_image_records();
+        }
+        self.load_annotations();
+        self.update_total_statistics();
+        Ok(report)
+    }
+
+    /// 导出时对标注的转换，由「导出为外接水平框」和类别重映射设置决定
+    pub fn label_transform(&self) -> LabelTransform {
+        LabelTransform {
+            axis_aligned: self.export_as_boxes,
+            remap: self.project.remap.export_table().cloned(),
+            skip_empty: self.project.remap.skip_empty,
+        }
+    }
+
+    /// 按当前过滤条件筛选后的图片列表
+    pub fn filtered_image_files(&self) -> Vec<PathBuf> {
+        self.cached_image_files
+            .iter()
+            .filter(|path| {
+                let name = path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let tags = self.image_tags.get(&name);
+                let tag_matches = match &self.file_filter.tag {
+                    TagFilter::All => true,
+                    TagFilter::Untagged => tags.is_none_or(|t| t.is_empty()),
+                    TagFilter::Tag(tag) => tags.is_some_and(|t| t.contains(tag)),
+                };
+                tag_matches
+                    && self
+                        .file_filter
+                        .status
+                        .is_none_or(|status| self.image_status(&name) == status)
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// 将当前过滤结果中的所有图片设置为指定状态，返回修改的数量
+    pub fn bulk_set_status(&mut self, status: ImageStatus) -> usize {
+        let names: Vec<String> = self
+            .filtered_image_files()
+            .iter()
+            .filter_map(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+            .collect();
+        let now = now_secs();
+        for name in &names {
+            let record = self.image_records.entry(name.clone()).or_default();
+            record.status = status;
+            record.updated_at = now;
+        }
+        self.save_image_records();
+        self.update_statistics();
+        names.len()
+    }
+
+    /// 审核模式只在"已标注"和"待复核"的图片之间切换
+    pub fn is_in_review_queue(status: ImageStatus) -> bool {
+        matches!(status, ImageStatus::Labeled | ImageStatus::NeedsReview)
+    }
+
+    pub fn review_queue_len(&self) -> usize {
+        self.cached_image_files
+            .iter()
+            .filter_map(|p| p.file_name().and_then(|n| n.to_str()))
+            .filter(|name| Self::is_in_review_queue(self.image_status(name)))
+            .count()
+    }
+
+    pub fn toggle_review_mode(&mut self) {
+        self.review_mode = !self.review_mode;
+        self.is_drawing = false;
+        self.drawing_start = None;
+        if self.review_mode {
+            self.show_status("已进入审核模式");
+            let current_in_queue = self
+                .current_image_name
+                .as_ref()
+                .is_some_and(|name| Self::is_in_review_queue(self.image_status(name)));
+            if !current_in_queue {
+                self.switch_review_image(true);
             }
+        } else {
+            self.show_status("已退出审核模式");
+        }
+    }
+
+    pub fn switch_review_image(&mut self, next: bool) {
+        if self.cached_image_files.is_empty() {
+            self.update_file_list();
+        }
+        let target = if next {
+            self.find_next_image(Self::is_in_review_queue)
+        } else {
+            self.find_prev_image(Self::is_in_review_queue)
+        };
+        match target {
+            Some(path) => {
+                self.load_image(&path);
+                self.scroll_to_current = true;
+            }
+            None => self.show_status("没有待审核的图片"),
+        }
+    }
+
+    pub fn approve_current(&mut self) {
+        if let Some(name) = self.current_image_name.clone() {
+            let record = self.image_records.entry(name.clone()).or_default();
+            record.reviewer = self.project.annotator_name();
+            // 通过时之前的审核意见视为已处理
+            for comment in &mut record.comments {
+                comment.resolved = true;
+            }
+            self.set_image_status(&name, ImageStatus::Approved);
+            self.show_status("已通过");
+            if self.review_mode {
+                self.switch_review_image(true);
+            }
+        }
+    }
+
+    /// 当前图片删除框后更新审核意见指向的框
+    pub fn remove_comment_boxes(&mut self, removed: &[usize]) {
+        let Some(name) = self.current_image_name.clone() else {
+            return;
+        };
+        if self
+            .image_records
+            .get_mut(&name)
+            .is_some_and(|record| record.remove_comment_boxes(removed))
+        {
+            self.save_image_records();
+        }
+    }
+
+    /// 驳回当前图片，box_index 为 None 时意见针对整张图片
+    pub fn reject_current(&mut self, text: String, box_index: Option<usize>) {
+        if let Some(name) = self.current_image_name.clone() {
+            let author = self.project.annotator_name();
+            let record = self.image_records.entry(name.clone()).or_default();
+            record.reviewer = author.clone();
+            if !text.trim().is_empty() {
+                record.comments.push(ReviewComment {
+                    box_index,
+                    text,
+                    author,
+                    created_at: now_secs(),
+                    resolved: false,
+                });
+            }
+            self.set_image_status(&name, ImageStatus::Rejected);
+            self.show_status("已驳回");
+            if self.review_mode {
+                self.switch_review_image(true);
+            }
+        }
+    }
+
+    pub fn set_comment_resolved(&mut self, name: &str, index: usize, resolved: bool) {
+        if let Some(comment) = self
+            .image_records
+            .get_mut(name)
+            .and_then(|r| r.comments.get_mut(index))
+        {
+            comment.resolved = resolved;
+            self.save_image_records();
         }
     }
 
@@ -401,137 +1014,1432 @@ impl AnnotationApp {
 
     pub fn select_label_dir(&mut self) {
         if let Some(path) = rfd::FileDialog::new().pick_folder() {
+            self.finish_visit();
             self.label_dir = Some(path);
-            self.load_modified_records();
+            self.load_project();
+            self.load_image_records();
             self.update_total_statistics();
             self.show_status("已加载标签目录");
         }
     }
-    pub fn update_statistics(&mut self) {
-            let mut stats = Statistics::default();
 
-            // 统计总图片数
-            stats.total_images = self.cached_image_files.len();
-            stats.modified_images = self.modified_images.len();
+    /// 向标签目录的会话日志追加一条记录，框数量取当前图片
+    fn log_session(&mut self, image: String, action: SessionAction, duration: u64) {
+        let Some(label_dir) = &self.label_dir else {
+            return;
+        };
+        let entry = SessionEntry {
+            timestamp: now_secs(),
+            image,
+            action,
+            duration,
+            boxes: self.bounding_boxes.len(),
+            annotator: self.project.annotator_name(),
+        };
+        if let Err(e) = session::append_entry(label_dir, &entry) {
+            self.show_status(&e);
+        }
+    }
+
+    /// 结束当前图片的停留并写入会话日志
+    pub fn finish_visit(&mut self) {
+        let Some(visit) = self.visit.take() else {
+            return;
+        };
+        let duration = visit.duration();
+        if visit.edited {
+            self.log_session(visit.image, SessionAction::Edited, duration);
+        } else if duration > 0 {
+            self.log_session(visit.image, SessionAction::Viewed, duration);
+        }
+    }
 
-            // 当前图片中的标注类型数量
-            for bbox in &self.bounding_boxes {
-                *stats.current_class_counts.entry(bbox.class).or_insert(0) += 1;
-            }
+    /// 重新读取会话日志并汇总标注进度，当前图片的停留先写入日志再重新计时
+    pub fn refresh_progress(&mut self) {
+        self.finish_visit();
+        self.visit = self.current_image_name.clone().map(ImageVisit::new);
+        self.progress_log = self
+            .label_dir
+            .as_ref()
+            .map(|dir| session::load_log(dir))
+            .unwrap_or_default();
+        self.update_progress_report();
+    }
 
-            // 保持总体统计不变
-            stats.total_class_counts = self.statistics.total_class_counts.clone();
+    /// 按选中的标注员重新汇总已读取的日志
+    pub fn update_progress_report(&mut self) {
+        self.progress_report =
+            ProgressReport::from_entries(&self.progress_log, self.progress_annotator.as_deref());
+    }
 
-            self.statistics = stats;
+    /// 在后台统计文件列表中所有图片的框尺寸、宽高比、位置等分布
+    pub fn start_dataset_stats(&mut self) {
+        if self.dataset_stats_job.is_some() {
+            return;
         }
+        let Some(label_dir) = self.label_dir.clone() else {
+            self.show_status("请先选择标签目录");
+            return;
+        };
+        // 先保存当前图片，统计包含未保存的修改
+        self.save_annotations();
+        self.dataset_stats_job = Some(stats::spawn_stats_job(
+            self.cached_image_files.clone(),
+            label_dir,
+            self.project.label_layout(),
+            true,
+        ));
+    }
 
-    pub fn update_total_statistics(&mut self) {
-        let mut stats = self.statistics.clone();
-        stats.total_class_counts.clear();
+    pub fn cancel_dataset_stats(&mut self) {
+        if let Some(job) = &self.dataset_stats_job
+            && let Ok(mut progress) = job.lock()
+        {
+            progress.cancelled = true;
+        }
+    }
 
-        // 统计所有图片中的标注类型数量
-        if let Some(label_dir) = &self.label_dir {
-            for image_path in &self.cached_image_files {
-                let label_path = label_dir
-                    .join(image_path.file_stem().unwrap())
-                    .with_extension("txt");
-
-                if let Ok(file) = File::open(label_path) {
-                    let reader = BufReader::new(file);
-                    for line in reader.lines() {
-                        if let Ok(line) = line {
-                            let parts: Vec<f32> = line
-                                .split_whitespace()
-                                .map(|s| s.parse().unwrap_or(0.0))
-                                .collect();
-
-                            if parts.len() >= 1 {
-                                let class = parts[0] as i32;
-                                *stats.total_class_counts.entry(class).or_insert(0) += 1;
-                            }
-                        }
-                    }
-                }
-            }
+    pub fn poll_dataset_stats(&mut self) {
+        let Some(job) = self.dataset_stats_job.clone() else {
+            return;
+        };
+        let Ok(mut progress) = job.lock() else {
+            self.dataset_stats_job = None;
+            return;
+        };
+        if !progress.finished {
+            return;
         }
-        self.statistics = stats;
+        if let Some(result) = progress.result.take() {
+            self.dataset_stats = Some(result);
+        }
+        drop(progress);
+        self.dataset_stats_job = None;
     }
 
-    pub fn on_exit(&mut self) {
-        self.save_modified_records();
-    }
-
-    pub fn export_modified_files(&mut self, export_dir: PathBuf) -> Result<i32, String> {
-        // 检查是否有已修改的文件
-        if self.modified_images.is_empty() {
-            return Err("没有已修改的文件可导出".to_string());
-        }
-        
-        // 检查源目录是否存在
-        if self.image_dir.is_none() || self.label_dir.is_none() {
-            return Err("请先选择图片和标签目录".to_string());
-        }
-        
-        let image_dir = self.image_dir.as_ref().unwrap();
-        let label_dir = self.label_dir.as_ref().unwrap();
-        
-        // 创建导出目录结构
-        let images_dir = export_dir.join("images");
-        let labels_dir = export_dir.join("labels");
-        
-        // 检查目标目录是否为空
-        let images_empty = if images_dir.exists() {
-            fs::read_dir(&images_dir).map(|entries| entries.count() == 0).unwrap_or(true)
-        } else {
-            true
+    pub fn export_dataset_stats_csv(&self, path: &Path) -> Result<(), String> {
+        let stats = self.dataset_stats.as_ref().ok_or("请先完成统计")?;
+        fs::write(path, stats.to_csv(&self.project)).map_err(|e| format!("写入 CSV 失败: {}", e))
+    }
+
+    /// 用最近一次分布统计的结果生成 HTML 数据集报告
+    pub fn export_dataset_report(&self, path: &Path) -> Result<(), String> {
+        let stats = self.dataset_stats.as_ref().ok_or("请先完成统计")?;
+        let label_dir = self.label_dir.as_ref().ok_or("请先选择标签目录")?;
+        let html = report::html_report(
+            stats,
+            &self.cached_image_files,
+            label_dir,
+            &self.project,
+            &self.image_records,
+        );
+        fs::write(path, html).map_err(|e| format!("写入报告失败: {}", e))
+    }
+
+    /// 在后台计算文件列表中所有图片的感知哈希，未变化的图片使用标签目录下的缓存
+    pub fn start_hash_job(&mut self) {
+        if self.dedup_job.is_some() {
+            return;
+        }
+        let Some(label_dir) = &self.label_dir else {
+            self.show_status("请先选择标签目录");
+            return;
+        };
+        if self.cached_image_files.is_empty() {
+            self.show_status("当前文件列表为空");
+            return;
+        }
+        self.dedup_job = Some(dedup::spawn_hash_job(
+            self.cached_image_files.clone(),
+            dedup::load_cache(label_dir),
+        ));
+    }
+
+    pub fn cancel_hash_job(&mut self) {
+        if let Some(job) = &self.dedup_job
+            && let Ok(mut progress) = job.lock()
+        {
+            progress.cancelled = true;
+        }
+    }
+
+    /// 后台计算结束后写回缓存并重新分组
+    pub fn poll_hash_job(&mut self) {
+        let Some(job) = self.dedup_job.clone() else {
+            return;
         };
-        
-        let labels_empty = if labels_dir.exists() {
-            fs::read_dir(&labels_dir).map(|entries| entries.count() == 0).unwrap_or(true)
+        let Ok(mut progress) = job.lock() else {
+            self.dedup_job = None;
+            return;
+        };
+        if !progress.finished {
+            return;
+        }
+        let cancelled = progress.cancelled;
+        let computed = std::mem::take(&mut progress.cache);
+        let errors = progress.errors.len();
+        drop(progress);
+        self.dedup_job = None;
+
+        let Some(label_dir) = self.label_dir.clone() else {
+            return;
+        };
+        // 取消时保留尚未处理到的旧缓存
+        let cache = if cancelled {
+            let mut cache = dedup::load_cache(&label_dir);
+            cache.extend(computed);
+            cache
         } else {
-            true
+            computed
         };
-        
-        // 如果目标目录不为空，返回错误
-        if !images_empty || !labels_empty {
-            return Err("目标目录不为空，请选择空目录或新目录".to_string());
+        if let Err(e) = dedup::save_cache(&label_dir, &cache) {
+            self.show_status(&e);
         }
-        
-        // 创建目录（如果不存在）
-        if !images_dir.exists() {
-            fs::create_dir_all(&images_dir).map_err(|e| format!("创建图片目录失败: {}", e))?;
+        let names: HashSet<String> = self
+            .cached_image_files
+            .iter()
+            .filter_map(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+            .collect();
+        self.dedup_hashes = cache
+            .into_iter()
+            .filter(|(name, _)| names.contains(name))
+            .map(|(name, cached)| (name, cached.hashes))
+            .collect();
+        self.regroup_duplicates();
+        let message = format!(
+            "已计算 {} 张图片的哈希{}",
+            self.dedup_hashes.len(),
+            if errors > 0 {
+                format!("，{} 张失败", errors)
+            } else {
+                String::new()
+            }
+        );
+        self.show_status(&message);
+    }
+
+    /// 按当前算法和阈值在后台重新分组，取消尚未完成的上一次分组
+    pub fn regroup_duplicates(&mut self) {
+        if let Some(job) = &self.dedup_group_job
+            && let Ok(mut progress) = job.lock()
+        {
+            progress.cancelled = true;
         }
-        
-        if !labels_dir.exists() {
-            fs::create_dir_all(&labels_dir).map_err(|e| format!("创建标签目录失败: {}", e))?;
+        let config = &self.project.dedup;
+        self.dedup_group_job = Some(dedup::spawn_group_job(
+            self.dedup_hashes.clone(),
+            config.kind,
+            config.threshold,
+        ));
+    }
+
+    /// 后台分组结束后替换分组结果
+    pub fn poll_group_job(&mut self) {
+        let Some(job) = &self.dedup_group_job else {
+            return;
+        };
+        let groups = match job.lock() {
+            Ok(mut progress) if progress.finished => std::mem::take(&mut progress.groups),
+            Ok(_) => return,
+            Err(_) => Vec::new(),
+        };
+        self.dedup_group_job = None;
+        self.dedup_groups = groups;
+        if self
+            .dedup_selected
+            .is_some_and(|index| index >= self.dedup_groups.len())
+        {
+            self.dedup_selected = None;
+        }
+        self.dedup_compare = (0, 1);
+    }
+
+    /// 对比视图用的缩略图，首次使用时加载
+    pub fn dedup_texture(&mut self, ctx: &egui::Context, name: &str) -> Option<egui::TextureHandle> {
+        if let Some(texture) = self.dedup_textures.get(name) {
+            return Some(texture.clone());
+        }
+        let path = self.image_dir.as_ref()?.join(name);
+        let image = resize_to_limit(&image::open(path).ok()?, 480, 480).to_rgb8();
+        let texture = ctx.load_texture(
+            format!("dedup_{}", name),
+            egui::ColorImage::from_rgb(
+                [image.width() as usize, image.height() as usize],
+                image.as_raw(),
+            ),
+            Default::default(),
+        );
+        self.dedup_textures.insert(name.to_string(), texture.clone());
+        Some(texture)
+    }
+
+    /// 将近似重复的图片标记为「跳过」
+    pub fn mark_duplicates_skipped(&mut self, names: &[String]) {
+        let now = now_secs();
+        for name in names {
+            let record = self.image_records.entry(name.clone()).or_default();
+            record.status = ImageStatus::Skipped;
+            record.updated_at = now;
+        }
+        self.save_image_records();
+        self.update_statistics();
+        self.show_status(&format!("已将 {} 张图片标记为「跳过」", names.len()));
+    }
+
+    /// 删除一张近似重复的图片及其标签，并从分组中移除
+    pub fn delete_duplicate(&mut self, name: &str) {
+        let Some(path) = self
+            .cached_image_files
+            .iter()
+            .find(|p| p.file_name().is_some_and(|n| n.to_string_lossy() == name))
+            .cloned()
+        else {
+            return;
+        };
+        if let Err(e) = self.delete_image_files(&path) {
+            self.show_status(&e);
+            return;
+        }
+        self.dedup_hashes.remove(name);
+        self.dedup_textures.remove(name);
+        // 重新分组完成前先从现有分组中移除，避免对比视图引用已删除的图片
+        for group in &mut self.dedup_groups {
+            group.retain(|n| n != name);
+        }
+        self.dedup_groups.retain(|g| g.len() > 1);
+        if self
+            .dedup_selected
+            .is_some_and(|index| index >= self.dedup_groups.len())
+        {
+            self.dedup_selected = None;
         }
-        
-        // 导出已修改的文件
-        let mut exported_count = 0;
-        
-        for filename in &self.modified_images {
-            // 复制图片文件
-            let src_image_path = image_dir.join(filename);
-            let dst_image_path = images_dir.join(filename);
-            
-            if src_image_path.exists() {
-                fs::copy(&src_image_path, &dst_image_path)
-                    .map_err(|e| format!("复制图片文件失败 {}: {}", filename, e))?;
-            }
-            
-            // 复制标签文件
-            let label_filename = src_image_path.file_stem().unwrap().to_string_lossy().to_string() + ".txt";
-            let src_label_path = label_dir.join(&label_filename);
-            let dst_label_path = labels_dir.join(&label_filename);
-            
-            if src_label_path.exists() {
-                fs::copy(&src_label_path, &dst_label_path)
-                    .map_err(|e| format!("复制标签文件失败 {}: {}", label_filename, e))?;
-            }
-            
-            exported_count += 1;
-        }
-        
-        Ok(exported_count)
+        self.dedup_compare = (0, 1);
+        self.regroup_duplicates();
+        self.update_file_list();
+        if self.current_image_path.as_ref() == Some(&path) {
+            self.history.retain(|p| p != &path);
+            if let Some(next_path) = self.cached_image_files.first().cloned() {
+                self.load_image(&next_path);
+            }
+        }
+        self.show_status(&format!("已删除 {}", name));
+    }
+
+    /// 删除图片及其标签、属性文件，并移除状态记录和缓存（不刷新文件列表）
+    pub fn delete_image_files(&mut self, image_path: &Path) -> Result<(), String> {
+        let label_dir = self.label_dir.as_ref().ok_or("请先选择标签目录")?;
+        let label_path = labels::label_path(label_dir, image_path);
+        if label_path.exists() {
+            let boxes = labels::read_label_file(&label_path, &self.project.label_layout());
+            fs::remove_file(&label_path).map_err(|e| format!("删除标签文件失败: {}", e))?;
+            self.statistics.remove_boxes(&boxes);
+            if self.totals_job.is_some() {
+                self.totals_dirty = true;
+            }
+        }
+        if self.current_image_path.as_deref() == Some(image_path) {
+            self.saved_boxes.clear();
+        }
+        let _ = fs::remove_file(labels::attributes_path(&label_path));
+        fs::remove_file(image_path).map_err(|e| format!("删除图片文件失败: {}", e))?;
+
+        if let Some(name) = image_path.file_name().map(|n| n.to_string_lossy().to_string()) {
+            self.image_records.remove(&name);
+        }
+        self.image_cache.remove(image_path);
+        self.save_image_records();
+        Ok(())
+    }
+
+    /// 按合并窗口中的项目列表合并到新的图片目录和标签目录
+    pub fn merge_projects(&mut self) -> Result<MergeReport, String> {
+        let image_out = self.merge_image_out.clone().ok_or("请先选择合并后的图片目录")?;
+        let label_out = self.merge_label_out.clone().ok_or("请先选择合并后的标签目录")?;
+        merge::merge(
+            &self.merge_sources,
+            &image_out,
+            &label_out,
+            self.merge_skip_duplicates,
+        )
+    }
+
+    /// 打开合并后的图片目录和标签目录
+    pub fn open_merged_dirs(&mut self) {
+        let (Some(image_dir), Some(label_dir)) =
+            (self.merge_image_out.clone(), self.merge_label_out.clone())
+        else {
+            return;
+        };
+        self.finish_visit();
+        self.label_dir = Some(label_dir);
+        self.load_project();
+        self.load_image_records();
+        self.image_dir = Some(image_dir);
+        self.update_file_list();
+        if let Some(path) = self.cached_image_files.first().cloned() {
+            self.load_image(&path);
+        }
+        self.show_status("已打开合并后的目录");
+    }
+    pub fn update_statistics(&mut self) {
+            let mut stats = Statistics {
+                // 统计总图片数
+                total_images: self.cached_image_files.len(),
+                ..Default::default()
+            };
+            for path in &self.cached_image_files {
+                if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+                    let status = self.image_status(name);
+                    *stats.status_counts.entry(status).or_insert(0) += 1;
+                    if status.is_done() {
+                        stats.completed_images += 1;
+                    }
+                }
+            }
+
+            // 当前图片中的标注类型数量
+            for bbox in &self.bounding_boxes {
+                *stats.current_class_counts.entry(bbox.class).or_insert(0) += 1;
+                *stats.current_shape_counts.entry(bbox.kind()).or_insert(0) += 1;
+            }
+
+            // 保持总体统计不变
+            stats.total_class_counts = self.statistics.total_class_counts.clone();
+            stats.total_shape_counts = self.statistics.total_shape_counts.clone();
+
+            self.statistics = stats;
+        }
+
+    /// 在后台重新统计所有图片的类别和形状数量。保存标注时总体统计已增量更新，
+    /// 只在打开目录、批量修改标签文件后或用户手动要求时调用
+    pub fn update_total_statistics(&mut self) {
+        if self.totals_job.is_some() {
+            // 正在统计时文件可能已被修改，结束后再统计一次
+            self.totals_dirty = true;
+            return;
+        }
+        let Some(label_dir) = self.label_dir.clone() else {
+            self.statistics.total_class_counts.clear();
+            self.statistics.total_shape_counts.clear();
+            return;
+        };
+        self.totals_dirty = false;
+        self.totals_job = Some(stats::spawn_stats_job(
+            self.cached_image_files.clone(),
+            label_dir,
+            self.project.label_layout(),
+            false,
+        ));
+    }
+
+    /// 每帧检查总体统计的后台任务，完成后替换增量维护的结果
+    pub fn poll_total_statistics(&mut self) {
+        let Some(job) = &self.totals_job else {
+            return;
+        };
+        let result = {
+            let Ok(mut progress) = job.lock() else {
+                return;
+            };
+            if !progress.finished {
+                return;
+            }
+            progress.result.take()
+        };
+        self.totals_job = None;
+        if let Some(result) = result {
+            self.statistics.total_class_counts = result.class_boxes.into_iter().collect();
+            self.statistics.total_shape_counts = result.shape_counts;
+        }
+        // 统计期间保存或删除过标签时结果可能不准确，再统计一次
+        if self.totals_dirty {
+            self.update_total_statistics();
+        }
+    }
+
+    pub fn select_compare_dir(&mut self) {
+        if let Some(path) = rfd::FileDialog::new().pick_folder() {
+            self.compare_dir = Some(path);
+            self.dataset_diff = None;
+            self.load_compare_annotations();
+            self.show_status("已加载对比标签目录");
+        }
+    }
+
+    pub fn close_compare_dir(&mut self) {
+        self.compare_dir = None;
+        self.compare_boxes.clear();
+        self.box_diffs.clear();
+        self.dataset_diff = None;
+    }
+
+    pub fn load_compare_annotations(&mut self) {
+        self.compare_boxes.clear();
+        if let (Some(image_path), Some(compare_dir)) = (&self.current_image_path, &self.compare_dir)
+        {
+            self.compare_boxes = labels::read_label_file(
+                &labels::label_path(compare_dir, image_path),
+                &self.project.label_layout(),
+            );
+        }
+        self.update_diff();
+    }
+
+    pub fn update_diff(&mut self) {
+        self.box_diffs = if self.compare_dir.is_some() {
+            diff::diff_boxes(
+                &self.bounding_boxes,
+                &self.compare_boxes,
+                self.diff_iou_threshold,
+            )
+        } else {
+            Vec::new()
+        };
+    }
+
+    /// 统计整个数据集两个标签目录之间的差异
+    pub fn update_dataset_diff(&mut self) {
+        let (Some(label_dir), Some(compare_dir)) = (&self.label_dir, &self.compare_dir) else {
+            return;
+        };
+        let mut summary = DiffSummary::default();
+        let layout = self.project.label_layout();
+        for image_path in &self.cached_image_files {
+            let ours = labels::read_label_file(&labels::label_path(label_dir, image_path), &layout);
+            let theirs =
+                labels::read_label_file(&labels::label_path(compare_dir, image_path), &layout);
+            summary.add_image(&diff::diff_boxes(&ours, &theirs, self.diff_iou_threshold));
+        }
+        self.dataset_diff = Some(summary);
+    }
+
+    /// 采用对比目录一侧的结果
+    pub fn accept_diff(&mut self, diff: &BoxDiff) {
+        match (diff.kind, diff.ours, diff.theirs) {
+            (DiffKind::Added, _, Some(theirs)) => {
+                self.bounding_boxes.push(self.compare_boxes[theirs].clone());
+            }
+            (DiffKind::Moved | DiffKind::Reclassified, Some(ours), Some(theirs)) => {
+                self.bounding_boxes[ours] = self.compare_boxes[theirs].clone();
+            }
+            (DiffKind::Removed, Some(ours), _) => {
+                self.bounding_boxes.remove(ours);
+                self.selected_box = None;
+                self.remove_comment_boxes(&[ours]);
+            }
+            _ => return,
+        }
+        self.save_annotations();
+        self.show_status("已采用对比目录的结果");
+    }
+
+    pub fn accept_all_diffs(&mut self) {
+        let removed: Vec<usize> = (0..self.bounding_boxes.len()).collect();
+        self.remove_comment_boxes(&removed);
+        self.bounding_boxes = self.compare_boxes.clone();
+        self.selected_box = None;
+        self.save_annotations();
+        self.show_status("已采用对比目录的全部标注");
+    }
+
+    /// 以标签目录为真值、对比目录为预测结果计算检测指标
+    pub fn run_evaluation(&mut self) {
+        if self.cached_image_files.is_empty() {
+            self.update_file_list();
+        }
+        let (Some(label_dir), Some(compare_dir)) = (&self.label_dir, &self.compare_dir) else {
+            self.show_status("请先选择标签目录和对比目录");
+            return;
+        };
+        self.eval_report = Some(metrics::evaluate(
+            &self.cached_image_files,
+            label_dir,
+            compare_dir,
+            self.metrics_conf_threshold,
+            &self.project.label_layout(),
+        ));
+        self.show_status("已完成指标计算");
+    }
+
+    /// 闭合正在绘制的多边形并添加为新标注
+    pub fn finish_polygon(&mut self) {
+        if self.polygon_draft.len() < 3 {
+            self.show_status("多边形至少需要三个顶点");
+            return;
+        }
+        let points = std::mem::take(&mut self.polygon_draft);
+        self.bounding_boxes
+            .push(BoundingBox::from_shape(self.selected_class, Shape::Polygon(points)));
+        self.save_annotations();
+        self.show_status("已添加多边形");
+    }
+
+    /// 类别的关键点模板，未定义或为空时返回 None
+    pub fn keypoint_schema(&self, class: i32) -> Option<&KeypointSchema> {
+        self.project
+            .keypoint_schemas
+            .get(&class)
+            .filter(|schema| !schema.names.is_empty())
+    }
+
+    /// 在选中框上放置当前待放置的关键点，然后切换到下一个未标注的关键点
+    pub fn place_keypoint(&mut self, x: f64, y: f64) {
+        let (Some(index), Some(selected)) = (self.active_keypoint, self.selected_box) else {
+            return;
+        };
+        let Some(class) = self.bounding_boxes.get(selected).map(|b| b.class) else {
+            return;
+        };
+        let Some(count) = self.keypoint_schema(class).map(|s| s.names.len()) else {
+            self.active_keypoint = None;
+            self.show_status("该类别没有关键点模板");
+            return;
+        };
+        if index >= count {
+            self.active_keypoint = None;
+            return;
+        }
+        let bbox = &mut self.bounding_boxes[selected];
+        bbox.keypoints.resize(count, Keypoint::default());
+        bbox.keypoints[index] = Keypoint {
+            x: x.clamp(0.0, 1.0),
+            y: y.clamp(0.0, 1.0),
+            visibility: 2,
+        };
+        self.active_keypoint = (index + 1..count).find(|&i| !bbox.keypoints[i].is_labeled());
+        self.save_annotations();
+        self.show_status(match self.active_keypoint {
+            Some(_) => "已放置关键点",
+            None => "关键点已全部放置",
+        });
+    }
+
+    /// 切换选中框关键点的可见/遮挡状态
+    pub fn toggle_keypoint_visibility(&mut self, index: usize) {
+        let Some(kp) = self
+            .selected_box
+            .and_then(|idx| self.bounding_boxes.get_mut(idx))
+            .and_then(|bbox| bbox.keypoints.get_mut(index))
+            .filter(|kp| kp.is_labeled())
+        else {
+            self.show_status("该关键点尚未标注");
+            return;
+        };
+        kp.visibility = if kp.visibility == 2 { 1 } else { 2 };
+        let visible = kp.visibility == 2;
+        self.save_annotations();
+        self.show_status(if visible {
+            "关键点已设为可见"
+        } else {
+            "关键点已设为遮挡"
+        });
+    }
+
+    /// 清除选中框的某个关键点（变为未标注）
+    pub fn clear_keypoint(&mut self, index: usize) {
+        if let Some(kp) = self
+            .selected_box
+            .and_then(|idx| self.bounding_boxes.get_mut(idx))
+            .and_then(|bbox| bbox.keypoints.get_mut(index))
+        {
+            *kp = Keypoint::default();
+            self.save_annotations();
+            self.show_status("已清除关键点");
+        }
+    }
+
+    /// 保存编辑窗口中的关键点模板，关键点名称为空时删除该类别的模板
+    pub fn apply_keypoint_schema(&mut self) -> Result<(), String> {
+        let class = self.keypoint_schema_draft.class;
+        let schema = self.keypoint_schema_draft.parse()?;
+        if schema.names.is_empty() {
+            self.project.keypoint_schemas.remove(&class);
+        } else {
+            self.project.keypoint_schemas.insert(class, schema);
+        }
+        self.save_project();
+        // 解析布局随模板变化，重新读取标签
+        self.load_annotations();
+        self.update_total_statistics();
+        Ok(())
+    }
+
+    /// 将选中的框转换为旋转框或水平框
+    pub fn convert_selected_shape(&mut self, oriented: bool) {
+        let Some(bbox) = self
+            .selected_box
+            .and_then(|idx| self.bounding_boxes.get_mut(idx))
+        else {
+            self.show_status("请先选中边界框");
+            return;
+        };
+        if !bbox.can_convert_shape(oriented) {
+            let message = if bbox.keypoints.is_empty() {
+                "多边形不能转换为水平框或旋转框"
+            } else {
+                "带关键点的框不能转换为旋转框"
+            };
+            self.show_status(message);
+            return;
+        }
+        if oriented {
+            bbox.make_oriented();
+        } else {
+            bbox.make_axis_aligned();
+        }
+        self.save_annotations();
+        self.show_status(if oriented {
+            "已转换为旋转框"
+        } else {
+            "已转换为水平框"
+        });
+    }
+
+    /// 将当前图片中的水平框/旋转框转换为目标形状，多边形和带关键点的框保持不变
+    pub fn convert_current_shapes(&mut self, oriented: bool) {
+        let mut skipped = 0;
+        for bbox in &mut self.bounding_boxes {
+            if !bbox.can_convert_shape(oriented) {
+                skipped += 1;
+            } else if oriented {
+                bbox.make_oriented();
+            } else {
+                bbox.make_axis_aligned();
+            }
+        }
+        self.save_annotations();
+        if skipped > 0 {
+            self.show_status(&format!(
+                "已转换当前图片的框，{} 个多边形或带关键点的框保持不变",
+                skipped
+            ));
+        } else {
+            self.show_status("已转换当前图片的所有框");
+        }
+    }
+
+    /// 将标签目录下所有图片的水平框/旋转框转换为目标形状，多边形和带关键点的框保持不变
+    pub fn convert_dataset_shapes(
+        &mut self,
+        oriented: bool,
+    ) -> Result<ShapeConversionReport, String> {
+        let label_dir = self.label_dir.clone().ok_or("请先选择标签目录")?;
+        if self.cached_image_files.is_empty() {
+            self.update_file_list();
+        }
+        // 先保存当前图片，避免转换结果被内存中的旧数据覆盖
+        self.save_annotations();
+        let report = labels::convert_label_dir(
+            &self.cached_image_files,
+            &label_dir,
+            &self.project.label_layout(),
+            oriented,
+        );
+        self.load_annotations();
+        self.update_total_statistics();
+        Ok(report)
+    }
+
+    /// 获取自动标注模型，尚未加载时按项目设置加载
+    pub fn ensure_detector(&mut self) -> Result<Arc<Detector>, String> {
+        if let Some(detector) = &self.detector {
+            return Ok(detector.clone());
+        }
+        if self.project.auto_label.model_path.is_empty() {
+            return Err("请先选择模型文件".to_string());
+        }
+        let detector = Arc::new(Detector::load(&self.project.auto_label)?);
+        self.detector = Some(detector.clone());
+        Ok(detector)
+    }
+
+    fn class_mapping(&self, detector: &Detector) -> ClassMapping {
+        ClassMapping::new(
+            &self.project.auto_label,
+            &detector.class_names,
+            &self.project,
+        )
+    }
+
+    /// 在后台对当前图片运行模型，替换未确认的预标注框
+    pub fn auto_label_current(&mut self) {
+        let Some(image_path) = self.current_image_path.clone() else {
+            self.show_status("请先打开图片");
+            return;
+        };
+        self.start_auto_label(vec![image_path], true);
+    }
+
+    /// 在后台批量标注所有"未开始"和"已预标注"的图片
+    pub fn auto_label_folder(&mut self) {
+        if self.cached_image_files.is_empty() {
+            self.update_file_list();
+        }
+        let images: Vec<PathBuf> = self
+            .cached_image_files
+            .iter()
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|name| {
+                        matches!(
+                            self.image_status(name),
+                            ImageStatus::Todo | ImageStatus::PreLabeled
+                        )
+                    })
+            })
+            .cloned()
+            .collect();
+        if images.is_empty() {
+            self.show_status("没有需要自动标注的图片");
+            return;
+        }
+        self.start_auto_label(images, false);
+    }
+
+    fn start_auto_label(&mut self, images: Vec<PathBuf>, single: bool) {
+        if self.auto_label_job.is_some() {
+            self.show_status("自动标注正在进行中");
+            return;
+        }
+        let Some(label_dir) = self.label_dir.clone() else {
+            self.show_status("请先选择标签目录");
+            return;
+        };
+        let detector = match self.ensure_detector() {
+            Ok(detector) => detector,
+            Err(e) => {
+                self.show_status(&e);
+                return;
+            }
+        };
+        let mapping = self.class_mapping(&detector);
+        self.auto_label_job = Some(autolabel::spawn_job(
+            detector,
+            mapping,
+            self.project.auto_label.clone(),
+            images,
+            label_dir,
+            single,
+        ));
+    }
+
+    pub fn cancel_auto_label(&mut self) {
+        if let Some(job) = &self.auto_label_job
+            && let Ok(mut progress) = job.lock()
+        {
+            progress.cancelled = true;
+        }
+    }
+
+    /// 在主线程写入后台标注的结果：当前图片在内存中合并，其他图片合并到标签文件；
+    /// 批量标注时跳过期间已被人工处理的图片
+    pub fn poll_auto_label(&mut self) {
+        let Some(job) = self.auto_label_job.clone() else {
+            return;
+        };
+        let Ok(mut progress) = job.lock() else {
+            self.auto_label_job = None;
+            return;
+        };
+        // 结束标志与结果同时取出，结束后不会再有新的结果
+        let results = std::mem::take(&mut progress.results);
+        let label_dir = progress.label_dir.clone();
+        let (single, finished) = (progress.single, progress.finished);
+        drop(progress);
+
+        let layout = self.project.label_layout();
+        let mut skipped = 0;
+        let mut errors = Vec::new();
+        for (path, predictions) in results {
+            let name = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or_default()
+                .to_string();
+            let unreviewed = matches!(
+                self.image_status(&name),
+                ImageStatus::Todo | ImageStatus::PreLabeled
+            );
+            if !single && !unreviewed {
+                skipped += 1;
+                continue;
+            }
+            let is_current = self.current_image_path.as_ref() == Some(&path)
+                && self.label_dir.as_ref() == Some(&label_dir);
+            let replaced = if is_current {
+                let existing = std::mem::take(&mut self.bounding_boxes);
+                let replaced = autolabel::replaced_indices(&existing);
+                self.bounding_boxes = autolabel::merge_predictions(existing, predictions);
+                self.selected_box = None;
+                self.save_annotations();
+                replaced
+            } else {
+                match autolabel::write_predictions(&path, predictions, &label_dir, &layout) {
+                    Ok(replaced) => replaced,
+                    Err(e) => {
+                        errors.push(format!("{}: {}", name, e));
+                        continue;
+                    }
+                }
+            };
+            if let Some(record) = self.image_records.get_mut(&name) {
+                record.remove_comment_boxes(&replaced);
+            }
+            // 未人工处理过的图片标记为"已预标注"，等待人工检查
+            if unreviewed {
+                let record = self.image_records.entry(name).or_default();
+                record.status = ImageStatus::PreLabeled;
+                record.updated_at = now_secs();
+            }
+        }
+        let Ok(mut progress) = job.lock() else {
+            self.auto_label_job = None;
+            return;
+        };
+        progress.skipped += skipped;
+        progress.errors.append(&mut errors);
+        if !finished {
+            return;
+        }
+
+        let message = if single {
+            match progress.errors.first() {
+                Some(e) => e.clone(),
+                None => format!("自动标注完成，共 {} 个框", progress.boxes),
+            }
+        } else {
+            let mut message = format!(
+                "批量标注完成：{} 张图片，共 {} 个框",
+                progress.processed, progress.boxes
+            );
+            if progress.skipped > 0 {
+                message.push_str(&format!(
+                    "，{} 张已被人工处理而跳过",
+                    progress.skipped
+                ));
+            }
+            if !progress.errors.is_empty() {
+                message.push_str(&format!("，{} 张失败", progress.errors.len()));
+            }
+            message
+        };
+        drop(progress);
+        self.auto_label_job = None;
+        self.save_image_records();
+        self.update_statistics();
+        self.update_total_statistics();
+        self.show_status(&message);
+    }
+
+    pub fn on_exit(&mut self) {
+        self.finish_visit();
+        self.save_image_records();
+        self.save_project();
+    }
+
+    pub fn load_project(&mut self) {
+        if let Some(label_dir) = self.label_dir.clone() {
+            match ProjectConfig::load(&label_dir) {
+                Ok(project) => {
+                    self.project = project;
+                    self.project_load_error = None;
+                }
+                Err(e) => {
+                    self.project = ProjectConfig::default();
+                    self.show_status(&format!("{}，修复该文件前不会保存项目配置", e));
+                    self.project_load_error = Some(e);
+                }
+            }
+        }
+        self.rebuild_keymap();
+    }
+
+    /// 默认快捷键 <- 用户配置 <- 项目配置
+    pub fn rebuild_keymap(&mut self) {
+        self.keymap = Keymap::with_overrides(&[&self.user_keymap, &self.project.keymap]);
+    }
+
+    pub fn save_keymap_to_user(&mut self) {
+        self.user_keymap = self.keymap.overrides();
+        match keymap::save_user_overrides(&self.user_keymap) {
+            Ok(()) => self.show_status("已保存到用户快捷键配置"),
+            Err(e) => self.show_status(&e),
+        }
+    }
+
+    pub fn save_keymap_to_project(&mut self) {
+        if self.label_dir.is_none() {
+            self.show_status("请先选择标签目录");
+            return;
+        }
+        self.project.keymap = self.keymap.overrides();
+        self.save_project();
+        self.show_status("已保存到项目快捷键配置");
+    }
+
+    pub fn save_project(&mut self) {
+        if let Some(e) = &self.project_load_error {
+            self.show_status(&format!("{}，未保存项目配置", e));
+            return;
+        }
+        if let Some(label_dir) = &self.label_dir
+            && let Err(e) = self.project.save(label_dir)
+        {
+            self.show_status(&e);
+        }
+    }
+
+    /// 类别颜色：优先使用项目中保存的自定义颜色，否则使用自动配色
+    pub fn class_color(&self, class: i32) -> egui::Color32 {
+        self.project.class_color(class)
+    }
+
+    pub fn is_class_visible(&self, class: i32) -> bool {
+        !self.hidden_classes.contains(&class)
+    }
+
+    /// 边界框是否在画布上显示（类别可见且未因低置信度被隐藏）
+    pub fn is_box_visible(&self, bbox: &BoundingBox) -> bool {
+        self.is_class_visible(bbox.class)
+            && !(self.hide_low_confidence && bbox.is_low_confidence(self.confidence_threshold))
+    }
+
+    pub fn low_confidence_count(&self) -> usize {
+        self.bounding_boxes
+            .iter()
+            .filter(|b| b.is_low_confidence(self.confidence_threshold))
+            .count()
+    }
+
+    /// 将选中的预标注框确认为真值，保存时不再写出置信度
+    pub fn promote_selected_box(&mut self) {
+        let Some(bbox) = self
+            .selected_box
+            .and_then(|idx| self.bounding_boxes.get_mut(idx))
+        else {
+            self.show_status("请先选中边界框");
+            return;
+        };
+        if bbox.confidence.take().is_none() {
+            self.show_status("选中的边界框已是真值");
+            return;
+        }
+        self.save_annotations();
+        self.show_status("已将选中的边界框确认为真值");
+    }
+
+    /// 将当前图片中所有预标注框确认为真值
+    pub fn promote_all_boxes(&mut self) {
+        let mut count = 0;
+        for bbox in &mut self.bounding_boxes {
+            if bbox.confidence.take().is_some() {
+                count += 1;
+            }
+        }
+        if count == 0 {
+            self.show_status("当前图片没有预标注框");
+            return;
+        }
+        self.save_annotations();
+        self.show_status(&format!("已将 {} 个预标注框确认为真值", count));
+    }
+
+    /// 按低置信度框数量从多到少依次跳转图片，重复调用时跳到排名中的下一张
+    pub fn switch_to_low_confidence_image(&mut self) {
+        if self.cached_image_files.is_empty() {
+            self.update_file_list();
+        }
+        let Some(label_dir) = &self.label_dir else {
+            self.show_status("请先选择标签目录");
+            return;
+        };
+
+        let layout = self.project.label_layout();
+        let mut ranking: Vec<(PathBuf, usize)> = self
+            .cached_image_files
+            .iter()
+            .map(|path| {
+                let label_path = labels::label_path(label_dir, path);
+                let count = labels::read_label_file(&label_path, &layout)
+                    .iter()
+                    .filter(|b| b.is_low_confidence(self.confidence_threshold))
+                    .count();
+                (path.clone(), count)
+            })
+            .filter(|(_, count)| *count > 0)
+            .collect();
+        if ranking.is_empty() {
+            self.show_status("没有包含低置信度框的图片");
+            return;
+        }
+        // 稳定排序，数量相同时保持文件列表顺序
+        ranking.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+
+        let next = self
+            .current_image_path
+            .as_ref()
+            .and_then(|current| ranking.iter().position(|(path, _)| path == current))
+            .map_or(0, |pos| (pos + 1) % ranking.len());
+        let (path, count) = ranking.swap_remove(next);
+        self.load_image(&path);
+        self.scroll_to_current = true;
+        self.show_status(&format!("该图片有 {} 个低置信度框", count));
+    }
+
+    pub fn toggle_class_visibility(&mut self, class: i32) {
+        if !self.hidden_classes.remove(&class) {
+            self.hidden_classes.insert(class);
+            // 隐藏的类别不能保持选中状态
+            if let Some(idx) = self.selected_box
+                && self.bounding_boxes.get(idx).map(|b| b.class) == Some(class)
+            {
+                self.selected_box = None;
+            }
+        }
+    }
+
+    /// 图例中需要列出的类别：项目中已命名/配色的类别以及标注中出现过的类别
+    pub fn legend_classes(&self) -> Vec<i32> {
+        let mut classes: Vec<i32> = self
+            .project
+            .class_names
+            .keys()
+            .chain(self.project.class_colors.keys())
+            .chain(self.statistics.total_class_counts.keys())
+            .chain(self.bounding_boxes.iter().map(|b| &b.class))
+            .copied()
+            .collect();
+        classes.sort();
+        classes.dedup();
+        classes
+    }
+
+    /// 导出方案选择的图片
+    fn export_scope_images(&self, scope: ExportScope) -> Vec<PathBuf> {
+        let name_of = |path: &PathBuf| {
+            path.file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default()
+        };
+        match scope {
+            ExportScope::All => self.cached_image_files.clone(),
+            ExportScope::Modified => self
+                .cached_image_files
+                .iter()
+                .filter(|path| self.is_modified(&name_of(path)))
+                .cloned()
+                .collect(),
+            ExportScope::Status(status) => self
+                .cached_image_files
+                .iter()
+                .filter(|path| self.image_status(&name_of(path)) == status)
+                .cloned()
+                .collect(),
+            ExportScope::Filtered => self.filtered_image_files(),
+        }
+    }
+
+    /// 按选中的导出方案生成导出计划（不写入文件）
+    pub fn plan_export(&self) -> Result<Vec<PlannedFile>, String> {
+        let label_dir = self.label_dir.as_ref().ok_or("请先选择图片和标签目录")?;
+        let target = self.export_target.as_ref().ok_or("请先选择导出目录")?;
+        let profile = self
+            .project
+            .export_profiles
+            .get(self.export_profile_index)
+            .ok_or("请先选择导出方案")?;
+        let transform = self.label_transform();
+        let images = transform.filter_images(
+            self.export_scope_images(profile.scope),
+            label_dir,
+            &self.project.label_layout(),
+        );
+        if images.is_empty() {
+            return Err(format!("{}中没有图片可导出", profile.scope.label()));
+        }
+        let convert_labels = !transform.is_identity();
+        if convert_labels && profile.transfer != TransferMode::Copy {
+            return Err("导出为外接水平框或重映射类别时标签需要重新写出，只能使用复制方式".to_string());
+        }
+        export::profile::plan(profile, &images, label_dir, target, convert_labels)
+    }
+
+    /// 按选中的导出方案导出。单个文件失败时记录后继续，结束后更新导出目录下的导出清单
+    pub fn run_export(&mut self) -> Result<ExportReport, String> {
+        let planned = self.plan_export()?;
+        let out_dir = self.export_target.clone().ok_or("请先选择导出目录")?;
+        let profile = &self.project.export_profiles[self.export_profile_index];
+        let transfer = profile.transfer;
+        let mut manifest = ExportManifest::load(&out_dir);
+        manifest.profile = profile.name.clone();
+        manifest.exported_at = now_secs();
+
+        let mut report = ExportReport::default();
+        for file in &planned {
+            let relative = file
+                .target
+                .strip_prefix(&out_dir)
+                .unwrap_or(&file.target)
+                .to_string_lossy()
+                .replace('\\', "/");
+            let result = match file.action {
+                PlanAction::Skip => {
+                    report.skipped += 1;
+                    continue;
+                }
+                PlanAction::Unchanged => {
+                    report.unchanged += 1;
+                    Ok(())
+                }
+                PlanAction::Remove => {
+                    match fs::remove_file(&file.target) {
+                        Ok(()) => {
+                            if file.kind == FileKind::Label {
+                                let _ = fs::remove_file(labels::attributes_path(&file.target));
+                            }
+                            manifest.files.remove(&relative);
+                            report.removed += 1;
+                        }
+                        Err(e) => report.failures.push(format!("删除 {} 失败: {}", relative, e)),
+                    }
+                    continue;
+                }
+                PlanAction::Write | PlanAction::Overwrite | PlanAction::Renamed => {
+                    let result = match file.target.parent() {
+                        Some(dir) => fs::create_dir_all(dir)
+                            .map_err(|e| format!("创建导出目录失败: {}", e)),
+                        None => Ok(()),
+                    };
+                    result
+                        .and_then(|()| match file.kind {
+                            FileKind::Image => {
+                                export::profile::transfer_file(&file.source, &file.target, transfer)
+                                    .map_err(|e| format!("写出图片失败 {}: {}", relative, e))
+                            }
+                            FileKind::Label => {
+                                self.export_label_file(&file.source, &file.target, transfer)
+                            }
+                        })
+                        .inspect(|()| report.written += 1)
+                }
+            };
+            let entry = result.and_then(|()| {
+                let (size, sha1) = export::manifest::file_digest(&file.target)
+                    .map_err(|e| format!("读取 {} 失败: {}", relative, e))?;
+                Ok(ManifestEntry {
+                    kind: file.kind,
+                    source: file.source.clone(),
+                    size,
+                    sha1,
+                })
+            });
+            match entry {
+                Ok(entry) => {
+                    manifest.files.insert(relative, entry);
+                }
+                Err(e) => {
+                    // 失败的文件不计入清单，重新导出时会再次写出
+                    manifest.files.remove(&relative);
+                    report.failures.push(e);
+                }
+            }
+        }
+
+        manifest.save(&out_dir)?;
+        self.export_preview = None;
+        Ok(report)
+    }
+
+    /// 将左侧文件列表中图片的每个框裁剪为分类数据集
+    pub fn export_crops(&mut self, out_dir: &Path) -> Result<CropReport, String> {
+        let label_dir = self.label_dir.as_ref().ok_or("请先选择图片和标签目录")?;
+        let images = self.filtered_image_files();
+        if images.is_empty() {
+            return Err("当前文件列表为空".to_string());
+        }
+        // 裁剪只按框的外接矩形进行，这里只应用类别重映射
+        let transform = LabelTransform {
+            axis_aligned: false,
+            ..self.label_transform()
+        };
+        export::crops::export_crops(
+            &images,
+            label_dir,
+            &self.project.label_layout(),
+            &transform,
+            &self.project,
+            &self.project.crops,
+            out_dir,
+        )
+    }
+
+    /// 按选中导出方案的范围和内容导出为压缩包，返回图片数量。
+    /// 按划分导出时沿用并更新划分记录，与「划分数据集」一致
+    pub fn export_archive(&mut self, path: &Path) -> Result<usize, String> {
+        let label_dir = self.label_dir.clone().ok_or("请先选择图片和标签目录")?;
+        let profile = self
+            .project
+            .export_profiles
+            .get(self.export_profile_index)
+            .ok_or("请先选择导出方案")?;
+        let content = profile.content;
+        let transform = self.label_transform();
+        let images = transform.filter_images(
+            self.export_scope_images(profile.scope),
+            &label_dir,
+            &self.project.label_layout(),
+        );
+        if images.is_empty() {
+            return Err(format!("{}中没有图片可导出", profile.scope.label()));
+        }
+
+        let assignment = self.archive_split.then(|| {
+            let inputs = split::collect_inputs(&images, &label_dir, &self.project.label_layout());
+            split::assign(&inputs, &self.project.split, &self.split_record)
+        });
+        let options = ArchiveOptions {
+            format: self.archive_format,
+            content,
+            transform: &transform,
+            assignment: assignment.as_ref(),
+        };
+        let count = export::archive::write_archive(
+            path,
+            &images,
+            &label_dir,
+            &self.project,
+            &self.image_records,
+            &options,
+        )?;
+
+        if let Some(assignment) = assignment {
+            self.split_record.extend(assignment);
+            split::save_record(&label_dir, &self.split_record)?;
+        }
+        Ok(count)
+    }
+
+    /// 复制单个标签文件（及其属性文件），需要转换外接水平框或重映射类别时转换后写出
+    fn export_label_file(
+        &self,
+        src: &Path,
+        dst: &Path,
+        transfer: TransferMode,
+    ) -> Result<(), String> {
+        let label_filename = src.file_name().unwrap_or_default().to_string_lossy();
+        let transform = self.label_transform();
+        if !transform.is_identity() {
+            let layout = self.project.label_layout();
+            let mut boxes = labels::read_label_file(src, &layout);
+            transform.apply(&mut boxes);
+            labels::write_label_file(dst, &boxes, &layout)
+                .map_err(|e| format!("写入标签文件失败 {}: {}", label_filename, e))?;
+        } else {
+            export::profile::transfer_file(src, dst, transfer)
+                .map_err(|e| format!("写出标签文件失败 {}: {}", label_filename, e))?;
+            // 框属性文件随标签一起复制
+            let src_attributes = labels::attributes_path(src);
+            if src_attributes.exists() {
+                let dst_attributes = labels::attributes_path(dst);
+                export::profile::transfer_file(&src_attributes, &dst_attributes, transfer)
+                    .map_err(|e| format!("写出属性文件失败 {}: {}", label_filename, e))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 参与划分的图片（当前文件列表）及其包含的类别
+    fn split_inputs(&self) -> Result<(Vec<PathBuf>, Vec<SplitInput>), String> {
+        let label_dir = self.label_dir.as_ref().ok_or("请先选择标签目录")?;
+        let images = self.filtered_image_files();
+        if images.is_empty() {
+            return Err("当前文件列表为空".to_string());
+        }
+        let inputs = split::collect_inputs(&images, label_dir, &self.project.label_layout());
+        Ok((images, inputs))
+    }
+
+    /// 按当前设置计算划分并统计，不写入任何文件
+    pub fn preview_split(&mut self) {
+        match self.split_inputs() {
+            Ok((_, inputs)) => {
+                let assignment = split::assign(&inputs, &self.project.split, &self.split_record);
+                self.split_preview = Some(split::summarize(&inputs, &assignment));
+            }
+            Err(e) => self.show_status(&e),
+        }
+    }
+
+    /// 清除划分记录，之后的导出将重新划分所有图片
+    pub fn clear_split_record(&mut self) {
+        self.split_record.clear();
+        if let Some(label_dir) = &self.label_dir {
+            let _ = fs::remove_file(label_dir.join(split::SPLIT_FILE_NAME));
+        }
+        self.split_preview = None;
+        self.show_status("已清除划分记录");
+    }
+
+    /// 按划分导出为 `images/{split}`、`labels/{split}` 结构并生成 data.yaml，返回导出的图片数量
+    pub fn export_split(&mut self, out_dir: &Path) -> Result<usize, String> {
+        let label_dir = self.label_dir.clone().ok_or("请先选择标签目录")?;
+        let (images, inputs) = self.split_inputs()?;
+        let transform = self.label_transform();
+        let kept: HashSet<PathBuf> = transform
+            .filter_images(images.clone(), &label_dir, &self.project.label_layout())
+            .into_iter()
+            .collect();
+        for dir in ["images", "labels"] {
+            let path = out_dir.join(dir);
+            if fs::read_dir(&path).is_ok_and(|mut entries| entries.next().is_some()) {
+                return Err("目标目录不为空，请选择空目录或新目录".to_string());
+            }
+        }
+
+        let assignment = split::assign(&inputs, &self.project.split, &self.split_record);
+        let mut used = BTreeSet::new();
+        let mut max_class = None;
+        for (path, input) in images.iter().zip(&inputs) {
+            let Some(split) = assignment.get(&input.name).copied() else {
+                continue;
+            };
+            if !kept.contains(path) {
+                continue;
+            }
+            let classes = input.classes.iter().filter_map(|class| match &transform.remap {
+                Some(table) => remap::map_class(table, *class),
+                None => Some(*class),
+            });
+            max_class = max_class.max(classes.max());
+            used.insert(split);
+            let images_dir = out_dir.join("images").join(split.name());
+            let labels_dir = out_dir.join("labels").join(split.name());
+            fs::create_dir_all(&images_dir).map_err(|e| format!("创建图片目录失败: {}", e))?;
+            fs::create_dir_all(&labels_dir).map_err(|e| format!("创建标签目录失败: {}", e))?;
+
+            fs::copy(path, images_dir.join(&input.name))
+                .map_err(|e| format!("复制图片文件失败 {}: {}", input.name, e))?;
+            let src_label = labels::label_path(&label_dir, path);
+            if src_label.exists() {
+                self.export_label_file(
+                    &src_label,
+                    &labels::label_path(&labels_dir, path),
+                    TransferMode::Copy,
+                )?;
+            }
+        }
+
+        let root = out_dir.canonicalize().unwrap_or_else(|_| out_dir.to_path_buf());
+        fs::write(
+            out_dir.join("data.yaml"),
+            split::data_yaml(&self.project, Some(&root), &Split::yaml_dirs(&used), max_class),
+        )
+        .map_err(|e| format!("写入 data.yaml 失败: {}", e))?;
+
+        self.split_record.extend(assignment);
+        split::save_record(&label_dir, &self.split_record)?;
+        self.split_preview = Some(split::summarize(&inputs, &self.split_record));
+        Ok(kept.len())
     }
 }
\ No newline at end of file
@@ -0,0 +1,524 @@
+use image::{DynamicImage, Rgb, RgbImage};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tract_onnx::prelude::*;
+
+use crate::diff;
+use crate::labels::{self, LabelLayout};
+use crate::models::{BoundingBox, Shape};
+use crate::project::ProjectConfig;
+
+/// 每张图片最多保留的检测框数量
+const MAX_DETECTIONS: usize = 300;
+
+/// letterbox 填充颜色（与 YOLO 训练时一致）
+const PAD_COLOR: u8 = 114;
+
+type OnnxPlan = TypedRunnableModel<TypedModel>;
+
+/// 自动标注设置，保存在项目配置中
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoLabelConfig {
+    pub model_path: String,
+    pub input_size: usize, // 模型输入边长（正方形）
+    pub conf_threshold: f32,
+    pub iou_threshold: f32,              // NMS 的 IoU 阈值
+    pub class_map: BTreeMap<usize, i32>, // 模型类别 -> 项目类别，优先于按名称匹配
+}
+
+impl Default for AutoLabelConfig {
+    fn default() -> Self {
+        Self {
+            model_path: String::new(),
+            input_size: 640,
+            conf_threshold: 0.25,
+            iou_threshold: 0.45,
+            class_map: BTreeMap::new(),
+        }
+    }
+}
+
+/// 已加载的 YOLO ONNX 模型（CPU 推理）
+pub struct Detector {
+    plan: OnnxPlan,
+    input_size: usize,
+    pub class_names: Vec<String>, // 模型元数据中的类别名称（如有）
+}
+
+impl Detector {
+    pub fn load(config: &AutoLabelConfig) -> Result<Self, String> {
+        let onnx = tract_onnx::onnx();
+        let proto = onnx
+            .proto_model_for_path(&config.model_path)
+            .map_err(|e| format!("读取模型失败: {}", e))?;
+        let class_names = proto
+            .metadata_props
+            .iter()
+            .find(|p| p.key == "names")
+            .map(|p| parse_class_names(&p.value))
+            .unwrap_or_default();
+
+        let size = config.input_size;
+        let plan = onnx
+            .model_for_proto_model(&proto)
+            .and_then(|model| model.with_input_fact(0, f32::fact([1, 3, size, size]).into()))
+            .and_then(|model| model.into_optimized())
+            .and_then(|model| model.into_runnable())
+            .map_err(|e| format!("加载模型失败: {}", e))?;
+
+        Ok(Self {
+            plan,
+            input_size: size,
+            class_names,
+        })
+    }
+
+    /// 检测单张图片，返回归一化坐标的边界框，类别为模型类别序号
+    pub fn detect(
+        &self,
+        img: &DynamicImage,
+        conf_threshold: f32,
+        iou_threshold: f32,
+    ) -> Result<Vec<BoundingBox>, String> {
+        let letterbox = Letterbox::new(img, self.input_size);
+        let outputs = self
+            .plan
+            .run(tvec!(letterbox.tensor.clone().into()))
+            .map_err(|e| format!("模型推理失败: {}", e))?;
+        let output = outputs[0]
+            .to_array_view::<f32>()
+            .map_err(|e| format!("无法解析模型输出: {}", e))?;
+        let boxes = decode_output(&output, &letterbox, conf_threshold)?;
+        Ok(non_max_suppression(boxes, iou_threshold as f64))
+    }
+}
+
+/// 解析模型输出为原图归一化坐标的候选框（未做 NMS）
+fn decode_output(
+    output: &tract_ndarray::ArrayViewD<f32>,
+    letterbox: &Letterbox,
+    conf_threshold: f32,
+) -> Result<Vec<BoundingBox>, String> {
+    let shape = output.shape().to_vec();
+    if shape.len() != 3 {
+        return Err(format!("不支持的模型输出形状: {:?}", shape));
+    }
+
+    // YOLOv8 及之后输出 [1, 4+类别数, 候选数]，YOLOv5 输出 [1, 候选数, 5+类别数]（含目标置信度）
+    let channels_first = shape[1] < shape[2];
+    let (candidates, channels) = if channels_first {
+        (shape[2], shape[1])
+    } else {
+        (shape[1], shape[2])
+    };
+    let value = |i: usize, c: usize| {
+        if channels_first {
+            output[[0, c, i]]
+        } else {
+            output[[0, i, c]]
+        }
+    };
+    let class_offset = if channels_first { 4 } else { 5 };
+    if channels <= class_offset {
+        return Err(format!("不支持的模型输出形状: {:?}", shape));
+    }
+
+    let mut boxes = Vec::new();
+    for i in 0..candidates {
+        let objectness = if channels_first { 1.0 } else { value(i, 4) };
+        let (class, score) = (class_offset..channels)
+            .map(|c| (c - class_offset, value(i, c)))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap_or((0, 0.0));
+        let confidence = objectness * score;
+        if confidence < conf_threshold {
+            continue;
+        }
+        if let Some(mut bbox) =
+            letterbox.to_normalized(value(i, 0), value(i, 1), value(i, 2), value(i, 3))
+        {
+            bbox.class = class as i32;
+            bbox.confidence = Some(confidence as f64);
+            boxes.push(bbox);
+        }
+    }
+
+    Ok(boxes)
+}
+
+/// 等比缩放并填充到正方形输入，记录还原坐标所需的参数
+struct Letterbox {
+    tensor: Tensor,
+    scale: f32,
+    pad_x: f32,
+    pad_y: f32,
+    width: f32,
+    height: f32,
+}
+
+impl Letterbox {
+    fn new(img: &DynamicImage, size: usize) -> Self {
+        let rgb = img.to_rgb8();
+        let (width, height) = (rgb.width() as f32, rgb.height() as f32);
+        let scale = (size as f32 / width).min(size as f32 / height);
+        let new_width = ((width * scale).round() as u32).max(1);
+        let new_height = ((height * scale).round() as u32).max(1);
+        let resized = image::imageops::resize(
+            &rgb,
+            new_width,
+            new_height,
+            image::imageops::FilterType::Triangle,
+        );
+
+        let pad_x = (size as u32 - new_width) / 2;
+        let pad_y = (size as u32 - new_height) / 2;
+        let mut canvas = RgbImage::from_pixel(size as u32, size as u32, Rgb([PAD_COLOR; 3]));
+        image::imageops::overlay(&mut canvas, &resized, pad_x as i64, pad_y as i64);
+
+        let tensor: Tensor =
+            tract_ndarray::Array4::from_shape_fn((1, 3, size, size), |(_, c, y, x)| {
+                canvas.get_pixel(x as u32, y as u32)[c] as f32 / 255.0
+            })
+            .into();
+
+        Self {
+            tensor,
+            scale,
+            pad_x: pad_x as f32,
+            pad_y: pad_y as f32,
+            width,
+            height,
+        }
+    }
+
+    /// 模型输入坐标系下的中心点框 -> 原图归一化坐标，裁剪到图片范围内
+    fn to_normalized(&self, cx: f32, cy: f32, w: f32, h: f32) -> Option<BoundingBox> {
+        let x1 = ((cx - w / 2.0 - self.pad_x) / self.scale / self.width).clamp(0.0, 1.0);
+        let y1 = ((cy - h / 2.0 - self.pad_y) / self.scale / self.height).clamp(0.0, 1.0);
+        let x2 = ((cx + w / 2.0 - self.pad_x) / self.scale / self.width).clamp(0.0, 1.0);
+        let y2 = ((cy + h / 2.0 - self.pad_y) / self.scale / self.height).clamp(0.0, 1.0);
+        if x2 <= x1 || y2 <= y1 {
+            return None;
+        }
+        Some(BoundingBox {
+            class: 0,
+            x: ((x1 + x2) / 2.0) as f64,
+            y: ((y1 + y2) / 2.0) as f64,
+            width: (x2 - x1) as f64,
+            height: (y2 - y1) as f64,
+            confidence: None,
+            shape: Shape::Rect,
+            keypoints: Vec::new(),
+            attributes: BTreeMap::new(),
+        })
+    }
+}
+
+/// 按类别进行非极大值抑制
+fn non_max_suppression(mut boxes: Vec<BoundingBox>, iou_threshold: f64) -> Vec<BoundingBox> {
+    boxes.sort_by(|a, b| {
+        b.confidence
+            .unwrap_or(0.0)
+            .total_cmp(&a.confidence.unwrap_or(0.0))
+    });
+    let mut kept: Vec<BoundingBox> = Vec::new();
+    for bbox in boxes {
+        let suppressed = kept
+            .iter()
+            .any(|k| k.class == bbox.class && diff::iou(k, &bbox) > iou_threshold);
+        if !suppressed {
+            kept.push(bbox);
+            if kept.len() >= MAX_DETECTIONS {
+                break;
+            }
+        }
+    }
+    kept
+}
+
+/// 解析 Ultralytics 导出模型元数据中的类别表，如 `{0: 'person', 1: 'bicycle'}`
+fn parse_class_names(value: &str) -> Vec<String> {
+    let mut names = BTreeMap::new();
+    let body = value.trim().trim_start_matches('{').trim_end_matches('}');
+    for entry in body.split(", ") {
+        if let Some((index, name)) = entry.split_once(':')
+            && let Ok(index) = index.trim().parse::<usize>()
+        {
+            let name = name.trim().trim_matches(|c| c == '\'' || c == '"');
+            names.insert(index, name.to_string());
+        }
+    }
+    names.into_values().collect()
+}
+
+/// 模型类别到项目类别的映射
+#[derive(Clone)]
+pub struct ClassMapping {
+    map: BTreeMap<usize, i32>,
+    strict: bool, // 为 true 时丢弃未映射的类别，否则按序号原样使用
+}
+
+impl ClassMapping {
+    /// 先使用手动映射，再按名称与项目类别匹配；两者都没有时按序号一一对应
+    pub fn new(config: &AutoLabelConfig, model_names: &[String], project: &ProjectConfig) -> Self {
+        let mut map = BTreeMap::new();
+        for (index, name) in model_names.iter().enumerate() {
+            if let Some((class, _)) = project.class_names.iter().find(|(_, n)| *n == name) {
+                map.insert(index, *class);
+            }
+        }
+        map.extend(config.class_map.iter().map(|(k, v)| (*k, *v)));
+        Self {
+            strict: !map.is_empty(),
+            map,
+        }
+    }
+
+    pub fn apply(&self, boxes: Vec<BoundingBox>) -> Vec<BoundingBox> {
+        boxes
+            .into_iter()
+            .filter_map(|mut bbox| {
+                let model_class = bbox.class as usize;
+                bbox.class = match self.map.get(&model_class) {
+                    Some(class) => *class,
+                    None if self.strict => return None,
+                    None => bbox.class,
+                };
+                Some(bbox)
+            })
+            .collect()
+    }
+}
+
+/// 合并预标注结果：保留人工确认过的框，替换旧的预标注框
+pub fn merge_predictions(
+    existing: Vec<BoundingBox>,
+    predictions: Vec<BoundingBox>,
+) -> Vec<BoundingBox> {
+    existing
+        .into_iter()
+        .filter(|b| b.confidence.is_none())
+        .chain(predictions)
+        .collect()
+}
+
+/// 后台标注的进度，主线程每帧读取。后台线程只运行模型，
+/// 标签文件由主线程写入，避免覆盖用户在标注期间的修改
+#[derive(Default)]
+pub struct AutoLabelProgress {
+    pub total: usize,
+    pub processed: usize,
+    pub boxes: usize,
+    pub skipped: usize, // 标注期间已被人工处理而未写入的图片
+    pub results: Vec<(PathBuf, Vec<BoundingBox>)>, // 尚未被主线程写入的预测结果
+    pub label_dir: PathBuf,
+    pub single: bool, // 只标注当前图片：不检查图片状态
+    pub errors: Vec<String>,
+    pub cancelled: bool,
+    pub finished: bool,
+}
+
+pub type SharedProgress = Arc<Mutex<AutoLabelProgress>>;
+
+/// 在后台线程中依次对图片运行模型，预测结果交给主线程写入
+pub fn spawn_job(
+    detector: Arc<Detector>,
+    mapping: ClassMapping,
+    config: AutoLabelConfig,
+    images: Vec<PathBuf>,
+    label_dir: PathBuf,
+    single: bool,
+) -> SharedProgress {
+    let progress = Arc::new(Mutex::new(AutoLabelProgress {
+        total: images.len(),
+        label_dir,
+        single,
+        ..Default::default()
+    }));
+    let shared = progress.clone();
+
+    std::thread::spawn(move || {
+        for path in images {
+            if shared.lock().map_or(true, |p| p.cancelled) {
+                break;
+            }
+            let result = detect_image(&detector, &mapping, &config, &path);
+            let Ok(mut p) = shared.lock() else {
+                return;
+            };
+            p.processed += 1;
+            match result {
+                Ok(predictions) => {
+                    p.boxes += predictions.len();
+                    p.results.push((path, predictions));
+                }
+                Err(e) => {
+                    let name = path
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or_default()
+                        .to_string();
+                    p.errors.push(format!("{}: {}", name, e));
+                }
+            }
+        }
+        if let Ok(mut p) = shared.lock() {
+            p.finished = true;
+        }
+    });
+
+    progress
+}
+
+fn detect_image(
+    detector: &Detector,
+    mapping: &ClassMapping,
+    config: &AutoLabelConfig,
+    image_path: &Path,
+) -> Result<Vec<BoundingBox>, String> {
+    let img = image::open(image_path).map_err(|e| format!("图片加载失败: {}", e))?;
+    Ok(mapping.apply(detector.detect(&img, config.conf_threshold, config.iou_threshold)?))
+}
+
+/// 合并时会被替换的旧预标注框的序号
+pub fn replaced_indices(existing: &[BoundingBox]) -> Vec<usize> {
+    existing
+        .iter()
+        .enumerate()
+        .filter(|(_, b)| b.confidence.is_some())
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// 将预测结果合并到标签文件，返回被替换的旧预标注框的序号
+pub fn write_predictions(
+    image_path: &Path,
+    predictions: Vec<BoundingBox>,
+    label_dir: &Path,
+    layout: &LabelLayout,
+) -> Result<Vec<usize>, String> {
+    let label_path = labels::label_path(label_dir, image_path);
+    let existing = labels::read_label_file(&label_path, layout);
+    let replaced = replaced_indices(&existing);
+    let boxes = merge_predictions(existing, predictions);
+    labels::write_label_file(&label_path, &boxes, layout)
+        .map_err(|e| format!("写入标签失败: {}", e))?;
+    Ok(replaced)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 200x100 的图片缩放到 100 输入：比例 0.5，上下各填充 25 像素
+    fn letterbox() -> Letterbox {
+        Letterbox::new(&DynamicImage::new_rgb8(200, 100), 100)
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-5,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    fn detection(class: i32, x: f64, confidence: f64) -> BoundingBox {
+        BoundingBox {
+            class,
+            x,
+            y: 0.5,
+            width: 0.2,
+            height: 0.2,
+            confidence: Some(confidence),
+            shape: Shape::Rect,
+            keypoints: Vec::new(),
+            attributes: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn letterbox_maps_back_to_original_image() {
+        let letterbox = letterbox();
+        assert_close(letterbox.scale as f64, 0.5);
+        assert_close(letterbox.pad_x as f64, 0.0);
+        assert_close(letterbox.pad_y as f64, 25.0);
+
+        let bbox = letterbox.to_normalized(50.0, 50.0, 20.0, 10.0).unwrap();
+        assert_close(bbox.x, 0.5);
+        assert_close(bbox.y, 0.5);
+        assert_close(bbox.width, 0.2);
+        assert_close(bbox.height, 0.2);
+
+        // 伸入填充区域的部分被裁掉，完全落在填充区域内的框被丢弃
+        let bbox = letterbox.to_normalized(50.0, 25.0, 20.0, 20.0).unwrap();
+        assert_close(bbox.y, 0.1);
+        assert_close(bbox.height, 0.2);
+        assert!(letterbox.to_normalized(50.0, 10.0, 20.0, 10.0).is_none());
+    }
+
+    #[test]
+    fn decodes_v8_output() {
+        // [1, 4+2, 8]：候选 0 属于类别 1，候选 1 低于阈值，其余全为 0
+        let mut data = vec![0.0f32; 6 * 8];
+        for (c, v) in [50.0, 50.0, 20.0, 10.0, 0.1, 0.9].into_iter().enumerate() {
+            data[c * 8] = v;
+        }
+        for (c, v) in [30.0, 50.0, 20.0, 10.0, 0.2, 0.1].into_iter().enumerate() {
+            data[c * 8 + 1] = v;
+        }
+        let output = tract_ndarray::Array3::from_shape_vec((1, 6, 8), data)
+            .unwrap()
+            .into_dyn();
+        let boxes = decode_output(&output.view(), &letterbox(), 0.25).unwrap();
+        assert_eq!(boxes.len(), 1);
+        assert_eq!(boxes[0].class, 1);
+        assert_close(boxes[0].confidence.unwrap(), 0.9);
+        assert_close(boxes[0].x, 0.5);
+        assert_close(boxes[0].width, 0.2);
+    }
+
+    #[test]
+    fn decodes_v5_output_with_objectness() {
+        // [1, 10, 5+2]：置信度为目标置信度乘以类别分数
+        let mut data = vec![0.0f32; 10 * 7];
+        data[..7].copy_from_slice(&[50.0, 50.0, 20.0, 10.0, 0.8, 0.5, 0.1]);
+        data[7..14].copy_from_slice(&[50.0, 50.0, 20.0, 10.0, 0.3, 0.9, 0.1]);
+        let output = tract_ndarray::Array3::from_shape_vec((1, 10, 7), data)
+            .unwrap()
+            .into_dyn();
+        let boxes = decode_output(&output.view(), &letterbox(), 0.3).unwrap();
+        assert_eq!(boxes.len(), 1);
+        assert_eq!(boxes[0].class, 0);
+        assert_close(boxes[0].confidence.unwrap(), 0.4);
+        assert_close(boxes[0].y, 0.5);
+        assert_close(boxes[0].height, 0.2);
+    }
+
+    #[test]
+    fn rejects_unsupported_output_shape() {
+        let output = tract_ndarray::Array2::<f32>::zeros((4, 6)).into_dyn();
+        assert!(decode_output(&output.view(), &letterbox(), 0.25).is_err());
+    }
+
+    #[test]
+    fn nms_is_per_class() {
+        let boxes = vec![
+            detection(0, 0.5, 0.6),
+            detection(0, 0.51, 0.9),
+            detection(1, 0.5, 0.7),
+            detection(0, 0.8, 0.5),
+        ];
+        let kept = non_max_suppression(boxes, 0.45);
+        let kept: Vec<(i32, f64)> = kept
+            .iter()
+            .map(|b| (b.class, b.confidence.unwrap()))
+            .collect();
+        // 类别 0 中重叠的低分框被抑制，重叠的类别 1 和不重叠的类别 0 保留
+        assert_eq!(kept, vec![(0, 0.9), (1, 0.7), (0, 0.5)]);
+    }
+}
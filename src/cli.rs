@@ -0,0 +1,299 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::export::{self, ArchiveFormat, ArchiveOptions, ExportContent, LabelTransform};
+use crate::merge::{self, MergeSource};
+use crate::metrics;
+use crate::project::ProjectConfig;
+use crate::report;
+use crate::split;
+use crate::stats;
+use crate::status;
+use crate::utils::list_files;
+
+/// 标签目录中不是标签文件的 .txt 文件
+const SIDECAR_TEXT_FILES: [&str; 2] = [status::LEGACY_RECORD_FILE_NAME, "classes.txt"];
+
+const USAGE: &str = "用法:
+  label_space                      启动图形界面
+  label_space eval --gt <真值标签目录> --pred <预测标签目录>
+                   [--images <图片目录>] [--conf <置信度阈值，默认0.25>]
+                   [--json <输出JSON>] [--csv <输出CSV>]
+  label_space archive --images <图片目录> --labels <标签目录> --out <输出.zip|.tar.gz>
+                   [--content <both|labels|images，默认both>] [--split <yes|no，默认no>]
+                   [--boxes <yes|no，多边形/旋转框导出为外接水平框，默认no>]
+  label_space merge --images <图片目录1> --labels <标签目录1> --images <图片目录2> --labels <标签目录2> ...
+                   --out-images <合并后图片目录> --out-labels <合并后标签目录>
+                   [--skip-duplicates <yes|no，跳过内容重复的图片，默认no>]
+  label_space stats --images <图片目录> --labels <标签目录> [--csv <输出CSV>]
+                   [--html <输出HTML报告>]";
+
+/// 命令行参数：`--key value` 形式的选项，同一选项可以重复出现
+struct Args {
+    options: HashMap<String, Vec<String>>,
+}
+
+impl Args {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = HashMap::new();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let key = arg
+                .strip_prefix("--")
+                .ok_or_else(|| format!("无法识别的参数: {}", arg))?;
+            let value = iter
+                .next()
+                .ok_or_else(|| format!("参数 --{} 缺少取值", key))?;
+            options
+                .entry(key.to_string())
+                .or_insert_with(Vec::new)
+                .push(value.clone());
+        }
+        Ok(Self { options })
+    }
+
+    /// 选项的取值，重复出现时取最后一个
+    fn get(&self, key: &str) -> Option<&str> {
+        self.all(key).last().map(|s| s.as_str())
+    }
+
+    fn all(&self, key: &str) -> &[String] {
+        self.options.get(key).map_or(&[], |values| values.as_slice())
+    }
+
+    fn required_path(&self, key: &str) -> Result<PathBuf, String> {
+        self.get(key)
+            .map(PathBuf::from)
+            .ok_or_else(|| format!("缺少参数 --{}", key))
+    }
+
+    fn optional_path(&self, key: &str) -> Option<PathBuf> {
+        self.get(key).map(PathBuf::from)
+    }
+
+    fn flag(&self, key: &str) -> Result<bool, String> {
+        match self.get(key) {
+            None | Some("no") => Ok(false),
+            Some("yes") => Ok(true),
+            Some(value) => Err(format!("参数 --{} 只能是 yes 或 no: {}", key, value)),
+        }
+    }
+
+    fn f64_or(&self, key: &str, default: f64) -> Result<f64, String> {
+        match self.get(key) {
+            Some(value) => value
+                .parse()
+                .map_err(|_| format!("参数 --{} 不是有效的数字: {}", key, value)),
+            None => Ok(default),
+        }
+    }
+}
+
+/// 执行命令行子命令，返回进程退出码
+pub fn run(args: &[String]) -> i32 {
+    let result = match args.first().map(|s| s.as_str()) {
+        Some("eval") => Args::parse(&args[1..]).and_then(|a| eval(&a)),
+        Some("archive") => Args::parse(&args[1..]).and_then(|a| archive(&a)),
+        Some("merge") => Args::parse(&args[1..]).and_then(|a| merge(&a)),
+        Some("stats") => Args::parse(&args[1..]).and_then(|a| stats(&a)),
+        Some("help") | Some("--help") | Some("-h") => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        Some(other) => Err(format!("未知的子命令: {}\n{}", other, USAGE)),
+        None => Ok(()),
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        }
+    }
+}
+
+fn eval(args: &Args) -> Result<(), String> {
+    let gt_dir = args.required_path("gt")?;
+    let pred_dir = args.required_path("pred")?;
+    let conf = args.f64_or("conf", 0.25)?;
+
+    // 未指定图片目录时取真值和预测目录中标签文件的并集，只有预测的图片计为误检
+    let images = match args.optional_path("images") {
+        Some(image_dir) => list_files(&image_dir, &["jpg", "png"])?,
+        None => label_files(&[&gt_dir, &pred_dir])?,
+    };
+
+    // 按真值目录中的项目配置解析关键点标签
+    let layout = ProjectConfig::load(&gt_dir)?.label_layout();
+    let report = metrics::evaluate(&images, &gt_dir, &pred_dir, conf, &layout);
+
+    println!(
+        "图片: {}  mAP@0.5: {:.4}  mAP@0.5:0.95: {:.4}",
+        report.images, report.map50, report.map50_95
+    );
+    print!("{}", report.to_csv());
+
+    if let Some(path) = args.optional_path("json") {
+        fs::write(&path, report.to_json()?).map_err(|e| format!("写入 JSON 失败: {}", e))?;
+    }
+    if let Some(path) = args.optional_path("csv") {
+        fs::write(&path, report.to_csv()).map_err(|e| format!("写入 CSV 失败: {}", e))?;
+    }
+    Ok(())
+}
+
+/// 多个目录中标签文件的并集（按文件名去重），跳过状态记录等非标签文件
+fn label_files(dirs: &[&Path]) -> Result<Vec<PathBuf>, String> {
+    let mut files = BTreeMap::new();
+    for dir in dirs {
+        for path in list_files(dir, &["txt"])? {
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            if !SIDECAR_TEXT_FILES.contains(&name) {
+                files.entry(name.to_string()).or_insert(path);
+            }
+        }
+    }
+    Ok(files.into_values().collect())
+}
+
+fn archive(args: &Args) -> Result<(), String> {
+    let image_dir = args.required_path("images")?;
+    let label_dir = args.required_path("labels")?;
+    let out = args.required_path("out")?;
+    let format = ArchiveFormat::from_path(&out).ok_or_else(|| {
+        format!(
+            "无法识别的压缩包格式（支持 .zip、.tar.gz）: {}",
+            out.display()
+        )
+    })?;
+    let content = match args.get("content").unwrap_or("both") {
+        "both" => ExportContent::Both,
+        "labels" => ExportContent::Labels,
+        "images" => ExportContent::Images,
+        other => return Err(format!("参数 --content 无效: {}", other)),
+    };
+
+    let project = ProjectConfig::load(&label_dir)?;
+    let records = status::load_records(&label_dir)?;
+    // 项目中勾选了导出时重映射的类别同样生效
+    let transform = LabelTransform {
+        axis_aligned: args.flag("boxes")?,
+        remap: project.remap.export_table().cloned(),
+        skip_empty: project.remap.skip_empty,
+    };
+    let images = transform.filter_images(
+        list_files(&image_dir, &["jpg", "png"])?,
+        &label_dir,
+        &project.label_layout(),
+    );
+
+    // 与图形界面一致：按划分导出时沿用并更新标签目录下的划分记录
+    let mut record = split::load_record(&label_dir);
+    let assignment = if args.flag("split")? {
+        let inputs = split::collect_inputs(&images, &label_dir, &project.label_layout());
+        Some(split::assign(&inputs, &project.split, &record))
+    } else {
+        None
+    };
+    let options = ArchiveOptions {
+        format,
+        content,
+        transform: &transform,
+        assignment: assignment.as_ref(),
+    };
+    let count =
+        export::archive::write_archive(&out, &images, &label_dir, &project, &records, &options)?;
+    if let Some(assignment) = assignment {
+        record.extend(assignment);
+        split::save_record(&label_dir, &record)?;
+    }
+    println!("已将 {} 张图片导出到 {}", count, out.display());
+    Ok(())
+}
+
+fn merge(args: &Args) -> Result<(), String> {
+    let image_dirs = args.all("images");
+    let label_dirs = args.all("labels");
+    if image_dirs.len() != label_dirs.len() {
+        return Err("--images 和 --labels 的数量必须一致，按出现顺序配对".to_string());
+    }
+    let sources: Vec<MergeSource> = image_dirs
+        .iter()
+        .zip(label_dirs)
+        .map(|(image_dir, label_dir)| MergeSource {
+            image_dir: PathBuf::from(image_dir),
+            label_dir: PathBuf::from(label_dir),
+        })
+        .collect();
+    let image_out = args.required_path("out-images")?;
+    let label_out = args.required_path("out-labels")?;
+
+    let report = merge::merge(
+        &sources,
+        &image_out,
+        &label_out,
+        args.flag("skip-duplicates")?,
+    )?;
+    println!("{}", report.summary());
+    for (index, mapping) in report.class_mappings.iter().enumerate() {
+        for (from, to) in mapping.iter().filter(|(from, to)| from != to) {
+            println!("项目 {}: 类别 {} -> {}", index + 1, from, to);
+        }
+    }
+    for duplicate in &report.duplicates {
+        println!(
+            "重复: {} 与 {} 内容相同",
+            duplicate.source.display(),
+            duplicate.duplicate_of
+        );
+    }
+    for failure in &report.failures {
+        eprintln!("{}", failure);
+    }
+    println!(
+        "合并报告已写入 {}",
+        label_out.join(merge::MERGE_REPORT_FILE_NAME).display()
+    );
+    Ok(())
+}
+
+fn stats(args: &Args) -> Result<(), String> {
+    let image_dir = args.required_path("images")?;
+    let label_dir = args.required_path("labels")?;
+    let images = list_files(&image_dir, &["jpg", "png"])?;
+    let project = ProjectConfig::load(&label_dir)?;
+    let stats = stats::compute(&images, &label_dir, &project.label_layout());
+
+    println!(
+        "图片: {}  有标注: {}  框: {}",
+        stats.images, stats.labeled_images, stats.boxes
+    );
+    for (class, boxes) in &stats.class_boxes {
+        println!(
+            "{} {}: {} 个框, {} 张图片",
+            class,
+            project.class_name(*class),
+            boxes,
+            stats.class_images.get(class).copied().unwrap_or(0)
+        );
+    }
+    for (kind, count) in stats.issue_counts() {
+        println!("{}: {}", kind.label(), count);
+    }
+    for failure in &stats.failures {
+        eprintln!("{}", failure);
+    }
+    if let Some(path) = args.optional_path("csv") {
+        fs::write(&path, stats.to_csv(&project)).map_err(|e| format!("写入 CSV 失败: {}", e))?;
+    }
+    if let Some(path) = args.optional_path("html") {
+        let records = status::load_records(&label_dir)?;
+        let html = report::html_report(&stats, &images, &label_dir, &project, &records);
+        fs::write(&path, html).map_err(|e| format!("写入报告失败: {}", e))?;
+    }
+    Ok(())
+}
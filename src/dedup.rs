@@ -0,0 +1,382 @@
+use image::imageops::FilterType;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::split::SplitRecord;
+
+/// 感知哈希缓存文件，保存在标签目录下
+pub const HASH_CACHE_FILE_NAME: &str = "image_hashes.json";
+
+/// 感知哈希算法
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HashKind {
+    Average, // aHash：8x8 灰度与均值比较，最快，对亮度变化敏感
+    #[default]
+    Difference, // dHash：相邻像素梯度，适合视频相邻帧
+    Perceptual, // pHash：DCT 低频分量，对缩放和压缩最稳定
+}
+
+impl HashKind {
+    pub const ALL: [HashKind; 3] = [
+        HashKind::Average,
+        HashKind::Difference,
+        HashKind::Perceptual,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            HashKind::Average => "aHash（均值）",
+            HashKind::Difference => "dHash（梯度）",
+            HashKind::Perceptual => "pHash（DCT）",
+        }
+    }
+}
+
+/// 近似重复检测设置，保存在项目配置中
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DedupConfig {
+    pub kind: HashKind,
+    pub threshold: u32, // 汉明距离不超过该值视为近似重复，0 表示只找完全相同的哈希
+}
+
+impl Default for DedupConfig {
+    fn default() -> Self {
+        Self {
+            kind: HashKind::Difference,
+            threshold: 4,
+        }
+    }
+}
+
+/// 一张图片的三种 64 位感知哈希
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImageHashes {
+    pub average: u64,
+    pub difference: u64,
+    pub perceptual: u64,
+}
+
+impl ImageHashes {
+    pub fn get(&self, kind: HashKind) -> u64 {
+        match kind {
+            HashKind::Average => self.average,
+            HashKind::Difference => self.difference,
+            HashKind::Perceptual => self.perceptual,
+        }
+    }
+}
+
+/// 缓存的哈希，文件大小或修改时间变化后重新计算
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CachedHashes {
+    pub size: u64,
+    pub modified: u64,
+    pub hashes: ImageHashes,
+}
+
+/// 图片文件名 -> 缓存的哈希
+pub type HashCache = BTreeMap<String, CachedHashes>;
+
+pub fn load_cache(label_dir: &Path) -> HashCache {
+    fs::read_to_string(label_dir.join(HASH_CACHE_FILE_NAME))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_cache(label_dir: &Path, cache: &HashCache) -> Result<(), String> {
+    let content = serde_json::to_string(cache).map_err(|e| format!("序列化哈希缓存失败: {}", e))?;
+    fs::write(label_dir.join(HASH_CACHE_FILE_NAME), content)
+        .map_err(|e| format!("保存哈希缓存失败: {}", e))
+}
+
+/// 文件大小和修改时间（秒），用于判断缓存是否失效
+fn file_stamp(path: &Path) -> std::io::Result<(u64, u64)> {
+    let metadata = fs::metadata(path)?;
+    let modified = metadata
+        .modified()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    Ok((metadata.len(), modified))
+}
+
+/// 计算一张图片的 aHash、dHash 和 pHash
+pub fn compute_hashes(path: &Path) -> Result<ImageHashes, String> {
+    let image = image::open(path).map_err(|e| format!("图片加载失败: {}", e))?;
+
+    let small = image.resize_exact(8, 8, FilterType::Triangle).to_luma8();
+    let mean = small.pixels().map(|p| p[0] as u32).sum::<u32>() / 64;
+    let average = bits(small.pixels().map(|p| p[0] as u32 > mean));
+
+    let wide = image.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+    let difference = bits(
+        (0..8)
+            .flat_map(|y| (0..8).map(move |x| (x, y)))
+            .map(|(x, y)| wide.get_pixel(x, y)[0] < wide.get_pixel(x + 1, y)[0]),
+    );
+
+    // 32x32 灰度图的二维 DCT，只取左上角 8x8 低频分量与中位数比较
+    let large = image.resize_exact(32, 32, FilterType::Triangle).to_luma8();
+    let cosines: Vec<[f64; 32]> = (0..8)
+        .map(|u| {
+            let mut row = [0.0; 32];
+            for (x, value) in row.iter_mut().enumerate() {
+                *value = ((2 * x + 1) as f64 * u as f64 * std::f64::consts::PI / 64.0).cos();
+            }
+            row
+        })
+        .collect();
+    let mut coefficients = [0.0f64; 64];
+    for v in 0..8 {
+        for u in 0..8 {
+            let mut sum = 0.0;
+            for y in 0..32 {
+                for x in 0..32 {
+                    sum += large.get_pixel(x as u32, y as u32)[0] as f64
+                        * cosines[u][x]
+                        * cosines[v][y];
+                }
+            }
+            coefficients[v * 8 + u] = sum;
+        }
+    }
+    // 直流分量只反映整体亮度，不参与中位数
+    let mut sorted = coefficients[1..].to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let median = sorted[sorted.len() / 2];
+    let perceptual = bits(coefficients.iter().map(|c| *c > median));
+
+    Ok(ImageHashes {
+        average,
+        difference,
+        perceptual,
+    })
+}
+
+fn bits(values: impl Iterator<Item = bool>) -> u64 {
+    values
+        .enumerate()
+        .fold(0, |hash, (i, bit)| if bit { hash | (1 << i) } else { hash })
+}
+
+pub fn hamming(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// 按汉明距离将近似重复的图片分组（传递闭包），只返回两张及以上的组，组内按文件名排序。
+/// 两两比较的复杂度为 O(n²)，`cancelled` 返回 true 时提前结束并返回不完整的结果
+pub fn group_duplicates(
+    hashes: &BTreeMap<String, ImageHashes>,
+    kind: HashKind,
+    threshold: u32,
+    cancelled: impl Fn() -> bool,
+) -> Vec<Vec<String>> {
+    let entries: Vec<(&String, u64)> = hashes
+        .iter()
+        .map(|(name, hashes)| (name, hashes.get(kind)))
+        .collect();
+    let mut parent: Vec<usize> = (0..entries.len()).collect();
+    fn find(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    for i in 0..entries.len() {
+        if cancelled() {
+            break;
+        }
+        for j in i + 1..entries.len() {
+            if hamming(entries[i].1, entries[j].1) <= threshold {
+                let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+                if a != b {
+                    parent[b] = a;
+                }
+            }
+        }
+    }
+
+    let mut groups: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+    for (i, (name, _)) in entries.iter().enumerate() {
+        let root = find(&mut parent, i);
+        groups.entry(root).or_default().push((*name).clone());
+    }
+    let mut groups: Vec<Vec<String>> = groups.into_values().filter(|g| g.len() > 1).collect();
+    groups.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+    groups
+}
+
+/// 后台计算哈希的进度，主线程每帧读取
+#[derive(Default)]
+pub struct HashProgress {
+    pub total: usize,
+    pub processed: usize,
+    pub cache: HashCache, // 计算完成后写回磁盘
+    pub errors: Vec<String>,
+    pub cancelled: bool,
+    pub finished: bool,
+}
+
+pub type SharedHashProgress = Arc<Mutex<HashProgress>>;
+
+/// 在后台线程中计算图片哈希，缓存中大小和修改时间未变的图片直接复用
+pub fn spawn_hash_job(images: Vec<PathBuf>, cache: HashCache) -> SharedHashProgress {
+    let progress = Arc::new(Mutex::new(HashProgress {
+        total: images.len(),
+        ..Default::default()
+    }));
+    let shared = progress.clone();
+
+    std::thread::spawn(move || {
+        let mut updated = HashCache::new();
+        let mut errors = Vec::new();
+        for path in images {
+            if shared.lock().map_or(true, |p| p.cancelled) {
+                break;
+            }
+            let name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let result = file_stamp(&path).map_err(|e| e.to_string()).and_then(
+                |(size, modified)| match cache.get(&name) {
+                    Some(cached) if cached.size == size && cached.modified == modified => {
+                        Ok(cached.clone())
+                    }
+                    _ => compute_hashes(&path).map(|hashes| CachedHashes {
+                        size,
+                        modified,
+                        hashes,
+                    }),
+                },
+            );
+            match result {
+                Ok(cached) => {
+                    updated.insert(name, cached);
+                }
+                Err(e) => errors.push(format!("{}: {}", name, e)),
+            }
+            if let Ok(mut p) = shared.lock() {
+                p.processed += 1;
+            }
+        }
+        if let Ok(mut p) = shared.lock() {
+            p.cache = updated;
+            p.errors = errors;
+            p.finished = true;
+        }
+    });
+
+    progress
+}
+
+/// 后台分组的结果，主线程每帧读取
+#[derive(Default)]
+pub struct GroupProgress {
+    pub groups: Vec<Vec<String>>,
+    pub cancelled: bool, // 算法或阈值再次变化时取消，结果不再使用
+    pub finished: bool,
+}
+
+pub type SharedGroupProgress = Arc<Mutex<GroupProgress>>;
+
+/// 在后台线程中分组，避免图片较多时调整阈值卡住界面
+pub fn spawn_group_job(
+    hashes: BTreeMap<String, ImageHashes>,
+    kind: HashKind,
+    threshold: u32,
+) -> SharedGroupProgress {
+    let progress = Arc::new(Mutex::new(GroupProgress::default()));
+    let shared = progress.clone();
+
+    std::thread::spawn(move || {
+        let groups = group_duplicates(&hashes, kind, threshold, || {
+            shared.lock().map_or(true, |p| p.cancelled)
+        });
+        if let Ok(mut p) = shared.lock() {
+            p.groups = groups;
+            p.finished = true;
+        }
+    });
+
+    progress
+}
+
+/// 组内图片是否被划分到了不同的训练/验证/测试集合，用于提示划分泄漏
+pub fn spans_splits(group: &[String], record: &SplitRecord) -> bool {
+    let mut splits = group.iter().filter_map(|name| record.get(name));
+    match splits.next() {
+        Some(first) => splits.any(|split| split != first),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hashes(values: &[(&str, u64)]) -> BTreeMap<String, ImageHashes> {
+        values
+            .iter()
+            .map(|(name, hash)| {
+                let hashes = ImageHashes {
+                    difference: *hash,
+                    ..Default::default()
+                };
+                (name.to_string(), hashes)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn groups_are_transitive_and_sorted() {
+        let hashes = hashes(&[
+            ("a.jpg", 0b0000),
+            ("b.jpg", 0b0001),
+            ("c.jpg", 0b0011),
+            ("d.jpg", u64::MAX),
+            ("e.jpg", u64::MAX),
+            ("f.jpg", 0xF0F0),
+        ]);
+        let groups = group_duplicates(&hashes, HashKind::Difference, 1, || false);
+        assert_eq!(
+            groups,
+            vec![
+                vec![
+                    "a.jpg".to_string(),
+                    "b.jpg".to_string(),
+                    "c.jpg".to_string()
+                ],
+                vec!["d.jpg".to_string(), "e.jpg".to_string()],
+            ]
+        );
+        let groups = group_duplicates(&hashes, HashKind::Difference, 0, || false);
+        assert_eq!(groups, vec![vec!["d.jpg".to_string(), "e.jpg".to_string()]]);
+    }
+
+    #[test]
+    fn background_grouping_matches_direct_grouping() {
+        let hashes = hashes(&[("a.jpg", 1), ("b.jpg", 3), ("c.jpg", 1 << 40)]);
+        let job = spawn_group_job(hashes.clone(), HashKind::Difference, 2);
+        let groups = loop {
+            let progress = job.lock().unwrap();
+            if progress.finished {
+                break progress.groups.clone();
+            }
+            drop(progress);
+            std::thread::yield_now();
+        };
+        assert_eq!(
+            groups,
+            group_duplicates(&hashes, HashKind::Difference, 2, || false)
+        );
+        assert!(group_duplicates(&hashes, HashKind::Difference, 2, || true).is_empty());
+    }
+}
@@ -0,0 +1,190 @@
+use eframe::egui;
+
+use crate::models::BoundingBox;
+
+/// 匹配后 IoU 高于该值且类别相同视为未变化，否则视为位置/大小有变化
+const UNCHANGED_IOU: f64 = 0.95;
+
+/// 两个标签目录之间单个边界框的差异类型，以当前标签目录为基准
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DiffKind {
+    Unchanged,
+    Moved,        // 位置或大小有变化
+    Reclassified, // 类别不同
+    Added,        // 仅对比目录中存在
+    Removed,      // 仅当前目录中存在
+}
+
+impl DiffKind {
+    pub const ALL: [DiffKind; 5] = [
+        DiffKind::Unchanged,
+        DiffKind::Moved,
+        DiffKind::Reclassified,
+        DiffKind::Added,
+        DiffKind::Removed,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DiffKind::Unchanged => "一致",
+            DiffKind::Moved => "位置变化",
+            DiffKind::Reclassified => "类别变化",
+            DiffKind::Added => "新增",
+            DiffKind::Removed => "缺失",
+        }
+    }
+
+    pub fn color(&self) -> egui::Color32 {
+        match self {
+            DiffKind::Unchanged => egui::Color32::from_gray(160),
+            DiffKind::Moved => egui::Color32::from_rgb(0, 200, 255),
+            DiffKind::Reclassified => egui::Color32::from_rgb(255, 0, 255),
+            DiffKind::Added => egui::Color32::from_rgb(0, 220, 0),
+            DiffKind::Removed => egui::Color32::from_rgb(255, 60, 60),
+        }
+    }
+}
+
+/// 单个差异：ours/theirs 分别为当前目录和对比目录中的边界框下标
+#[derive(Clone, Debug)]
+pub struct BoxDiff {
+    pub kind: DiffKind,
+    pub ours: Option<usize>,
+    pub theirs: Option<usize>,
+    pub iou: f64,
+}
+
+/// 差异汇总
+#[derive(Clone, Default)]
+pub struct DiffSummary {
+    pub images: usize,
+    pub images_with_changes: usize,
+    pub unchanged: usize,
+    pub moved: usize,
+    pub reclassified: usize,
+    pub added: usize,
+    pub removed: usize,
+}
+
+impl DiffSummary {
+    pub fn from_diffs(diffs: &[BoxDiff]) -> Self {
+        let mut summary = Self::default();
+        summary.add_image(diffs);
+        summary
+    }
+
+    pub fn add_image(&mut self, diffs: &[BoxDiff]) {
+        self.images += 1;
+        if diffs.iter().any(|d| d.kind != DiffKind::Unchanged) {
+            self.images_with_changes += 1;
+        }
+        for diff in diffs {
+            match diff.kind {
+                DiffKind::Unchanged => self.unchanged += 1,
+                DiffKind::Moved => self.moved += 1,
+                DiffKind::Reclassified => self.reclassified += 1,
+                DiffKind::Added => self.added += 1,
+                DiffKind::Removed => self.removed += 1,
+            }
+        }
+    }
+
+    pub fn count(&self, kind: DiffKind) -> usize {
+        match kind {
+            DiffKind::Unchanged => self.unchanged,
+            DiffKind::Moved => self.moved,
+            DiffKind::Reclassified => self.reclassified,
+            DiffKind::Added => self.added,
+            DiffKind::Removed => self.removed,
+        }
+    }
+}
+
+/// 两个归一化边界框的交并比
+pub fn iou(a: &BoundingBox, b: &BoundingBox) -> f64 {
+    let (ax1, ay1, ax2, ay2) = (
+        a.x - a.width / 2.0,
+        a.y - a.height / 2.0,
+        a.x + a.width / 2.0,
+        a.y + a.height / 2.0,
+    );
+    let (bx1, by1, bx2, by2) = (
+        b.x - b.width / 2.0,
+        b.y - b.height / 2.0,
+        b.x + b.width / 2.0,
+        b.y + b.height / 2.0,
+    );
+
+    let inter_w = (ax2.min(bx2) - ax1.max(bx1)).max(0.0);
+    let inter_h = (ay2.min(by2) - ay1.max(by1)).max(0.0);
+    let inter = inter_w * inter_h;
+    let union = a.width * a.height + b.width * b.height - inter;
+    if union <= 0.0 { 0.0 } else { inter / union }
+}
+
+/// 按 IoU 从大到小贪心匹配两组边界框（不区分类别，以便识别类别变化）
+pub fn diff_boxes(
+    ours: &[BoundingBox],
+    theirs: &[BoundingBox],
+    iou_threshold: f64,
+) -> Vec<BoxDiff> {
+    let mut pairs = Vec::new();
+    for (i, a) in ours.iter().enumerate() {
+        for (j, b) in theirs.iter().enumerate() {
+            let value = iou(a, b);
+            if value >= iou_threshold {
+                pairs.push((value, i, j));
+            }
+        }
+    }
+    pairs.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let mut ours_matched = vec![false; ours.len()];
+    let mut theirs_matched = vec![false; theirs.len()];
+    let mut diffs = Vec::new();
+
+    for (value, i, j) in pairs {
+        if ours_matched[i] || theirs_matched[j] {
+            continue;
+        }
+        ours_matched[i] = true;
+        theirs_matched[j] = true;
+
+        let kind = if ours[i].class != theirs[j].class {
+            DiffKind::Reclassified
+        } else if value < UNCHANGED_IOU {
+            DiffKind::Moved
+        } else {
+            DiffKind::Unchanged
+        };
+        diffs.push(BoxDiff {
+            kind,
+            ours: Some(i),
+            theirs: Some(j),
+            iou: value,
+        });
+    }
+
+    for (i, matched) in ours_matched.iter().enumerate() {
+        if !matched {
+            diffs.push(BoxDiff {
+                kind: DiffKind::Removed,
+                ours: Some(i),
+                theirs: None,
+                iou: 0.0,
+            });
+        }
+    }
+    for (j, matched) in theirs_matched.iter().enumerate() {
+        if !matched {
+            diffs.push(BoxDiff {
+                kind: DiffKind::Added,
+                ours: None,
+                theirs: Some(j),
+                iou: 0.0,
+            });
+        }
+    }
+
+    diffs
+}
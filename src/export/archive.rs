@@ -0,0 +1,403 @@
+use chrono::{Datelike, Local, Timelike};
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use serde::Serialize;
+use sha1::{Digest, Sha1};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZIP64_BYTES_THR, ZipWriter};
+
+use super::manifest::{ExportManifest, MANIFEST_FILE_NAME, ManifestEntry};
+use super::{ExportContent, FileKind, LabelTransform};
+use crate::labels;
+use crate::models::ImageRecord;
+use crate::project::ProjectConfig;
+use crate::split::{self, Split, SplitRecord};
+use crate::utils::now_secs;
+
+/// 压缩包中的统计快照文件
+pub const STATISTICS_FILE_NAME: &str = "statistics.json";
+/// 不可压缩的数据经 deflate 后会略大于原文件，接近 4GB 的文件同样使用 ZIP64 记录
+const ZIP64_MARGIN: u64 = 64 << 20;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Zip,
+    TarGz,
+}
+
+impl ArchiveFormat {
+    pub const ALL: [ArchiveFormat; 2] = [ArchiveFormat::Zip, ArchiveFormat::TarGz];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ArchiveFormat::Zip => "zip",
+            ArchiveFormat::TarGz => "tar.gz",
+        }
+    }
+
+    /// 按文件名后缀判断格式
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy().to_lowercase();
+        if name.ends_with(".zip") {
+            Some(ArchiveFormat::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveFormat::TarGz)
+        } else {
+            None
+        }
+    }
+}
+
+enum ArchiveWriter {
+    // 每个文件边读边压缩，写完后回填本地文件头中的大小和校验和
+    Zip(Box<ZipWriter<BufWriter<File>>>, SimpleFileOptions),
+    TarGz(tar::Builder<GzEncoder<BufWriter<File>>>),
+}
+
+impl ArchiveWriter {
+    fn create(path: &Path, format: ArchiveFormat) -> io::Result<Self> {
+        let file = BufWriter::new(File::create(path)?);
+        Ok(match format {
+            ArchiveFormat::Zip => {
+                // 所有文件使用同一个修改时间
+                let now = Local::now();
+                let mut options =
+                    SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+                if let Ok(time) = zip::DateTime::from_date_and_time(
+                    now.year().clamp(1980, 2107) as u16,
+                    now.month() as u8,
+                    now.day() as u8,
+                    now.hour() as u8,
+                    now.minute() as u8,
+                    now.second().min(58) as u8,
+                ) {
+                    options = options.last_modified_time(time);
+                }
+                ArchiveWriter::Zip(Box::new(ZipWriter::new(file)), options)
+            }
+            ArchiveFormat::TarGz => ArchiveWriter::TarGz(tar::Builder::new(GzEncoder::new(
+                file,
+                Compression::default(),
+            ))),
+        })
+    }
+
+    /// 写入一个文件。tar 格式需要预先知道大小，ZIP 按大小决定是否使用 ZIP64 记录
+    fn append(&mut self, name: &str, size: u64, mut reader: impl Read) -> io::Result<()> {
+        match self {
+            ArchiveWriter::Zip(zip, options) => {
+                let large_file = size + ZIP64_MARGIN > ZIP64_BYTES_THR;
+                zip.start_file(name, options.large_file(large_file))?;
+                io::copy(&mut reader, zip)?;
+                Ok(())
+            }
+            ArchiveWriter::TarGz(builder) => {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(size);
+                header.set_mode(0o644);
+                header.set_mtime(now_secs());
+                builder.append_data(&mut header, name, reader)
+            }
+        }
+    }
+
+    fn finish(self) -> io::Result<()> {
+        match self {
+            ArchiveWriter::Zip(zip, _) => (*zip)
+                .finish()?
+                .into_inner()
+                .map(|_| ())
+                .map_err(|e| e.into_error()),
+            ArchiveWriter::TarGz(builder) => builder
+                .into_inner()?
+                .finish()?
+                .into_inner()
+                .map(|_| ())
+                .map_err(|e| e.into_error()),
+        }
+    }
+}
+
+/// 读取时同时计算大小和 SHA-1，用于写出导出清单
+struct HashingReader<R> {
+    inner: R,
+    hasher: Sha1,
+    size: u64,
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.hasher.update(&buf[..read]);
+        self.size += read as u64;
+        Ok(read)
+    }
+}
+
+/// 压缩包导出选项
+pub struct ArchiveOptions<'a> {
+    pub format: ArchiveFormat,
+    pub content: ExportContent,
+    pub transform: &'a LabelTransform,
+    pub assignment: Option<&'a SplitRecord>, // 为空时不划分，图片直接放在 images/ 下
+}
+
+#[derive(Serialize)]
+struct ClassSnapshot {
+    id: i32,
+    name: String,
+    boxes: usize,
+    images: usize,
+}
+
+/// 导出时的统计快照，随数据集一起交付
+#[derive(Serialize, Default)]
+struct StatisticsSnapshot {
+    generated_at: u64,
+    images: usize,
+    completed_images: usize,
+    boxes: usize,
+    status_counts: BTreeMap<&'static str, usize>,
+    shape_counts: BTreeMap<&'static str, usize>,
+    split_counts: BTreeMap<&'static str, usize>,
+    classes: Vec<ClassSnapshot>,
+}
+
+/// 将数据集直接写入压缩包（YOLO 目录结构 + data.yaml + 导出清单 + 统计快照），返回图片数量。
+/// 图片和标签边读边写，不生成临时副本；失败时删除未写完的压缩包
+pub fn write_archive(
+    path: &Path,
+    images: &[PathBuf],
+    label_dir: &Path,
+    project: &ProjectConfig,
+    records: &HashMap<String, ImageRecord>,
+    options: &ArchiveOptions,
+) -> Result<usize, String> {
+    let result = write_entries(path, images, label_dir, project, records, options);
+    if result.is_err() {
+        let _ = fs::remove_file(path);
+    }
+    result
+}
+
+fn write_entries(
+    path: &Path,
+    images: &[PathBuf],
+    label_dir: &Path,
+    project: &ProjectConfig,
+    records: &HashMap<String, ImageRecord>,
+    options: &ArchiveOptions,
+) -> Result<usize, String> {
+    let mut writer = ArchiveWriter::create(path, options.format)
+        .map_err(|e| format!("创建压缩包失败: {}", e))?;
+    let layout = project.label_layout();
+    let mut manifest = ExportManifest {
+        profile: "archive".to_string(),
+        exported_at: now_secs(),
+        ..Default::default()
+    };
+    let mut stats = StatisticsSnapshot {
+        generated_at: manifest.exported_at,
+        images: images.len(),
+        ..Default::default()
+    };
+    let mut class_boxes: BTreeMap<i32, usize> = BTreeMap::new();
+    let mut class_images: BTreeMap<i32, usize> = BTreeMap::new();
+    let mut used_splits = BTreeSet::new();
+
+    let mut add = |writer: &mut ArchiveWriter,
+                   name: String,
+                   kind: FileKind,
+                   source: &Path,
+                   size: u64,
+                   reader: &mut dyn Read|
+     -> Result<(), String> {
+        let mut hashing = HashingReader {
+            inner: reader,
+            hasher: Sha1::new(),
+            size: 0,
+        };
+        writer
+            .append(&name, size, &mut hashing)
+            .map_err(|e| format!("写入 {} 失败: {}", name, e))?;
+        let mut sha1 = String::with_capacity(40);
+        for byte in hashing.hasher.finalize() {
+            let _ = write!(sha1, "{:02x}", byte);
+        }
+        manifest.files.insert(
+            name,
+            ManifestEntry {
+                kind,
+                source: source.to_path_buf(),
+                size: hashing.size,
+                sha1,
+            },
+        );
+        Ok(())
+    };
+
+    for image in images {
+        let file_name = image
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let split = options.assignment.and_then(|a| a.get(&file_name)).copied();
+        let subdir = match split {
+            Some(split) => {
+                used_splits.insert(split);
+                *stats.split_counts.entry(split.name()).or_insert(0) += 1;
+                format!("/{}", split.name())
+            }
+            None => String::new(),
+        };
+
+        let record = records.get(&file_name);
+        let status = record.map(|r| r.status).unwrap_or_default();
+        *stats.status_counts.entry(status.id()).or_insert(0) += 1;
+        if status.is_done() {
+            stats.completed_images += 1;
+        }
+
+        if options.content.images() {
+            let file = File::open(image).map_err(|e| format!("读取 {} 失败: {}", file_name, e))?;
+            let size = file
+                .metadata()
+                .map_err(|e| format!("读取 {} 失败: {}", file_name, e))?
+                .len();
+            add(
+                &mut writer,
+                format!("images{}/{}", subdir, file_name),
+                FileKind::Image,
+                image,
+                size,
+                &mut io::BufReader::new(file),
+            )?;
+        }
+
+        let label_path = labels::label_path(label_dir, image);
+        // 统计快照按转换后的标注计算
+        let mut boxes = labels::read_label_file(&label_path, &layout);
+        options.transform.apply(&mut boxes);
+        stats.boxes += boxes.len();
+        let classes: BTreeSet<i32> = boxes.iter().map(|b| b.class).collect();
+        for bbox in &boxes {
+            *class_boxes.entry(bbox.class).or_insert(0) += 1;
+            *stats.shape_counts.entry(bbox.kind().label()).or_insert(0) += 1;
+        }
+        for class in classes {
+            *class_images.entry(class).or_insert(0) += 1;
+        }
+
+        if !options.content.labels() || !label_path.exists() {
+            continue;
+        }
+        let label_name = label_path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if !options.transform.is_identity() {
+            let mut content = Vec::new();
+            labels::write_labels(&mut content, &boxes, &layout)
+                .map_err(|e| format!("转换标签失败 {}: {}", label_name, e))?;
+            add(
+                &mut writer,
+                format!("labels{}/{}", subdir, label_name),
+                FileKind::Label,
+                &label_path,
+                content.len() as u64,
+                &mut content.as_slice(),
+            )?;
+        } else {
+            let file =
+                File::open(&label_path).map_err(|e| format!("读取 {} 失败: {}", label_name, e))?;
+            let size = file
+                .metadata()
+                .map_err(|e| format!("读取 {} 失败: {}", label_name, e))?
+                .len();
+            add(
+                &mut writer,
+                format!("labels{}/{}", subdir, label_name),
+                FileKind::Label,
+                &label_path,
+                size,
+                &mut io::BufReader::new(file),
+            )?;
+        }
+        // 框属性文件放在标签旁边，训练时会被忽略；重映射删除框后序号会变，需要重新生成
+        let attributes_path = labels::attributes_path(&label_path);
+        let name = attributes_path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if !options.transform.is_identity() {
+            let content = labels::attributes_content(&boxes)
+                .map_err(|e| format!("转换属性失败 {}: {}", name, e))?;
+            if let Some(content) = content {
+                add(
+                    &mut writer,
+                    format!("labels{}/{}", subdir, name),
+                    FileKind::Label,
+                    &attributes_path,
+                    content.len() as u64,
+                    &mut content.as_bytes(),
+                )?;
+            }
+        } else if let Ok(file) = File::open(&attributes_path) {
+            let size = file
+                .metadata()
+                .map_err(|e| format!("读取 {} 失败: {}", name, e))?
+                .len();
+            add(
+                &mut writer,
+                format!("labels{}/{}", subdir, name),
+                FileKind::Label,
+                &attributes_path,
+                size,
+                &mut io::BufReader::new(file),
+            )?;
+        }
+    }
+
+    stats.classes = class_boxes
+        .iter()
+        .map(|(class, boxes)| ClassSnapshot {
+            id: *class,
+            name: project.class_name(*class),
+            boxes: *boxes,
+            images: class_images.get(class).copied().unwrap_or(0),
+        })
+        .collect();
+    let dirs = if options.assignment.is_some() {
+        Split::yaml_dirs(&used_splits)
+    } else {
+        vec![
+            (Split::Train.name(), "images".to_string()),
+            (Split::Val.name(), "images".to_string()),
+        ]
+    };
+    let max_class = class_boxes.keys().next_back().copied();
+    let data_yaml = split::data_yaml(project, None, &dirs, max_class);
+    let statistics =
+        serde_json::to_string_pretty(&stats).map_err(|e| format!("序列化统计快照失败: {}", e))?;
+    let manifest_json = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| format!("序列化导出清单失败: {}", e))?;
+    for (name, content) in [
+        ("data.yaml", data_yaml),
+        (STATISTICS_FILE_NAME, statistics),
+        (MANIFEST_FILE_NAME, manifest_json),
+    ] {
+        writer
+            .append(name, content.len() as u64, content.as_bytes())
+            .map_err(|e| format!("写入 {} 失败: {}", name, e))?;
+    }
+
+    writer
+        .finish()
+        .map_err(|e| format!("写入压缩包失败: {}", e))?;
+    Ok(images.len())
+}
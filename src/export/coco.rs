@@ -0,0 +1,146 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::ExportItem;
+use crate::models::{AttributeValue, Shape};
+use crate::project::ProjectConfig;
+
+#[derive(Serialize)]
+struct CocoDataset {
+    images: Vec<CocoImage>,
+    annotations: Vec<CocoAnnotation>,
+    categories: Vec<CocoCategory>,
+}
+
+#[derive(Serialize)]
+struct CocoImage {
+    id: usize,
+    file_name: String,
+    width: u32,
+    height: u32,
+}
+
+#[derive(Serialize)]
+struct CocoAnnotation {
+    id: usize,
+    image_id: usize,
+    category_id: i32,
+    bbox: [f64; 4], // 左上角 x, y, 宽, 高（像素）
+    area: f64,
+    iscrowd: u8,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    segmentation: Vec<Vec<f64>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    keypoints: Vec<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_keypoints: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    score: Option<f64>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    attributes: BTreeMap<String, AttributeValue>,
+}
+
+#[derive(Serialize)]
+struct CocoCategory {
+    id: i32,
+    name: String,
+    supercategory: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    keypoints: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    skeleton: Vec<[usize; 2]>, // COCO 中关键点序号从 1 开始
+}
+
+/// 生成 COCO 格式的 JSON。category_id 与 YOLO 类别编号相同；
+/// 多边形和旋转框写入 segmentation，关键点写入 keypoints，框属性写入 attributes
+pub fn to_json(items: &[ExportItem], project: &ProjectConfig) -> Result<String, String> {
+    let mut images = Vec::new();
+    let mut annotations = Vec::new();
+    let mut classes: BTreeSet<i32> = project.class_names.keys().copied().collect();
+
+    for (index, item) in items.iter().enumerate() {
+        let image_id = index + 1;
+        images.push(CocoImage {
+            id: image_id,
+            file_name: item.file_name.clone(),
+            width: item.width,
+            height: item.height,
+        });
+
+        for bbox in &item.boxes {
+            classes.insert(bbox.class);
+            let [x, y] = item.to_pixels([bbox.x - bbox.width / 2.0, bbox.y - bbox.height / 2.0]);
+            let [w, h] = item.to_pixels([bbox.width, bbox.height]);
+
+            let segmentation: Vec<Vec<f64>> = match &bbox.shape {
+                Shape::Rect => Vec::new(),
+                Shape::Oriented(_) | Shape::Polygon(_) => vec![
+                    bbox.outline()
+                        .into_iter()
+                        .flat_map(|p| item.to_pixels(p))
+                        .collect(),
+                ],
+            };
+            let keypoints: Vec<f64> = bbox
+                .keypoints
+                .iter()
+                .flat_map(|kp| {
+                    let [kx, ky] = item.to_pixels([kp.x, kp.y]);
+                    [kx, ky, kp.visibility as f64]
+                })
+                .collect();
+
+            annotations.push(CocoAnnotation {
+                id: annotations.len() + 1,
+                image_id,
+                category_id: bbox.class,
+                bbox: [x, y, w, h],
+                area: match &bbox.shape {
+                    Shape::Rect => w * h,
+                    _ => polygon_area(&segmentation[0]),
+                },
+                iscrowd: 0,
+                segmentation,
+                num_keypoints: (!keypoints.is_empty())
+                    .then(|| bbox.keypoints.iter().filter(|kp| kp.is_labeled()).count()),
+                keypoints,
+                score: bbox.confidence,
+                attributes: bbox.attributes.clone(),
+            });
+        }
+    }
+
+    let categories = classes
+        .into_iter()
+        .map(|class| {
+            let schema = project.keypoint_schemas.get(&class);
+            CocoCategory {
+                id: class,
+                name: project.class_name(class),
+                supercategory: String::new(),
+                keypoints: schema.map(|s| s.names.clone()).unwrap_or_default(),
+                skeleton: schema
+                    .map(|s| s.skeleton.iter().map(|[a, b]| [a + 1, b + 1]).collect())
+                    .unwrap_or_default(),
+            }
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&CocoDataset {
+        images,
+        annotations,
+        categories,
+    })
+    .map_err(|e| format!("序列化 COCO 数据失败: {}", e))
+}
+
+/// 鞋带公式计算多边形面积，输入为 x1, y1, x2, y2, ...
+fn polygon_area(flat: &[f64]) -> f64 {
+    let points: Vec<&[f64]> = flat.chunks(2).collect();
+    let mut sum = 0.0;
+    for i in 0..points.len() {
+        let (a, b) = (points[i], points[(i + 1) % points.len()]);
+        sum += a[0] * b[1] - b[0] * a[1];
+    }
+    sum.abs() / 2.0
+}
@@ -0,0 +1,256 @@
+use image::{DynamicImage, GenericImageView, Rgb, RgbImage, imageops};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::LabelTransform;
+use crate::labels::{self, LabelLayout};
+use crate::project::ProjectConfig;
+use crate::utils::csv_field;
+
+/// 裁剪映射表文件，保存在导出目录下
+pub const CROPS_CSV_FILE_NAME: &str = "crops.csv";
+/// letterbox 填充颜色，与 YOLO 预处理一致
+const LETTERBOX_COLOR: Rgb<u8> = Rgb([114, 114, 114]);
+
+/// 裁剪区域的形状
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CropMode {
+    Tight,     // 按框（加边距）裁剪
+    Square,    // 以框中心扩展为正方形后裁剪，包含更多背景
+    Letterbox, // 按框裁剪后用灰色填充为正方形，不引入额外背景
+}
+
+impl CropMode {
+    pub const ALL: [CropMode; 3] = [CropMode::Tight, CropMode::Square, CropMode::Letterbox];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CropMode::Tight => "按框裁剪",
+            CropMode::Square => "扩展为正方形",
+            CropMode::Letterbox => "填充为正方形",
+        }
+    }
+}
+
+/// 裁剪导出设置，保存在项目配置中
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CropConfig {
+    pub padding: f64, // 每边外扩的比例（相对框的宽高）
+    pub mode: CropMode,
+    pub min_size: u32,    // 框的短边小于该像素数时跳过
+    pub output_size: u32, // 输出图片的长边，0 表示保持原始分辨率
+    pub jpeg_quality: u8,
+}
+
+impl Default for CropConfig {
+    fn default() -> Self {
+        Self {
+            padding: 0.1,
+            mode: CropMode::Tight,
+            min_size: 8,
+            output_size: 0,
+            jpeg_quality: 95,
+        }
+    }
+}
+
+/// 裁剪导出结果
+#[derive(Default)]
+pub struct CropReport {
+    pub crops: usize,
+    pub skipped: usize, // 小于最小尺寸的框
+    pub failures: Vec<String>,
+}
+
+/// 从原始分辨率的图片中裁剪每个框，按 ImageFolder 结构写出
+/// `crops/{类别名}/{图片名}_{扩展名}_{序号}.jpg`（类别按重映射后的编号，序号为框在原标签文件中的序号，
+/// 扩展名避免 `a.jpg` 与 `a.png` 重名），并写出将裁剪图对应回原始框的 `crops.csv`。
+/// 单张图片读取或写出失败时记录后继续
+pub fn export_crops(
+    images: &[PathBuf],
+    label_dir: &Path,
+    layout: &LabelLayout,
+    transform: &LabelTransform,
+    project: &ProjectConfig,
+    config: &CropConfig,
+    out_dir: &Path,
+) -> Result<CropReport, String> {
+    let crops_dir = out_dir.join("crops");
+    fs::create_dir_all(&crops_dir).map_err(|e| format!("创建导出目录失败: {}", e))?;
+    let mut report = CropReport::default();
+    let mut csv = String::from(
+        "crop,image,box_index,class,class_name,confidence,crop_x1,crop_y1,crop_x2,crop_y2\n",
+    );
+
+    for image_path in images {
+        // 逐个转换并保留原始序号，重映射删除的框不影响其余框的序号
+        let boxes: Vec<_> =
+            labels::read_label_file(&labels::label_path(label_dir, image_path), layout)
+                .into_iter()
+                .enumerate()
+                .filter_map(|(index, bbox)| {
+                    let mut single = vec![bbox];
+                    transform.apply(&mut single);
+                    single.pop().map(|bbox| (index, bbox))
+                })
+                .collect();
+        if boxes.is_empty() {
+            continue;
+        }
+        let image_name = image_path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        // 使用原始分辨率，而不是画布显示用的缩放图
+        let image = match image::open(image_path) {
+            Ok(image) => image,
+            Err(e) => {
+                report
+                    .failures
+                    .push(format!("读取 {} 失败: {}", image_name, e));
+                continue;
+            }
+        };
+        let stem = Path::new(&image_name)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let extension = Path::new(&image_name)
+            .extension()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        for (index, bbox) in &boxes {
+            let (width, height) = image.dimensions();
+            let box_width = bbox.width * width as f64;
+            let box_height = bbox.height * height as f64;
+            if box_width.min(box_height) < config.min_size as f64 {
+                report.skipped += 1;
+                continue;
+            }
+            let (x1, y1, x2, y2) = crop_region(
+                bbox.x * width as f64,
+                bbox.y * height as f64,
+                box_width,
+                box_height,
+                config,
+                width,
+                height,
+            );
+            if x2 <= x1 || y2 <= y1 {
+                report.skipped += 1;
+                continue;
+            }
+
+            let class_name = project.class_name(bbox.class);
+            let class_dir = crops_dir.join(dir_name(&class_name));
+            let file_name = format!("{}_{}_{}.jpg", stem, extension, index);
+            let target = class_dir.join(&file_name);
+            let crop = finish_crop(image.crop_imm(x1, y1, x2 - x1, y2 - y1), config);
+            let result = fs::create_dir_all(&class_dir)
+                .map_err(|e| e.to_string())
+                .and_then(|()| save_jpeg(&crop, &target, config.jpeg_quality));
+            if let Err(e) = result {
+                report
+                    .failures
+                    .push(format!("写出 {} 失败: {}", file_name, e));
+                continue;
+            }
+
+            let relative = format!("crops/{}/{}", dir_name(&class_name), file_name);
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{},{},{},{},{},{}",
+                csv_field(&relative),
+                csv_field(&image_name),
+                index,
+                bbox.class,
+                csv_field(&class_name),
+                bbox.confidence.map(|c| c.to_string()).unwrap_or_default(),
+                x1,
+                y1,
+                x2,
+                y2
+            );
+            report.crops += 1;
+        }
+    }
+
+    fs::write(out_dir.join(CROPS_CSV_FILE_NAME), csv)
+        .map_err(|e| format!("写入 {} 失败: {}", CROPS_CSV_FILE_NAME, e))?;
+    Ok(report)
+}
+
+/// 计算像素裁剪区域（左上角、右下角），加边距并按模式扩展后限制在图片范围内
+fn crop_region(
+    center_x: f64,
+    center_y: f64,
+    box_width: f64,
+    box_height: f64,
+    config: &CropConfig,
+    width: u32,
+    height: u32,
+) -> (u32, u32, u32, u32) {
+    let padding = config.padding.max(0.0);
+    let mut half_width = box_width * (0.5 + padding);
+    let mut half_height = box_height * (0.5 + padding);
+    if config.mode == CropMode::Square {
+        half_width = half_width.max(half_height);
+        half_height = half_width;
+    }
+    let clamp = |value: f64, max: u32| value.round().clamp(0.0, max as f64) as u32;
+    (
+        clamp(center_x - half_width, width),
+        clamp(center_y - half_height, height),
+        clamp(center_x + half_width, width),
+        clamp(center_y + half_height, height),
+    )
+}
+
+/// 按模式填充为正方形并缩放到输出尺寸
+fn finish_crop(crop: DynamicImage, config: &CropConfig) -> RgbImage {
+    let mut crop = crop.to_rgb8();
+    if config.mode == CropMode::Letterbox {
+        let side = crop.width().max(crop.height());
+        let mut canvas = RgbImage::from_pixel(side, side, LETTERBOX_COLOR);
+        imageops::overlay(
+            &mut canvas,
+            &crop,
+            ((side - crop.width()) / 2) as i64,
+            ((side - crop.height()) / 2) as i64,
+        );
+        crop = canvas;
+    }
+    let longest = crop.width().max(crop.height());
+    if config.output_size > 0 && longest != config.output_size {
+        let scale = config.output_size as f64 / longest as f64;
+        let new_width = ((crop.width() as f64 * scale).round() as u32).max(1);
+        let new_height = ((crop.height() as f64 * scale).round() as u32).max(1);
+        crop = imageops::resize(&crop, new_width, new_height, imageops::FilterType::Triangle);
+    }
+    crop
+}
+
+fn save_jpeg(crop: &RgbImage, path: &Path, quality: u8) -> Result<(), String> {
+    let file = fs::File::create(path).map_err(|e| e.to_string())?;
+    let mut encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(
+        std::io::BufWriter::new(file),
+        quality.clamp(1, 100),
+    );
+    encoder.encode_image(crop).map_err(|e| e.to_string())
+}
+
+/// 类别名中不能用作目录名的字符替换为下划线
+fn dir_name(class_name: &str) -> String {
+    class_name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c => c,
+        })
+        .collect()
+}
@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use super::FileKind;
+
+/// 导出清单文件，保存在导出目录下，记录每个导出文件的来源和内容摘要
+pub const MANIFEST_FILE_NAME: &str = "export_manifest.json";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub kind: FileKind,
+    pub source: PathBuf,
+    pub size: u64,
+    pub sha1: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportManifest {
+    pub profile: String,                        // 最近一次导出使用的方案
+    pub exported_at: u64,                       // 最近一次导出时间（Unix 秒）
+    pub files: BTreeMap<String, ManifestEntry>, // 相对导出目录的路径 -> 文件信息
+}
+
+impl ExportManifest {
+    pub fn load(out_dir: &Path) -> Self {
+        fs::read_to_string(out_dir.join(MANIFEST_FILE_NAME))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, out_dir: &Path) -> Result<(), String> {
+        let content =
+            serde_json::to_string_pretty(self).map_err(|e| format!("序列化导出清单失败: {}", e))?;
+        fs::write(out_dir.join(MANIFEST_FILE_NAME), content)
+            .map_err(|e| format!("保存导出清单失败: {}", e))
+    }
+}
+
+/// 文件大小和 SHA-1 摘要
+pub fn file_digest(path: &Path) -> std::io::Result<(u64, String)> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha1::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut size = 0;
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        size += read as u64;
+    }
+    let mut hex = String::with_capacity(40);
+    for byte in hasher.finalize() {
+        let _ = write!(hex, "{:02x}", byte);
+    }
+    Ok((size, hex))
+}
+
+/// 两个文件内容是否相同：先比较大小，大小相同时再比较摘要
+pub fn same_content(a: &Path, b: &Path) -> bool {
+    let (Ok(meta_a), Ok(meta_b)) = (fs::metadata(a), fs::metadata(b)) else {
+        return false;
+    };
+    if meta_a.len() != meta_b.len() {
+        return false;
+    }
+    match (file_digest(a), file_digest(b)) {
+        (Ok(digest_a), Ok(digest_b)) => digest_a == digest_b,
+        _ => false,
+    }
+}
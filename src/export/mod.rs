@@ -0,0 +1,114 @@
+pub mod archive;
+pub mod coco;
+pub mod crops;
+pub mod manifest;
+pub mod profile;
+pub mod voc;
+
+pub use archive::{ArchiveFormat, ArchiveOptions};
+pub use crops::{CropConfig, CropMode, CropReport};
+pub use manifest::{ExportManifest, ManifestEntry};
+pub use profile::{
+    CollisionPolicy, ExportContent, ExportProfile, ExportReport, ExportScope, FileKind, PlanAction,
+    PlannedFile, TransferMode,
+};
+
+use std::path::{Path, PathBuf};
+
+use crate::labels::{self, LabelLayout};
+use crate::models::BoundingBox;
+use crate::remap::{self, RemapTable};
+
+/// 导出时对标注的转换：类别重映射、多边形/旋转框转换为外接水平框
+#[derive(Clone, Default)]
+pub struct LabelTransform {
+    pub axis_aligned: bool,
+    pub remap: Option<RemapTable>,
+    pub skip_empty: bool, // 跳过重映射后不再有框的图片
+}
+
+impl LabelTransform {
+    /// 是否原样导出标签文件
+    pub fn is_identity(&self) -> bool {
+        !self.axis_aligned && self.remap.is_none()
+    }
+
+    /// 转换一组框，返回是否因重映射变为空（原本没有框的图片不算）
+    pub fn apply(&self, boxes: &mut Vec<BoundingBox>) -> bool {
+        let had_boxes = !boxes.is_empty();
+        if let Some(table) = &self.remap {
+            remap::apply(table, boxes);
+        }
+        if self.axis_aligned {
+            for bbox in boxes.iter_mut() {
+                bbox.make_axis_aligned();
+            }
+        }
+        had_boxes && boxes.is_empty()
+    }
+
+    /// 去掉转换后需要跳过的图片
+    pub fn filter_images(
+        &self,
+        images: Vec<PathBuf>,
+        label_dir: &Path,
+        layout: &LabelLayout,
+    ) -> Vec<PathBuf> {
+        if !self.skip_empty || self.remap.is_none() {
+            return images;
+        }
+        images
+            .into_iter()
+            .filter(|image| {
+                let mut boxes =
+                    labels::read_label_file(&labels::label_path(label_dir, image), layout);
+                !self.apply(&mut boxes)
+            })
+            .collect()
+    }
+}
+
+/// 导出用的单张图片：像素尺寸和标注
+pub struct ExportItem {
+    pub image_path: PathBuf,
+    pub file_name: String,
+    pub width: u32,
+    pub height: u32,
+    pub boxes: Vec<BoundingBox>,
+}
+
+impl ExportItem {
+    /// 归一化坐标 -> 像素坐标
+    pub fn to_pixels(&self, [x, y]: [f64; 2]) -> [f64; 2] {
+        [x * self.width as f64, y * self.height as f64]
+    }
+}
+
+/// 读取图片尺寸和标签（包括属性文件）并按导出设置转换，读取图片尺寸失败时返回错误
+pub fn collect_items(
+    images: &[PathBuf],
+    label_dir: &Path,
+    layout: &LabelLayout,
+    transform: &LabelTransform,
+) -> Result<Vec<ExportItem>, String> {
+    images
+        .iter()
+        .map(|image_path| {
+            let (width, height) = image::image_dimensions(image_path)
+                .map_err(|e| format!("读取图片尺寸失败 {}: {}", image_path.display(), e))?;
+            let mut boxes =
+                labels::read_label_file(&labels::label_path(label_dir, image_path), layout);
+            transform.apply(&mut boxes);
+            Ok(ExportItem {
+                image_path: image_path.clone(),
+                file_name: image_path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                width,
+                height,
+                boxes,
+            })
+        })
+        .collect()
+}
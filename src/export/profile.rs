@@ -0,0 +1,391 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::manifest::{self, ExportManifest};
+use crate::labels;
+use crate::models::ImageStatus;
+
+/// 导出范围
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportScope {
+    All,                 // 图片目录中的全部图片
+    Modified,            // 人工修改过的图片
+    Status(ImageStatus), // 指定状态的图片
+    Filtered,            // 左侧文件列表（当前过滤条件）
+}
+
+impl ExportScope {
+    pub fn label(&self) -> String {
+        match self {
+            ExportScope::All => "全部图片".to_string(),
+            ExportScope::Modified => "已修改的图片".to_string(),
+            ExportScope::Status(status) => format!("状态: {}", status.label()),
+            ExportScope::Filtered => "当前文件列表".to_string(),
+        }
+    }
+}
+
+/// 导出内容
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportContent {
+    Images,
+    Labels,
+    Both,
+}
+
+impl ExportContent {
+    pub const ALL: [ExportContent; 3] = [
+        ExportContent::Both,
+        ExportContent::Labels,
+        ExportContent::Images,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ExportContent::Images => "仅图片",
+            ExportContent::Labels => "仅标签",
+            ExportContent::Both => "图片+标签",
+        }
+    }
+
+    pub fn images(&self) -> bool {
+        *self != ExportContent::Labels
+    }
+
+    pub fn labels(&self) -> bool {
+        *self != ExportContent::Images
+    }
+}
+
+/// 文件写出方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferMode {
+    Copy,
+    HardLink,
+    SymLink,
+}
+
+impl TransferMode {
+    pub const ALL: [TransferMode; 3] = [
+        TransferMode::Copy,
+        TransferMode::HardLink,
+        TransferMode::SymLink,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TransferMode::Copy => "复制",
+            TransferMode::HardLink => "硬链接",
+            TransferMode::SymLink => "符号链接",
+        }
+    }
+}
+
+/// 目标文件已存在时的处理方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CollisionPolicy {
+    Update,    // 内容相同的文件跳过，不同的覆盖，可中断后重新导出
+    Fail,      // 中止导出
+    Skip,      // 保留已有文件
+    Overwrite, // 覆盖
+    Rename,    // 图片和标签一起改名为 name_1、name_2 ...
+}
+
+impl CollisionPolicy {
+    pub const ALL: [CollisionPolicy; 5] = [
+        CollisionPolicy::Update,
+        CollisionPolicy::Fail,
+        CollisionPolicy::Skip,
+        CollisionPolicy::Overwrite,
+        CollisionPolicy::Rename,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CollisionPolicy::Update => "增量更新",
+            CollisionPolicy::Fail => "中止",
+            CollisionPolicy::Skip => "跳过",
+            CollisionPolicy::Overwrite => "覆盖",
+            CollisionPolicy::Rename => "重命名",
+        }
+    }
+}
+
+/// 导出方案，保存在项目配置中
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExportProfile {
+    pub name: String,
+    pub scope: ExportScope,
+    pub content: ExportContent,
+    pub transfer: TransferMode,
+    pub collision: CollisionPolicy,
+    #[serde(default)]
+    pub remove_stale: bool, // 删除上次导出过、本次不再导出的文件（只删除导出清单中记录的文件）
+}
+
+impl ExportProfile {
+    /// 项目中没有导出方案时提供的默认方案
+    pub fn presets() -> Vec<ExportProfile> {
+        let profile = |name: &str, scope, content| ExportProfile {
+            name: name.to_string(),
+            scope,
+            content,
+            transfer: TransferMode::Copy,
+            collision: CollisionPolicy::Update,
+            remove_stale: false,
+        };
+        vec![
+            profile(
+                "已修改的图片和标签",
+                ExportScope::Modified,
+                ExportContent::Both,
+            ),
+            profile("全部标签", ExportScope::All, ExportContent::Labels),
+            profile(
+                "当前列表的图片",
+                ExportScope::Filtered,
+                ExportContent::Images,
+            ),
+        ]
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileKind {
+    Image,
+    Label,
+}
+
+/// 单个文件的处理结果
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlanAction {
+    Write,
+    Overwrite,
+    Renamed,
+    Skip,
+    Unchanged, // 目标文件与源文件内容相同
+    Remove,    // 上次导出过、本次不再导出
+}
+
+impl PlanAction {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PlanAction::Write => "写入",
+            PlanAction::Overwrite => "覆盖",
+            PlanAction::Renamed => "重命名",
+            PlanAction::Skip => "跳过",
+            PlanAction::Unchanged => "未变化",
+            PlanAction::Remove => "删除",
+        }
+    }
+}
+
+/// 导出计划中的一个文件，预览和实际导出使用同一份计划
+#[derive(Clone, Debug)]
+pub struct PlannedFile {
+    pub kind: FileKind,
+    pub source: PathBuf, // 删除旧文件时为空
+    pub target: PathBuf,
+    pub action: PlanAction,
+}
+
+/// 导出结果，单个文件失败不会中止导出
+#[derive(Clone, Debug, Default)]
+pub struct ExportReport {
+    pub written: usize,
+    pub unchanged: usize,
+    pub skipped: usize,
+    pub removed: usize,
+    pub failures: Vec<String>,
+}
+
+impl ExportReport {
+    pub fn summary(&self) -> String {
+        let mut text = format!(
+            "写入 {} 个文件，未变化 {}，跳过 {}，删除 {}",
+            self.written, self.unchanged, self.skipped, self.removed
+        );
+        if !self.failures.is_empty() {
+            text.push_str(&format!(
+                "\n{} 个文件失败（重新导出会继续处理）:",
+                self.failures.len()
+            ));
+            for failure in self.failures.iter().take(10) {
+                text.push('\n');
+                text.push_str(failure);
+            }
+            if self.failures.len() > 10 {
+                text.push_str("\n……");
+            }
+        }
+        text
+    }
+}
+
+/// 标签文件及其属性文件是否与已导出的文件相同
+fn same_label(source: &Path, target: &Path) -> bool {
+    let (source_attributes, target_attributes) = (
+        labels::attributes_path(source),
+        labels::attributes_path(target),
+    );
+    manifest::same_content(source, target)
+        && match (source_attributes.exists(), target_attributes.exists()) {
+            (true, true) => manifest::same_content(&source_attributes, &target_attributes),
+            (false, false) => true,
+            _ => false,
+        }
+}
+
+/// 生成导出计划：图片写入 `images/`，标签写入 `labels/`。
+/// 没有标签文件的图片只导出图片；冲突按图片和标签成对处理，保证改名后仍然同名。
+/// `convert_labels` 为真时标签需要转换后写出，增量更新无法比较内容，总是重新写出
+pub fn plan(
+    profile: &ExportProfile,
+    images: &[PathBuf],
+    label_dir: &Path,
+    out_dir: &Path,
+    convert_labels: bool,
+) -> Result<Vec<PlannedFile>, String> {
+    let images_dir = out_dir.join("images");
+    let labels_dir = out_dir.join("labels");
+    let mut planned = Vec::new();
+    let mut claimed = HashSet::new();
+
+    for image in images {
+        let Some(file_name) = image.file_name().map(|n| n.to_string_lossy().to_string()) else {
+            continue;
+        };
+        let stem = Path::new(&file_name)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let extension = Path::new(&file_name)
+            .extension()
+            .map(|e| format!(".{}", e.to_string_lossy()))
+            .unwrap_or_default();
+        let label = labels::label_path(label_dir, image);
+
+        let mut sources = Vec::new();
+        if profile.content.images() && image.exists() {
+            sources.push((
+                FileKind::Image,
+                image.clone(),
+                images_dir.clone(),
+                extension,
+            ));
+        }
+        if profile.content.labels() && label.exists() {
+            sources.push((
+                FileKind::Label,
+                label,
+                labels_dir.clone(),
+                ".txt".to_string(),
+            ));
+        }
+        if sources.is_empty() {
+            continue;
+        }
+
+        // 已存在的文件或本次导出中同名的其他图片都视为冲突
+        let taken = |stem: &str| {
+            sources.iter().any(|(_, _, dir, ext)| {
+                let target = dir.join(format!("{}{}", stem, ext));
+                target.exists() || claimed.contains(&target)
+            })
+        };
+        let (target_stem, action) = if !taken(&stem) {
+            (stem.clone(), PlanAction::Write)
+        } else {
+            match profile.collision {
+                // 逐个文件比较，见下方
+                CollisionPolicy::Update => (stem.clone(), PlanAction::Overwrite),
+                CollisionPolicy::Fail => {
+                    return Err(format!("目标目录中已存在 {}，导出已中止", stem));
+                }
+                CollisionPolicy::Skip => (stem.clone(), PlanAction::Skip),
+                CollisionPolicy::Overwrite => (stem.clone(), PlanAction::Overwrite),
+                CollisionPolicy::Rename => {
+                    let renamed = (1..)
+                        .map(|n| format!("{}_{}", stem, n))
+                        .find(|candidate| !taken(candidate))
+                        .unwrap_or_default();
+                    (renamed, PlanAction::Renamed)
+                }
+            }
+        };
+
+        for (kind, source, dir, ext) in sources {
+            let target = dir.join(format!("{}{}", target_stem, ext));
+            let action = match action {
+                PlanAction::Overwrite if profile.collision == CollisionPolicy::Update => {
+                    let exists = target.exists();
+                    let unchanged = exists
+                        && match kind {
+                            FileKind::Image => manifest::same_content(&source, &target),
+                            FileKind::Label => !convert_labels && same_label(&source, &target),
+                        };
+                    match (unchanged, exists) {
+                        (true, _) => PlanAction::Unchanged,
+                        (false, true) => PlanAction::Overwrite,
+                        (false, false) => PlanAction::Write,
+                    }
+                }
+                action => action,
+            };
+            claimed.insert(target.clone());
+            planned.push(PlannedFile {
+                kind,
+                source,
+                target,
+                action,
+            });
+        }
+    }
+
+    if profile.remove_stale {
+        let previous = ExportManifest::load(out_dir);
+        for (relative, entry) in previous.files {
+            let target = out_dir.join(&relative);
+            if !claimed.contains(&target) && target.symlink_metadata().is_ok() {
+                planned.push(PlannedFile {
+                    kind: entry.kind,
+                    source: PathBuf::new(),
+                    target,
+                    action: PlanAction::Remove,
+                });
+            }
+        }
+    }
+    Ok(planned)
+}
+
+/// 按指定方式写出单个文件，目标已存在时先删除
+pub fn transfer_file(source: &Path, target: &Path, mode: TransferMode) -> std::io::Result<()> {
+    if target.symlink_metadata().is_ok() {
+        fs::remove_file(target)?;
+    }
+    match mode {
+        TransferMode::Copy => fs::copy(source, target).map(|_| ()),
+        TransferMode::HardLink => fs::hard_link(source, target),
+        TransferMode::SymLink => {
+            // 使用绝对路径，导出目录移动后链接仍然有效
+            let source = source.canonicalize()?;
+            #[cfg(unix)]
+            return std::os::unix::fs::symlink(source, target);
+            #[cfg(windows)]
+            return std::os::windows::fs::symlink_file(source, target);
+            #[cfg(not(any(unix, windows)))]
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "当前系统不支持符号链接",
+            ));
+        }
+    }
+}
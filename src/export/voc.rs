@@ -0,0 +1,101 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use super::ExportItem;
+use crate::project::ProjectConfig;
+
+/// VOC 中有专门字段的属性，其余属性写入 attributes 节点
+const VOC_FLAGS: [&str; 3] = ["truncated", "difficult", "occluded"];
+
+/// 每张图片写出一个 Pascal VOC XML 文件，返回写出的文件数量。
+/// 多边形和旋转框写为外接水平框，关键点不导出
+pub fn write_dir(
+    items: &[ExportItem],
+    project: &ProjectConfig,
+    out_dir: &Path,
+) -> Result<usize, String> {
+    fs::create_dir_all(out_dir).map_err(|e| format!("创建导出目录失败: {}", e))?;
+    for item in items {
+        let stem = Path::new(&item.file_name)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let path = out_dir.join(format!("{}.xml", stem));
+        fs::write(&path, to_xml(item, project))
+            .map_err(|e| format!("写入 {} 失败: {}", path.display(), e))?;
+    }
+    Ok(items.len())
+}
+
+fn to_xml(item: &ExportItem, project: &ProjectConfig) -> String {
+    let folder = item
+        .image_path
+        .parent()
+        .and_then(|p| p.file_name())
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let mut xml = String::from("<annotation>\n");
+    let _ = writeln!(xml, "  <folder>{}</folder>", escape(&folder));
+    let _ = writeln!(xml, "  <filename>{}</filename>", escape(&item.file_name));
+    let _ = writeln!(
+        xml,
+        "  <size>\n    <width>{}</width>\n    <height>{}</height>\n    <depth>3</depth>\n  </size>",
+        item.width, item.height
+    );
+    xml.push_str("  <segmented>0</segmented>\n");
+
+    for bbox in &item.boxes {
+        let [x1, y1] = item.to_pixels([bbox.x - bbox.width / 2.0, bbox.y - bbox.height / 2.0]);
+        let [x2, y2] = item.to_pixels([bbox.x + bbox.width / 2.0, bbox.y + bbox.height / 2.0]);
+        let flag = |name: &str| u8::from(bbox.attributes.get(name).is_some_and(|v| v.as_flag()));
+
+        xml.push_str("  <object>\n");
+        let _ = writeln!(
+            xml,
+            "    <name>{}</name>",
+            escape(&project.class_name(bbox.class))
+        );
+        xml.push_str("    <pose>Unspecified</pose>\n");
+        for name in VOC_FLAGS {
+            let _ = writeln!(xml, "    <{}>{}</{}>", name, flag(name), name);
+        }
+        let _ = writeln!(
+            xml,
+            "    <bndbox>\n      <xmin>{}</xmin>\n      <ymin>{}</ymin>\n      <xmax>{}</xmax>\n      <ymax>{}</ymax>\n    </bndbox>",
+            x1.round() as i64,
+            y1.round() as i64,
+            x2.round() as i64,
+            y2.round() as i64
+        );
+        let others: Vec<_> = bbox
+            .attributes
+            .iter()
+            .filter(|(name, _)| !VOC_FLAGS.contains(&name.as_str()))
+            .collect();
+        if !others.is_empty() {
+            xml.push_str("    <attributes>\n");
+            for (name, value) in others {
+                let _ = writeln!(
+                    xml,
+                    "      <attribute>\n        <name>{}</name>\n        <value>{}</value>\n      </attribute>",
+                    escape(name),
+                    escape(&value.display())
+                );
+            }
+            xml.push_str("    </attributes>\n");
+        }
+        xml.push_str("  </object>\n");
+    }
+    xml.push_str("</annotation>\n");
+    xml
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
@@ -0,0 +1,406 @@
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::models::ImageStatus;
+
+/// 可绑定快捷键的操作
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Action {
+    PrevImage,
+    NextImage,
+    RandomUnmodified,
+    GoBack,
+    NextUnmodified,
+    ToggleDrawing,
+    Save,
+    DeleteBox,
+    Cancel,
+    ToggleReviewMode,
+    Approve,
+    PromoteBox,
+    NextLowConfidence,
+    ClosePolygon,
+    SelectClass(i32),
+    SetStatus(ImageStatus),
+    ToggleTag(usize), // 切换项目中第 N 个图片标签
+}
+
+/// 数字键可直接切换的类别数量（0-9，Shift+0-9 对应 10-19）
+pub const CLASS_HOTKEY_COUNT: i32 = 20;
+/// Ctrl+1-9 可直接切换的图片标签数量
+pub const TAG_HOTKEY_COUNT: usize = 9;
+
+impl Action {
+    /// 所有操作，按快捷键设置窗口中的显示顺序排列
+    pub fn all() -> Vec<Action> {
+        let mut actions = vec![
+            Action::PrevImage,
+            Action::NextImage,
+            Action::RandomUnmodified,
+            Action::GoBack,
+            Action::NextUnmodified,
+            Action::ToggleDrawing,
+            Action::Save,
+            Action::DeleteBox,
+            Action::Cancel,
+            Action::ToggleReviewMode,
+            Action::Approve,
+            Action::PromoteBox,
+            Action::NextLowConfidence,
+            Action::ClosePolygon,
+        ];
+        actions.extend((0..CLASS_HOTKEY_COUNT).map(Action::SelectClass));
+        actions.extend(ImageStatus::ALL.into_iter().map(Action::SetStatus));
+        actions.extend((0..TAG_HOTKEY_COUNT).map(Action::ToggleTag));
+        actions
+    }
+
+    /// 配置文件中使用的标识
+    pub fn id(&self) -> String {
+        match self {
+            Action::PrevImage => "prev_image".to_string(),
+            Action::NextImage => "next_image".to_string(),
+            Action::RandomUnmodified => "random_unmodified".to_string(),
+            Action::GoBack => "go_back".to_string(),
+            Action::NextUnmodified => "next_unmodified".to_string(),
+            Action::ToggleDrawing => "toggle_drawing".to_string(),
+            Action::Save => "save".to_string(),
+            Action::DeleteBox => "delete_box".to_string(),
+            Action::Cancel => "cancel".to_string(),
+            Action::ToggleReviewMode => "toggle_review_mode".to_string(),
+            Action::Approve => "approve".to_string(),
+            Action::PromoteBox => "promote_box".to_string(),
+            Action::NextLowConfidence => "next_low_confidence".to_string(),
+            Action::ClosePolygon => "close_polygon".to_string(),
+            Action::SelectClass(class) => format!("select_class_{}", class),
+            Action::SetStatus(status) => format!("set_status_{}", status.id()),
+            Action::ToggleTag(index) => format!("toggle_tag_{}", index + 1),
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Action> {
+        if let Some(class) = id.strip_prefix("select_class_") {
+            return class.parse().ok().map(Action::SelectClass);
+        }
+        Action::all().into_iter().find(|action| action.id() == id)
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            Action::PrevImage => "上一张图片".to_string(),
+            Action::NextImage => "下一张图片".to_string(),
+            Action::RandomUnmodified => "随机跳转到未修改的图片".to_string(),
+            Action::GoBack => "返回上一张查看的图片".to_string(),
+            Action::NextUnmodified => "跳转到下一张未修改的图片".to_string(),
+            Action::ToggleDrawing => "切换绘制模式".to_string(),
+            Action::Save => "手动保存当前标注".to_string(),
+            Action::DeleteBox => "删除选中的边界框".to_string(),
+            Action::Cancel => "退出当前操作模式".to_string(),
+            Action::ToggleReviewMode => "切换审核模式".to_string(),
+            Action::Approve => "审核通过并跳到下一张".to_string(),
+            Action::PromoteBox => "将选中的预标注框确认为真值".to_string(),
+            Action::NextLowConfidence => "跳转到低置信度框最多的图片".to_string(),
+            Action::ClosePolygon => "闭合正在绘制的多边形".to_string(),
+            Action::SelectClass(class) => format!("切换到类别 {}", class),
+            Action::SetStatus(status) => format!("标记为「{}」", status.label()),
+            Action::ToggleTag(index) => format!("切换第 {} 个图片标签", index + 1),
+        }
+    }
+}
+
+/// 单个按键组合
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyBinding {
+    pub key: String, // egui::Key 的名称，如 "A"、"1"、"Delete"
+    #[serde(default)]
+    pub ctrl: bool,
+    #[serde(default)]
+    pub shift: bool,
+    #[serde(default)]
+    pub alt: bool,
+}
+
+impl KeyBinding {
+    pub fn new(key: egui::Key) -> Self {
+        Self {
+            key: key.name().to_string(),
+            ctrl: false,
+            shift: false,
+            alt: false,
+        }
+    }
+
+    pub fn shift(key: egui::Key) -> Self {
+        Self {
+            shift: true,
+            ..Self::new(key)
+        }
+    }
+
+    pub fn from_event(key: egui::Key, modifiers: egui::Modifiers) -> Self {
+        Self {
+            key: key.name().to_string(),
+            ctrl: modifiers.command,
+            shift: modifiers.shift,
+            alt: modifiers.alt,
+        }
+    }
+
+    fn modifiers(&self) -> egui::Modifiers {
+        let mut modifiers = egui::Modifiers::NONE;
+        if self.ctrl {
+            modifiers |= egui::Modifiers::COMMAND;
+        }
+        if self.shift {
+            modifiers |= egui::Modifiers::SHIFT;
+        }
+        if self.alt {
+            modifiers |= egui::Modifiers::ALT;
+        }
+        modifiers
+    }
+
+    fn matches(&self, key: egui::Key, modifiers: egui::Modifiers) -> bool {
+        key.name() == self.key && modifiers.matches_exact(self.modifiers())
+    }
+
+    pub fn display(&self) -> String {
+        let mut text = String::new();
+        if self.ctrl {
+            text.push_str("Ctrl+");
+        }
+        if self.shift {
+            text.push_str("Shift+");
+        }
+        if self.alt {
+            text.push_str("Alt+");
+        }
+        text.push_str(&self.key);
+        text
+    }
+}
+
+/// 快捷键覆盖配置：操作标识 -> 按键组合，只记录与默认值不同的操作
+pub type KeymapOverrides = BTreeMap<String, Vec<KeyBinding>>;
+
+/// 生效的快捷键表
+#[derive(Clone)]
+pub struct Keymap {
+    pub bindings: BTreeMap<Action, Vec<KeyBinding>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        use egui::Key;
+
+        let mut bindings = BTreeMap::new();
+        bindings.insert(
+            Action::PrevImage,
+            vec![KeyBinding::new(Key::W), KeyBinding::new(Key::A)],
+        );
+        bindings.insert(
+            Action::NextImage,
+            vec![KeyBinding::new(Key::S), KeyBinding::new(Key::D)],
+        );
+        bindings.insert(Action::RandomUnmodified, vec![KeyBinding::new(Key::Space)]);
+        bindings.insert(Action::GoBack, vec![KeyBinding::new(Key::B)]);
+        bindings.insert(Action::NextUnmodified, vec![KeyBinding::new(Key::N)]);
+        bindings.insert(Action::ToggleDrawing, vec![KeyBinding::new(Key::E)]);
+        bindings.insert(Action::Save, vec![KeyBinding::new(Key::Q)]);
+        bindings.insert(Action::DeleteBox, vec![KeyBinding::new(Key::Delete)]);
+        bindings.insert(Action::Cancel, vec![KeyBinding::new(Key::Escape)]);
+        bindings.insert(Action::ToggleReviewMode, vec![KeyBinding::new(Key::R)]);
+        bindings.insert(
+            Action::Approve,
+            vec![KeyBinding {
+                ctrl: true,
+                ..KeyBinding::new(Key::Enter)
+            }],
+        );
+        bindings.insert(Action::PromoteBox, vec![KeyBinding::new(Key::P)]);
+        bindings.insert(Action::NextLowConfidence, vec![KeyBinding::new(Key::L)]);
+        bindings.insert(Action::ClosePolygon, vec![KeyBinding::new(Key::Enter)]);
+
+        // 数字键 N 切换到类别 N，Shift+N 切换到类别 10+N
+        let digits = [
+            Key::Num0,
+            Key::Num1,
+            Key::Num2,
+            Key::Num3,
+            Key::Num4,
+            Key::Num5,
+            Key::Num6,
+            Key::Num7,
+            Key::Num8,
+            Key::Num9,
+        ];
+        for (class, key) in digits.into_iter().enumerate() {
+            let class = class as i32;
+            bindings.insert(Action::SelectClass(class), vec![KeyBinding::new(key)]);
+            bindings.insert(Action::SelectClass(class + 10), vec![KeyBinding::shift(key)]);
+        }
+
+        // F1-F8 依次设置图片状态
+        let function_keys = [
+            Key::F1,
+            Key::F2,
+            Key::F3,
+            Key::F4,
+            Key::F5,
+            Key::F6,
+            Key::F7,
+            Key::F8,
+        ];
+        for (status, key) in ImageStatus::ALL.into_iter().zip(function_keys) {
+            bindings.insert(Action::SetStatus(status), vec![KeyBinding::new(key)]);
+        }
+
+        // Ctrl+1-9 依次切换图片标签
+        for (index, key) in digits[1..].iter().enumerate() {
+            bindings.insert(
+                Action::ToggleTag(index),
+                vec![KeyBinding {
+                    ctrl: true,
+                    ..KeyBinding::new(*key)
+                }],
+            );
+        }
+
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    /// 按顺序叠加覆盖配置（后面的优先），得到生效的快捷键表
+    pub fn with_overrides(layers: &[&KeymapOverrides]) -> Self {
+        let mut keymap = Self::default();
+        for layer in layers {
+            for (id, bindings) in layer.iter() {
+                if let Some(action) = Action::from_id(id) {
+                    keymap.bindings.insert(action, bindings.clone());
+                }
+            }
+        }
+        keymap
+    }
+
+    /// 与默认值不同的部分，用于保存
+    pub fn overrides(&self) -> KeymapOverrides {
+        let defaults = Self::default();
+        self.bindings
+            .iter()
+            .filter(|(action, bindings)| defaults.bindings.get(action) != Some(bindings))
+            .map(|(action, bindings)| (action.id(), bindings.clone()))
+            .collect()
+    }
+
+    pub fn bindings_of(&self, action: Action) -> &[KeyBinding] {
+        self.bindings.get(&action).map_or(&[], |b| b.as_slice())
+    }
+
+    pub fn display(&self, action: Action) -> String {
+        let bindings = self.bindings_of(action);
+        if bindings.is_empty() {
+            return "未绑定".to_string();
+        }
+        bindings
+            .iter()
+            .map(|b| b.display())
+            .collect::<Vec<_>>()
+            .join(" / ")
+    }
+
+    /// 使用相同按键组合的其他操作
+    pub fn conflicts(&self, action: Action) -> Vec<Action> {
+        let own = self.bindings_of(action);
+        self.bindings
+            .iter()
+            .filter(|(other, bindings)| {
+                **other != action && bindings.iter().any(|b| own.contains(b))
+            })
+            .map(|(other, _)| *other)
+            .collect()
+    }
+
+    /// 本帧触发的操作。文本输入框获得焦点时不响应任何快捷键
+    pub fn triggered(&self, ctx: &egui::Context) -> Vec<Action> {
+        if ctx.wants_keyboard_input() {
+            return Vec::new();
+        }
+
+        ctx.input(|i| {
+            let mut actions = Vec::new();
+            for event in &i.events {
+                if let egui::Event::Key {
+                    key,
+                    physical_key,
+                    pressed: true,
+                    modifiers,
+                    ..
+                } = event
+                {
+                    let key = event_key(*key, *physical_key);
+                    for (action, bindings) in &self.bindings {
+                        if bindings.iter().any(|b| b.matches(key, *modifiers)) {
+                            actions.push(*action);
+                        }
+                    }
+                }
+            }
+            actions
+        })
+    }
+}
+
+/// 按键事件对应的绑定按键。数字键按物理键位匹配，
+/// 因为 Shift+数字在多数键盘布局下产生的是符号键（如 Shift+1 为 !）
+pub fn event_key(key: egui::Key, physical_key: Option<egui::Key>) -> egui::Key {
+    match physical_key {
+        Some(physical) if is_digit(physical) => physical,
+        _ => key,
+    }
+}
+
+fn is_digit(key: egui::Key) -> bool {
+    use egui::Key;
+    matches!(
+        key,
+        Key::Num0
+            | Key::Num1
+            | Key::Num2
+            | Key::Num3
+            | Key::Num4
+            | Key::Num5
+            | Key::Num6
+            | Key::Num7
+            | Key::Num8
+            | Key::Num9
+    )
+}
+
+/// 用户级快捷键配置文件（~/.label_space/keymap.json）
+pub fn user_keymap_path() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(|home| PathBuf::from(home).join(".label_space").join("keymap.json"))
+}
+
+pub fn load_user_overrides() -> KeymapOverrides {
+    user_keymap_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_user_overrides(overrides: &KeymapOverrides) -> Result<(), String> {
+    let path = user_keymap_path().ok_or("无法确定用户配置目录")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建用户配置目录失败: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(overrides)
+        .map_err(|e| format!("序列化快捷键配置失败: {}", e))?;
+    fs::write(path, content).map_err(|e| format!("保存快捷键配置失败: {}", e))
+}
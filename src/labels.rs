@@ -0,0 +1,402 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use crate::models::{AttributeValue, BoundingBox, Keypoint, Shape, is_parallelogram};
+
+/// 解析标签行所需的项目信息。关键点行与多边形行的字段数可能相同，只能按类别的关键点模板区分
+#[derive(Clone, Default)]
+pub struct LabelLayout {
+    pub keypoints: HashMap<i32, KeypointLayout>,
+}
+
+#[derive(Clone, Copy)]
+pub struct KeypointLayout {
+    pub count: usize,
+    pub with_visibility: bool, // 每个关键点是否带可见性（3 列或 2 列）
+}
+
+impl KeypointLayout {
+    fn dims(&self) -> usize {
+        if self.with_visibility { 3 } else { 2 }
+    }
+}
+
+/// 图片对应的标签文件路径（同名 .txt）
+pub fn label_path(label_dir: &Path, image_path: &Path) -> PathBuf {
+    label_dir
+        .join(image_path.file_stem().unwrap_or_default())
+        .with_extension("txt")
+}
+
+/// 解析一行 YOLO 标注，预标注可在末尾带一列置信度：
+/// - 水平框：`类别 中心点X 中心点Y 宽度 高度`
+/// - 旋转框（YOLO-OBB）：`类别 x1 y1 x2 y2 x3 y3 x4 y4`
+/// - 分割多边形（YOLO-seg）：`类别 x1 y1 x2 y2 ... xn yn`（至少三个顶点）
+/// - 关键点（YOLO-pose）：`类别 中心点X 中心点Y 宽度 高度 kx1 ky1 [v1] ...`，仅对定义了关键点模板的类别
+///
+/// 四个顶点构成平行四边形时按旋转框处理，否则按多边形处理，两者写回的内容相同
+pub fn parse_line(line: &str, layout: &LabelLayout) -> Option<BoundingBox> {
+    let parts: Vec<f64> = line
+        .split_whitespace()
+        .map(|s| s.parse().unwrap_or(0.0))
+        .collect();
+
+    let class = *parts.first()? as i32;
+    let rect = |confidence: Option<f64>| BoundingBox {
+        class,
+        x: parts[1],
+        y: parts[2],
+        width: parts[3],
+        height: parts[4],
+        confidence,
+        shape: Shape::Rect,
+        keypoints: Vec::new(),
+        attributes: BTreeMap::new(),
+    };
+    if parts.len() == 5 || parts.len() == 6 {
+        return Some(rect(parts.get(5).copied()));
+    }
+
+    if let Some(kpt) = layout.keypoints.get(&class) {
+        let expected = 5 + kpt.count * kpt.dims();
+        if parts.len() == expected || parts.len() == expected + 1 {
+            let mut bbox = rect(parts.get(expected).copied());
+            bbox.keypoints = parts[5..expected]
+                .chunks(kpt.dims())
+                .map(|c| Keypoint {
+                    x: c[0],
+                    y: c[1],
+                    // 不带可见性时，坐标为 0 视为未标注
+                    visibility: match c.get(2) {
+                        Some(v) => *v as u8,
+                        None if c[0] == 0.0 && c[1] == 0.0 => 0,
+                        None => 2,
+                    },
+                })
+                .collect();
+            return Some(bbox);
+        }
+    }
+
+    // 坐标数为奇数时最后一列是置信度
+    let mut coords = &parts[1..];
+    let confidence = if coords.len() % 2 == 1 {
+        let (last, rest) = coords.split_last()?;
+        coords = rest;
+        Some(*last)
+    } else {
+        None
+    };
+    if coords.len() < 6 {
+        return None;
+    }
+    let points: Vec<[f64; 2]> = coords.chunks(2).map(|c| [c[0], c[1]]).collect();
+
+    let shape = match <[[f64; 2]; 4]>::try_from(points.as_slice()) {
+        Ok(corners) if is_parallelogram(&corners) => Shape::Oriented(corners),
+        _ => Shape::Polygon(points),
+    };
+    let mut bbox = BoundingBox::from_shape(class, shape);
+    bbox.confidence = confidence;
+    Some(bbox)
+}
+
+/// 按多边形或旋转框解析、但坐标或置信度超出 [0, 1] 的框，多半是未定义关键点模板的类别的
+/// 关键点行（可见性列为 2），编辑后会按多边形改写，应先为该类别添加关键点模板
+pub fn looks_like_keypoints(bbox: &BoundingBox) -> bool {
+    !matches!(bbox.shape, Shape::Rect)
+        && (bbox
+            .outline()
+            .iter()
+            .flatten()
+            .any(|v| !(0.0..=1.0).contains(v))
+            || bbox.confidence.is_some_and(|c| c > 1.0))
+}
+
+/// 框属性旁路文件：与标签文件同名，扩展名为 `.attrs.json`，标签文件本身保持 YOLO 格式
+pub fn attributes_path(label_path: &Path) -> PathBuf {
+    label_path.with_extension("attrs.json")
+}
+
+/// 属性文件中的一项，按框在标签文件中的序号对应，类别不一致时忽略
+#[derive(Serialize, Deserialize)]
+struct BoxAttributes {
+    index: usize,
+    class: i32,
+    attributes: BTreeMap<String, AttributeValue>,
+}
+
+fn read_attributes(label_path: &Path, boxes: &mut [BoundingBox]) {
+    let Some(entries) = fs::read_to_string(attributes_path(label_path))
+        .ok()
+        .and_then(|content| serde_json::from_str::<Vec<BoxAttributes>>(&content).ok())
+    else {
+        return;
+    };
+    for entry in entries {
+        if let Some(bbox) = boxes.get_mut(entry.index).filter(|b| b.class == entry.class) {
+            bbox.attributes = entry.attributes;
+        }
+    }
+}
+
+/// 属性文件内容，没有任何属性时返回 None
+pub fn attributes_content(boxes: &[BoundingBox]) -> std::io::Result<Option<String>> {
+    let entries: Vec<BoxAttributes> = boxes
+        .iter()
+        .enumerate()
+        .filter(|(_, bbox)| !bbox.attributes.is_empty())
+        .map(|(index, bbox)| BoxAttributes {
+            index,
+            class: bbox.class,
+            attributes: bbox.attributes.clone(),
+        })
+        .collect();
+    if entries.is_empty() {
+        return Ok(None);
+    }
+    serde_json::to_string_pretty(&entries)
+        .map(Some)
+        .map_err(std::io::Error::other)
+}
+
+/// 写出属性文件，没有任何属性时删除旧文件
+fn write_attributes(label_path: &Path, boxes: &[BoundingBox]) -> std::io::Result<()> {
+    let path = attributes_path(label_path);
+    match attributes_content(boxes)? {
+        Some(content) => fs::write(path, content),
+        None => match fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        },
+    }
+}
+
+/// 读取标签文件及其属性文件，文件不存在时返回空列表
+pub fn read_label_file(path: &Path, layout: &LabelLayout) -> Vec<BoundingBox> {
+    let mut boxes = Vec::new();
+    if let Ok(file) = File::open(path) {
+        let reader = BufReader::new(file);
+        for line in reader.lines().map_while(Result::ok) {
+            if let Some(bbox) = parse_line(&line, layout) {
+                boxes.push(bbox);
+            }
+        }
+        read_attributes(path, &mut boxes);
+    }
+    boxes
+}
+
+/// 读取预测结果文件，缺少置信度列的框视为置信度 1.0
+pub fn read_prediction_file(path: &Path, layout: &LabelLayout) -> Vec<(BoundingBox, f64)> {
+    let mut predictions = Vec::new();
+    if let Ok(file) = File::open(path) {
+        let reader = BufReader::new(file);
+        for line in reader.lines().map_while(Result::ok) {
+            if let Some(bbox) = parse_line(&line, layout) {
+                let confidence = bbox.confidence.unwrap_or(1.0);
+                predictions.push((bbox, confidence));
+            }
+        }
+    }
+    predictions
+}
+
+/// 写入标签文件及其属性文件，未确认的预标注框保留置信度列
+pub fn write_label_file(
+    path: &Path,
+    boxes: &[BoundingBox],
+    layout: &LabelLayout,
+) -> std::io::Result<()> {
+    let mut file = File::create(path)?;
+    write_labels(&mut file, boxes, layout)?;
+    write_attributes(path, boxes)
+}
+
+/// 批量转换框形状的结果
+#[derive(Default)]
+pub struct ShapeConversionReport {
+    pub files: usize,   // 修改的标签文件数量
+    pub boxes: usize,   // 转换的框数量
+    pub skipped: usize, // 不能转换而保持原样的框（多边形、带关键点的框）
+    pub failures: Vec<String>,
+}
+
+/// 将标签目录中的水平框和旋转框统一转换为目标形状，不能转换的框保持原样，
+/// 没有变化的文件不重写，写入失败的文件记录后继续
+pub fn convert_label_dir(
+    images: &[PathBuf],
+    label_dir: &Path,
+    layout: &LabelLayout,
+    oriented: bool,
+) -> ShapeConversionReport {
+    let mut report = ShapeConversionReport::default();
+    for image in images {
+        let path = label_path(label_dir, image);
+        if !path.exists() {
+            continue;
+        }
+        let mut boxes = read_label_file(&path, layout);
+        let mut converted = 0;
+        for bbox in &mut boxes {
+            if matches!(bbox.shape, Shape::Oriented(_)) == oriented {
+                continue;
+            }
+            if !bbox.can_convert_shape(oriented) {
+                report.skipped += 1;
+                continue;
+            }
+            if oriented {
+                bbox.make_oriented();
+            } else {
+                bbox.make_axis_aligned();
+            }
+            converted += 1;
+        }
+        if converted == 0 {
+            continue;
+        }
+        match write_label_file(&path, &boxes, layout) {
+            Ok(()) => {
+                report.files += 1;
+                report.boxes += converted;
+            }
+            Err(e) => report
+                .failures
+                .push(format!("写入 {} 失败: {}", path.display(), e)),
+        }
+    }
+    report
+}
+
+/// 按 YOLO 格式写出标签行（不包括属性）
+pub fn write_labels(
+    file: &mut impl Write,
+    boxes: &[BoundingBox],
+    layout: &LabelLayout,
+) -> std::io::Result<()> {
+    for bbox in boxes {
+        match &bbox.shape {
+            Shape::Rect => {
+                write!(
+                    file,
+                    "{} {} {} {} {}",
+                    bbox.class, bbox.x, bbox.y, bbox.width, bbox.height
+                )?;
+                let with_visibility = layout
+                    .keypoints
+                    .get(&bbox.class)
+                    .is_none_or(|k| k.with_visibility);
+                for kp in &bbox.keypoints {
+                    write!(file, " {} {}", kp.x, kp.y)?;
+                    if with_visibility {
+                        write!(file, " {}", kp.visibility)?;
+                    }
+                }
+            }
+            Shape::Oriented(_) | Shape::Polygon(_) => {
+                write!(file, "{}", bbox.class)?;
+                for [x, y] in bbox.outline() {
+                    write!(file, " {} {}", x, y)?;
+                }
+            }
+        }
+        match bbox.confidence {
+            Some(confidence) => writeln!(file, " {}", confidence)?,
+            None => writeln!(file)?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout() -> LabelLayout {
+        LabelLayout {
+            keypoints: HashMap::from([
+                (
+                    3,
+                    KeypointLayout {
+                        count: 2,
+                        with_visibility: true,
+                    },
+                ),
+                (
+                    4,
+                    KeypointLayout {
+                        count: 2,
+                        with_visibility: false,
+                    },
+                ),
+            ]),
+        }
+    }
+
+    /// 解析一行并写回，写回的内容应与原行相同
+    fn round_trip(line: &str) -> BoundingBox {
+        let layout = layout();
+        let bbox = parse_line(line, &layout).unwrap();
+        let mut written = Vec::new();
+        write_labels(&mut written, std::slice::from_ref(&bbox), &layout).unwrap();
+        assert_eq!(String::from_utf8(written).unwrap(), format!("{}\n", line));
+        bbox
+    }
+
+    #[test]
+    fn rect_with_and_without_confidence() {
+        let bbox = round_trip("0 0.5 0.5 0.2 0.3");
+        assert!(matches!(bbox.shape, Shape::Rect));
+        assert_eq!(bbox.confidence, None);
+
+        let bbox = round_trip("0 0.5 0.5 0.2 0.3 0.9");
+        assert!(matches!(bbox.shape, Shape::Rect));
+        assert_eq!(bbox.confidence, Some(0.9));
+    }
+
+    #[test]
+    fn odd_coordinate_count_has_trailing_confidence() {
+        let bbox = round_trip("1 0.1 0.1 0.5 0.1 0.3 0.4 0.8");
+        assert!(matches!(&bbox.shape, Shape::Polygon(points) if points.len() == 3));
+        assert_eq!(bbox.confidence, Some(0.8));
+    }
+
+    #[test]
+    fn parallelogram_is_oriented_otherwise_polygon() {
+        let bbox = round_trip("2 0.1 0.1 0.5 0.1 0.6 0.4 0.2 0.4");
+        assert!(matches!(bbox.shape, Shape::Oriented(_)));
+
+        let bbox = round_trip("2 0.1 0.1 0.5 0.1 0.6 0.4 0.1 0.4");
+        assert!(matches!(&bbox.shape, Shape::Polygon(points) if points.len() == 4));
+        assert_eq!(bbox.confidence, None);
+    }
+
+    #[test]
+    fn keypoints_follow_class_layout() {
+        let bbox = round_trip("3 0.5 0.5 0.2 0.2 0.4 0.4 2 0.6 0.6 1");
+        assert!(matches!(bbox.shape, Shape::Rect));
+        let visibility: Vec<u8> = bbox.keypoints.iter().map(|k| k.visibility).collect();
+        assert_eq!(visibility, vec![2, 1]);
+
+        // 不带可见性的模板：坐标为 0 的点视为未标注
+        let bbox = round_trip("4 0.5 0.5 0.2 0.2 0.4 0.4 0 0 0.7");
+        let visibility: Vec<u8> = bbox.keypoints.iter().map(|k| k.visibility).collect();
+        assert_eq!(visibility, vec![2, 0]);
+        assert_eq!(bbox.confidence, Some(0.7));
+        assert!(!looks_like_keypoints(&bbox));
+    }
+
+    #[test]
+    fn keypoint_line_without_schema_is_flagged() {
+        let bbox = round_trip("5 0.5 0.5 0.2 0.2 0.4 0.4 2 0.6 0.6 1");
+        assert!(!matches!(bbox.shape, Shape::Rect));
+        assert!(looks_like_keypoints(&bbox));
+
+        let bbox = round_trip("5 0.1 0.1 0.5 0.1 0.3 0.4");
+        assert!(!looks_like_keypoints(&bbox));
+    }
+}
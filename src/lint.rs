@@ -0,0 +1,99 @@
+use std::collections::BTreeMap;
+
+use crate::models::{BoundingBox, Shape};
+use crate::project::ProjectConfig;
+
+/// 宽或高小于该像素数的框视为过小
+pub const TINY_BOX_PIXELS: f64 = 4.0;
+/// 超出图像范围的容差（归一化坐标），避免浮点误差误报
+const BOUNDS_EPSILON: f64 = 1e-6;
+
+/// 标注问题类型
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LintKind {
+    Degenerate,  // 宽或高为零，或多边形顶点不足三个
+    OutOfBounds, // 框超出图像范围
+    Tiny,        // 像素宽高过小，需要图片尺寸
+    Duplicate,   // 与同一图片中的另一个框类别和位置完全相同
+}
+
+impl LintKind {
+    pub const ALL: [LintKind; 4] = [
+        LintKind::Degenerate,
+        LintKind::OutOfBounds,
+        LintKind::Tiny,
+        LintKind::Duplicate,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LintKind::Degenerate => "退化框",
+            LintKind::OutOfBounds => "超出图像",
+            LintKind::Tiny => "过小的框",
+            LintKind::Duplicate => "重复框",
+        }
+    }
+}
+
+/// 一个框的标注问题
+#[derive(Clone)]
+pub struct LintIssue {
+    pub image: String,
+    pub box_index: usize,
+    pub class: i32,
+    pub kind: LintKind,
+}
+
+/// 检查一张图片的框的几何问题，`size` 为图片像素尺寸，未知时不检查过小的框
+pub fn check_boxes(boxes: &[BoundingBox], size: Option<(u32, u32)>) -> Vec<(usize, LintKind)> {
+    let mut issues = Vec::new();
+    for (index, bbox) in boxes.iter().enumerate() {
+        let degenerate = bbox.width <= 0.0
+            || bbox.height <= 0.0
+            || matches!(&bbox.shape, Shape::Polygon(points) if points.len() < 3);
+        if degenerate {
+            issues.push((index, LintKind::Degenerate));
+            continue;
+        }
+        let out_of_bounds = bbox
+            .outline()
+            .iter()
+            .flatten()
+            .any(|v| !(-BOUNDS_EPSILON..=1.0 + BOUNDS_EPSILON).contains(v));
+        if out_of_bounds {
+            issues.push((index, LintKind::OutOfBounds));
+        }
+        if let Some((width, height)) = size
+            && (bbox.width * (width as f64) < TINY_BOX_PIXELS
+                || bbox.height * (height as f64) < TINY_BOX_PIXELS)
+        {
+            issues.push((index, LintKind::Tiny));
+        }
+        let duplicate = boxes[..index].iter().any(|other| {
+            other.class == bbox.class
+                && (other.x - bbox.x).abs() < BOUNDS_EPSILON
+                && (other.y - bbox.y).abs() < BOUNDS_EPSILON
+                && (other.width - bbox.width).abs() < BOUNDS_EPSILON
+                && (other.height - bbox.height).abs() < BOUNDS_EPSILON
+        });
+        if duplicate {
+            issues.push((index, LintKind::Duplicate));
+        }
+    }
+    issues
+}
+
+/// 项目配置了类别名称时，统计中出现但未命名的类别及其框数量
+pub fn unknown_classes(
+    class_boxes: &BTreeMap<i32, usize>,
+    project: &ProjectConfig,
+) -> BTreeMap<i32, usize> {
+    if project.class_names.is_empty() {
+        return BTreeMap::new();
+    }
+    class_boxes
+        .iter()
+        .filter(|(class, _)| !project.class_names.contains_key(class))
+        .map(|(class, count)| (*class, *count))
+        .collect()
+}
@@ -1,17 +1,57 @@
 #![windows_subsystem = "windows"]
 
 mod app;
+mod autolabel;
+mod cli;
+mod dedup;
+mod diff;
+mod export;
+mod keymap;
+mod labels;
+mod lint;
+mod merge;
+mod metrics;
 mod models;
+mod project;
+mod remap;
+mod report;
+mod session;
+mod split;
+mod stats;
+mod status;
+mod tags;
 mod ui;
 mod utils;
 
 use app::AnnotationApp;
-use ctrlc;
 use eframe::egui;
 use std::panic;
 use std::sync::{Arc, Mutex};
 
+/// 程序以 GUI 子系统编译，Windows 下没有控制台；命令行模式附加到启动它的终端，
+/// 否则输出会丢失
+#[cfg(windows)]
+fn attach_parent_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    #[link(name = "kernel32")]
+    unsafe extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    // 失败（如从资源管理器启动）时没有可输出的终端，忽略即可
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
 fn main() {
+    // 带参数启动时作为命令行工具运行
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        #[cfg(windows)]
+        attach_parent_console();
+        std::process::exit(cli::run(&args));
+    }
+
     let app = Arc::new(Mutex::new(None::<AnnotationApp>));
     let app_clone1 = app.clone();
     let app_clone2 = app.clone();
@@ -86,7 +126,21 @@ impl eframe::App for MyApp {
         ui::top::top_panel(&mut self.app, ctx);
         ui::side::side_panel(&mut self.app, ctx);
         ui::statistics::statistics_panel(&mut self.app, ctx);
+        ui::metrics::metrics_panel(&mut self.app, ctx);
         ui::central::central_panel(&mut self.app, ctx);
+        ui::keymap::keymap_window(&mut self.app, ctx);
+        ui::autolabel::auto_label_window(&mut self.app, ctx);
+        ui::review::review_window(&mut self.app, ctx);
+        ui::keypoints::keypoint_schema_window(&mut self.app, ctx);
+        ui::attributes::attribute_definitions_window(&mut self.app, ctx);
+        ui::split::split_window(&mut self.app, ctx);
+        ui::export::export_profile_window(&mut self.app, ctx);
+        ui::crops::crop_export_window(&mut self.app, ctx);
+        ui::remap::class_remap_window(&mut self.app, ctx);
+        ui::merge::merge_window(&mut self.app, ctx);
+        ui::dedup::duplicate_window(&mut self.app, ctx);
+        ui::dataset_stats::dataset_stats_window(&mut self.app, ctx);
+        ui::progress::progress_window(&mut self.app, ctx);
     }
     
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
@@ -0,0 +1,318 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::export::manifest::file_digest;
+use crate::labels;
+use crate::project::ProjectConfig;
+use crate::remap::{self, RemapTable, RemapTarget};
+use crate::status;
+use crate::tags::{self, ImageTags};
+use crate::utils::{list_files, now_secs};
+
+/// 合并报告文件，保存在合并后的标签目录下
+pub const MERGE_REPORT_FILE_NAME: &str = "merge_report.json";
+
+/// 一个待合并的项目：图片目录和标签目录
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct MergeSource {
+    pub image_dir: PathBuf,
+    pub label_dir: PathBuf,
+}
+
+/// 因重名而改名的图片
+#[derive(Serialize)]
+pub struct RenamedImage {
+    pub source: PathBuf,
+    pub name: String, // 合并后的文件名
+}
+
+/// 内容与已合并图片相同的图片
+#[derive(Serialize)]
+pub struct DuplicateImage {
+    pub source: PathBuf,
+    pub duplicate_of: String, // 合并后目录中内容相同的图片
+    pub skipped: bool,
+}
+
+/// 合并结果，同时写出到合并后标签目录下的 `merge_report.json`
+#[derive(Serialize, Default)]
+pub struct MergeReport {
+    pub merged_at: u64,
+    pub sources: Vec<MergeSource>,
+    pub images: usize,
+    pub labels: usize,
+    pub class_mappings: Vec<BTreeMap<i32, i32>>, // 每个项目：原类别 -> 合并后类别（编号不变的也列出）
+    pub renamed: Vec<RenamedImage>,
+    pub duplicates: Vec<DuplicateImage>,
+    pub failures: Vec<String>,
+}
+
+impl MergeReport {
+    pub fn summary(&self) -> String {
+        let mut info = format!(
+            "已合并 {} 个项目的 {} 张图片、{} 个标签文件，重名改名 {} 张，内容重复 {} 张",
+            self.sources.len(),
+            self.images,
+            self.labels,
+            self.renamed.len(),
+            self.duplicates.len()
+        );
+        let skipped = self.duplicates.iter().filter(|d| d.skipped).count();
+        if skipped > 0 {
+            info.push_str(&format!("（已跳过 {} 张）", skipped));
+        }
+        if !self.failures.is_empty() {
+            info.push_str(&format!("，{} 个文件失败", self.failures.len()));
+        }
+        info
+    }
+}
+
+/// 合并后的类别表：有名称的类别按名称对应，没有名称的类别按编号对应
+#[derive(Default)]
+struct ClassRegistry {
+    by_name: HashMap<String, i32>,
+    used: BTreeSet<i32>,
+}
+
+impl ClassRegistry {
+    /// 为一个项目中的类别分配合并后的编号；新名称优先沿用原编号，已被占用时使用新编号
+    fn map_source(
+        &mut self,
+        project: &ProjectConfig,
+        classes: &BTreeSet<i32>,
+        merged: &mut ProjectConfig,
+    ) -> BTreeMap<i32, i32> {
+        let mut mapping = BTreeMap::new();
+        for &class in classes {
+            let Some(name) = project
+                .class_names
+                .get(&class)
+                .map(|n| n.trim().to_string())
+            else {
+                self.used.insert(class);
+                mapping.insert(class, class);
+                continue;
+            };
+            let target = match self.by_name.get(&name) {
+                Some(&target) => target,
+                None => {
+                    let target = if self.used.contains(&class) {
+                        self.used.last().map_or(0, |max| max + 1)
+                    } else {
+                        class
+                    };
+                    self.by_name.insert(name.clone(), target);
+                    self.used.insert(target);
+                    merged.class_names.insert(target, name);
+                    // 类别颜色、属性和关键点模板随类别一起带过来
+                    if let Some(color) = project.class_colors.get(&class) {
+                        merged.class_colors.entry(target).or_insert(*color);
+                    }
+                    if let Some(attributes) = project.class_attributes.get(&class) {
+                        merged
+                            .class_attributes
+                            .entry(target)
+                            .or_insert_with(|| attributes.clone());
+                    }
+                    if let Some(schema) = project.keypoint_schemas.get(&class) {
+                        merged
+                            .keypoint_schemas
+                            .entry(target)
+                            .or_insert_with(|| schema.clone());
+                    }
+                    target
+                }
+            };
+            mapping.insert(class, target);
+        }
+        mapping
+    }
+}
+
+/// 合并多个项目到新的图片目录和标签目录。
+/// 类别按名称统一编号，重名图片按项目序号改名（`{文件名}_{序号}`），
+/// 同时合并图片状态、图片标签，并按内容（SHA-1）检测重复图片。
+/// 输出目录必须为空；单个文件失败时记录后继续
+pub fn merge(
+    sources: &[MergeSource],
+    image_out: &Path,
+    label_out: &Path,
+    skip_duplicates: bool,
+) -> Result<MergeReport, String> {
+    if sources.len() < 2 {
+        return Err("至少需要两个项目才能合并".to_string());
+    }
+    for dir in [image_out, label_out] {
+        if fs::read_dir(dir).is_ok_and(|mut entries| entries.next().is_some()) {
+            return Err(format!(
+                "目标目录不为空，请选择空目录或新目录: {}",
+                dir.display()
+            ));
+        }
+        fs::create_dir_all(dir).map_err(|e| format!("创建目录失败 {}: {}", dir.display(), e))?;
+    }
+
+    // 先读取所有项目，确定合并后的类别表
+    let mut projects = Vec::with_capacity(sources.len());
+    for source in sources {
+        let project = ProjectConfig::load(&source.label_dir)?;
+        let images = list_files(&source.image_dir, &["jpg", "png"])?;
+        let layout = project.label_layout();
+        let mut classes: BTreeSet<i32> = project.class_names.keys().copied().collect();
+        for image in &images {
+            let label_path = labels::label_path(&source.label_dir, image);
+            classes.extend(
+                labels::read_label_file(&label_path, &layout)
+                    .iter()
+                    .map(|b| b.class),
+            );
+        }
+        projects.push((project, images, classes));
+    }
+    // 快捷键、标注员等其他设置沿用第一个项目
+    let mut merged = ProjectConfig {
+        class_names: BTreeMap::new(),
+        class_colors: BTreeMap::new(),
+        ..projects[0].0.clone()
+    };
+    let mut registry = ClassRegistry::default();
+    let mut report = MergeReport {
+        merged_at: now_secs(),
+        sources: sources.to_vec(),
+        ..Default::default()
+    };
+    for (project, _, classes) in &projects {
+        let mapping = registry.map_source(project, classes, &mut merged);
+        report.class_mappings.push(mapping);
+    }
+    let out_layout = merged.label_layout();
+
+    let mut records = HashMap::new();
+    let mut merged_tags = ImageTags::new();
+    let mut taken: BTreeSet<String> = BTreeSet::new();
+    let mut digests: HashMap<String, String> = HashMap::new();
+
+    for (index, (source, (project, images, _))) in sources.iter().zip(&projects).enumerate() {
+        let layout = project.label_layout();
+        let table: RemapTable = report.class_mappings[index]
+            .iter()
+            .filter(|(from, to)| from != to)
+            .map(|(from, to)| (*from, RemapTarget::Class(*to)))
+            .collect();
+        let source_records = status::load_records(&source.label_dir)?;
+        let source_tags = tags::load_tags(&source.label_dir);
+
+        for image in images {
+            let file_name = image
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let digest = match file_digest(image) {
+                Ok((_, digest)) => digest,
+                Err(e) => {
+                    report
+                        .failures
+                        .push(format!("读取 {} 失败: {}", image.display(), e));
+                    continue;
+                }
+            };
+            if let Some(existing) = digests.get(&digest) {
+                report.duplicates.push(DuplicateImage {
+                    source: image.clone(),
+                    duplicate_of: existing.clone(),
+                    skipped: skip_duplicates,
+                });
+                if skip_duplicates {
+                    continue;
+                }
+            }
+
+            let name = unique_name(&file_name, index + 1, &taken);
+            if name != file_name {
+                report.renamed.push(RenamedImage {
+                    source: image.clone(),
+                    name: name.clone(),
+                });
+            }
+            let target = image_out.join(&name);
+            if let Err(e) = fs::copy(image, &target) {
+                report
+                    .failures
+                    .push(format!("复制 {} 失败: {}", image.display(), e));
+                continue;
+            }
+            taken.insert(name.clone());
+            digests.entry(digest).or_insert_with(|| name.clone());
+            report.images += 1;
+
+            let label_path = labels::label_path(&source.label_dir, image);
+            let mut removed = Vec::new();
+            if label_path.exists() {
+                let mut boxes = labels::read_label_file(&label_path, &layout);
+                removed = remap::dropped_indices(&table, &boxes);
+                remap::apply(&table, &mut boxes);
+                match labels::write_label_file(
+                    &labels::label_path(label_out, &target),
+                    &boxes,
+                    &out_layout,
+                ) {
+                    Ok(()) => report.labels += 1,
+                    Err(e) => report
+                        .failures
+                        .push(format!("写入 {} 的标签失败: {}", name, e)),
+                }
+            }
+            if let Some(record) = source_records.get(&file_name) {
+                let mut record = record.clone();
+                record.remove_comment_boxes(&removed);
+                records.insert(name.clone(), record);
+            }
+            if let Some(image_tags) = source_tags.get(&file_name) {
+                merged_tags.insert(name, image_tags.clone());
+            }
+        }
+    }
+
+    // 图片标签定义按名称去重合并
+    for (project, _, _) in projects.iter().skip(1) {
+        for tag in &project.image_tags {
+            if !merged.image_tags.iter().any(|t| t.name == tag.name) {
+                merged.image_tags.push(tag.clone());
+            }
+        }
+    }
+    merged.save(label_out)?;
+    status::save_records(label_out, &records)?;
+    tags::save_tags(label_out, &merged_tags)?;
+    let content =
+        serde_json::to_string_pretty(&report).map_err(|e| format!("序列化合并报告失败: {}", e))?;
+    fs::write(label_out.join(MERGE_REPORT_FILE_NAME), content)
+        .map_err(|e| format!("写入 {} 失败: {}", MERGE_REPORT_FILE_NAME, e))?;
+    Ok(report)
+}
+
+/// 重名时在文件名后加项目序号（仍重名时再加计数），结果只取决于合并顺序
+fn unique_name(file_name: &str, source: usize, taken: &BTreeSet<String>) -> String {
+    if !taken.contains(file_name) {
+        return file_name.to_string();
+    }
+    let path = Path::new(file_name);
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    let mut name = format!("{}_{}{}", stem, source, extension);
+    let mut counter = 2;
+    while taken.contains(&name) {
+        name = format!("{}_{}_{}{}", stem, source, counter, extension);
+        counter += 1;
+    }
+    name
+}
@@ -0,0 +1,381 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use crate::diff;
+use crate::labels::{self, LabelLayout};
+use crate::models::BoundingBox;
+
+/// COCO 风格 mAP@0.5:0.95 使用的 IoU 阈值
+const COCO_IOU_THRESHOLDS: [f64; 10] = [0.5, 0.55, 0.6, 0.65, 0.7, 0.75, 0.8, 0.85, 0.9, 0.95];
+
+/// 单个类别的评估结果
+#[derive(Clone, Serialize)]
+pub struct ClassMetrics {
+    pub class: i32,
+    pub gt_count: usize,
+    pub pred_count: usize,
+    pub ap50: f64,
+    pub ap50_95: f64,
+    pub precision: f64, // 以下指标在 IoU 0.5 和置信度阈值下计算
+    pub recall: f64,
+    pub f1: f64,
+    pub tp: usize,
+    pub fp: usize,
+    pub fn_: usize,
+}
+
+/// 混淆矩阵：行为真值类别，列为预测类别，最后一行/列为背景（漏检/误检）
+#[derive(Clone, Serialize)]
+pub struct ConfusionMatrix {
+    pub classes: Vec<i32>,
+    pub matrix: Vec<Vec<usize>>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct EvalReport {
+    pub images: usize,
+    pub conf_threshold: f64,
+    pub map50: f64,
+    pub map50_95: f64,
+    pub classes: Vec<ClassMetrics>,
+    pub confusion: ConfusionMatrix,
+}
+
+/// 单张图片的真值与预测
+struct ImageSample {
+    gt: Vec<BoundingBox>,
+    preds: Vec<(BoundingBox, f64)>,
+}
+
+/// 评估预测目录相对于真值目录的检测指标。
+/// images 可以是图片路径或标签路径，只使用其文件名（不含扩展名）匹配标签文件
+pub fn evaluate(
+    images: &[PathBuf],
+    gt_dir: &Path,
+    pred_dir: &Path,
+    conf_threshold: f64,
+    layout: &LabelLayout,
+) -> EvalReport {
+    let samples: Vec<ImageSample> = images
+        .iter()
+        .map(|image| ImageSample {
+            gt: labels::read_label_file(&labels::label_path(gt_dir, image), layout),
+            preds: labels::read_prediction_file(&labels::label_path(pred_dir, image), layout),
+        })
+        .collect();
+    evaluate_samples(&samples, conf_threshold)
+}
+
+fn evaluate_samples(samples: &[ImageSample], conf_threshold: f64) -> EvalReport {
+    let classes: BTreeSet<i32> = samples
+        .iter()
+        .flat_map(|s| {
+            s.gt.iter()
+                .map(|b| b.class)
+                .chain(s.preds.iter().map(|(b, _)| b.class))
+        })
+        .collect();
+
+    let mut class_metrics = Vec::new();
+    for &class in &classes {
+        let aps: Vec<f64> = COCO_IOU_THRESHOLDS
+            .iter()
+            .map(|&t| average_precision(samples, class, t))
+            .collect();
+
+        let (tp, fp, gt_count) = count_matches(samples, class, 0.5, conf_threshold);
+        let fn_ = gt_count - tp;
+        let precision = ratio(tp, tp + fp);
+        let recall = ratio(tp, gt_count);
+        let f1 = if precision + recall > 0.0 {
+            2.0 * precision * recall / (precision + recall)
+        } else {
+            0.0
+        };
+
+        class_metrics.push(ClassMetrics {
+            class,
+            gt_count,
+            pred_count: samples
+                .iter()
+                .flat_map(|s| &s.preds)
+                .filter(|(b, _)| b.class == class)
+                .count(),
+            ap50: aps[0],
+            ap50_95: aps.iter().sum::<f64>() / aps.len() as f64,
+            precision,
+            recall,
+            f1,
+            tp,
+            fp,
+            fn_,
+        });
+    }
+
+    // 与 COCO 一致，只对存在真值的类别求平均
+    let with_gt: Vec<&ClassMetrics> = class_metrics.iter().filter(|m| m.gt_count > 0).collect();
+    let mean = |f: fn(&ClassMetrics) -> f64| {
+        if with_gt.is_empty() {
+            0.0
+        } else {
+            with_gt.iter().map(|m| f(m)).sum::<f64>() / with_gt.len() as f64
+        }
+    };
+
+    EvalReport {
+        images: samples.len(),
+        conf_threshold,
+        map50: mean(|m| m.ap50),
+        map50_95: mean(|m| m.ap50_95),
+        confusion: confusion_matrix(samples, &classes, conf_threshold),
+        classes: class_metrics,
+    }
+}
+
+fn ratio(numerator: usize, denominator: usize) -> f64 {
+    if denominator == 0 {
+        0.0
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
+
+/// 按置信度从高到低将预测与同类别真值匹配，返回每个预测是否为 TP 及其置信度
+fn match_predictions(samples: &[ImageSample], class: i32, iou_threshold: f64) -> Vec<(f64, bool)> {
+    let mut results = Vec::new();
+    for sample in samples {
+        let gt: Vec<&BoundingBox> = sample.gt.iter().filter(|b| b.class == class).collect();
+        let mut preds: Vec<&(BoundingBox, f64)> = sample
+            .preds
+            .iter()
+            .filter(|(b, _)| b.class == class)
+            .collect();
+        preds.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let mut matched = vec![false; gt.len()];
+        for (pred, confidence) in preds {
+            let best = gt
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| !matched[*i])
+                .map(|(i, g)| (i, diff::iou(pred, g)))
+                .filter(|(_, iou)| *iou >= iou_threshold)
+                .max_by(|a, b| a.1.total_cmp(&b.1));
+            match best {
+                Some((i, _)) => {
+                    matched[i] = true;
+                    results.push((*confidence, true));
+                }
+                None => results.push((*confidence, false)),
+            }
+        }
+    }
+    results.sort_by(|a, b| b.0.total_cmp(&a.0));
+    results
+}
+
+/// COCO 101 点插值 AP
+fn average_precision(samples: &[ImageSample], class: i32, iou_threshold: f64) -> f64 {
+    let gt_count: usize = samples
+        .iter()
+        .map(|s| s.gt.iter().filter(|b| b.class == class).count())
+        .sum();
+    if gt_count == 0 {
+        return 0.0;
+    }
+
+    let matches = match_predictions(samples, class, iou_threshold);
+    let mut precisions = Vec::with_capacity(matches.len());
+    let mut recalls = Vec::with_capacity(matches.len());
+    let (mut tp, mut fp) = (0usize, 0usize);
+    for (_, is_tp) in &matches {
+        if *is_tp {
+            tp += 1;
+        } else {
+            fp += 1;
+        }
+        precisions.push(tp as f64 / (tp + fp) as f64);
+        recalls.push(tp as f64 / gt_count as f64);
+    }
+
+    // 精度包络：每个召回率位置取其右侧的最大精度
+    for i in (0..precisions.len().saturating_sub(1)).rev() {
+        precisions[i] = precisions[i].max(precisions[i + 1]);
+    }
+
+    let mut sum = 0.0;
+    for step in 0..=100 {
+        let r = step as f64 / 100.0;
+        let idx = recalls.partition_point(|&recall| recall < r);
+        if idx < precisions.len() {
+            sum += precisions[idx];
+        }
+    }
+    sum / 101.0
+}
+
+/// 统计置信度阈值以上预测的 TP/FP 以及真值数量
+fn count_matches(
+    samples: &[ImageSample],
+    class: i32,
+    iou_threshold: f64,
+    conf_threshold: f64,
+) -> (usize, usize, usize) {
+    let gt_count: usize = samples
+        .iter()
+        .map(|s| s.gt.iter().filter(|b| b.class == class).count())
+        .sum();
+    let matches = match_predictions(samples, class, iou_threshold);
+    let kept = matches.iter().filter(|(c, _)| *c >= conf_threshold);
+    let tp = kept.clone().filter(|(_, is_tp)| *is_tp).count();
+    let fp = kept.count() - tp;
+    (tp, fp, gt_count)
+}
+
+fn confusion_matrix(
+    samples: &[ImageSample],
+    classes: &BTreeSet<i32>,
+    conf_threshold: f64,
+) -> ConfusionMatrix {
+    let classes: Vec<i32> = classes.iter().copied().collect();
+    let index: BTreeMap<i32, usize> = classes.iter().enumerate().map(|(i, c)| (*c, i)).collect();
+    let background = classes.len();
+    let mut matrix = vec![vec![0usize; classes.len() + 1]; classes.len() + 1];
+
+    for sample in samples {
+        let preds: Vec<BoundingBox> = sample
+            .preds
+            .iter()
+            .filter(|(_, c)| *c >= conf_threshold)
+            .map(|(b, _)| b.clone())
+            .collect();
+        // 与标签对比使用相同的匹配方式（不区分类别），匹配上但类别不同即为混淆
+        for d in diff::diff_boxes(&sample.gt, &preds, 0.5) {
+            let row = d.ours.map_or(background, |i| index[&sample.gt[i].class]);
+            let col = d.theirs.map_or(background, |j| index[&preds[j].class]);
+            matrix[row][col] += 1;
+        }
+    }
+
+    ConfusionMatrix { classes, matrix }
+}
+
+impl EvalReport {
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("序列化评估结果失败: {}", e))
+    }
+
+    /// 每个类别一行的 CSV，末尾附加 all 汇总行
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("class,gt,pred,ap50,ap50_95,precision,recall,f1,tp,fp,fn\n");
+        for m in &self.classes {
+            let _ = writeln!(
+                csv,
+                "{},{},{},{:.4},{:.4},{:.4},{:.4},{:.4},{},{},{}",
+                m.class,
+                m.gt_count,
+                m.pred_count,
+                m.ap50,
+                m.ap50_95,
+                m.precision,
+                m.recall,
+                m.f1,
+                m.tp,
+                m.fp,
+                m.fn_
+            );
+        }
+        let _ = writeln!(
+            csv,
+            "all,{},{},{:.4},{:.4},,,,,,",
+            self.classes.iter().map(|m| m.gt_count).sum::<usize>(),
+            self.classes.iter().map(|m| m.pred_count).sum::<usize>(),
+            self.map50,
+            self.map50_95
+        );
+        csv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Shape;
+
+    fn rect(class: i32, x: f64, y: f64) -> BoundingBox {
+        BoundingBox {
+            class,
+            x,
+            y,
+            width: 0.1,
+            height: 0.1,
+            confidence: None,
+            shape: Shape::Rect,
+            keypoints: Vec::new(),
+            attributes: Default::default(),
+        }
+    }
+
+    /// 类别 0 有两个真值；预测按置信度依次为 TP、FP、TP，类别 1 只有一个误检
+    fn samples() -> Vec<ImageSample> {
+        vec![ImageSample {
+            gt: vec![rect(0, 0.2, 0.2), rect(0, 0.6, 0.6)],
+            preds: vec![
+                (rect(0, 0.2, 0.2), 0.9),
+                (rect(0, 0.8, 0.2), 0.8),
+                (rect(0, 0.6, 0.6), 0.7),
+                (rect(1, 0.2, 0.8), 0.95),
+            ],
+        }]
+    }
+
+    #[test]
+    fn average_precision_of_hand_computed_curve() {
+        // 精度 1, 1/2, 2/3，召回 1/2, 1/2, 1；包络后召回 0-0.5 的 51 个点精度为 1，
+        // 0.51-1.0 的 50 个点精度为 2/3
+        let expected = (51.0 + 50.0 * 2.0 / 3.0) / 101.0;
+        let ap = average_precision(&samples(), 0, 0.5);
+        assert!((ap - expected).abs() < 1e-9, "{}", ap);
+    }
+
+    #[test]
+    fn counts_at_confidence_threshold() {
+        let report = evaluate_samples(&samples(), 0.75);
+        let class0 = &report.classes[0];
+        assert_eq!((class0.tp, class0.fp, class0.fn_), (1, 1, 1));
+        assert_eq!((class0.gt_count, class0.pred_count), (2, 3));
+        assert!((class0.precision - 0.5).abs() < 1e-9);
+        assert!((class0.recall - 0.5).abs() < 1e-9);
+
+        let report = evaluate_samples(&samples(), 0.5);
+        let class0 = &report.classes[0];
+        assert_eq!((class0.tp, class0.fp, class0.fn_), (2, 1, 0));
+    }
+
+    #[test]
+    fn class_without_ground_truth_is_excluded_from_map() {
+        let report = evaluate_samples(&samples(), 0.5);
+        assert_eq!(report.classes.len(), 2);
+        let class1 = &report.classes[1];
+        assert_eq!((class1.gt_count, class1.fp, class1.ap50), (0, 1, 0.0));
+        assert!((report.map50 - report.classes[0].ap50).abs() < 1e-9);
+        assert!((report.map50_95 - report.classes[0].ap50_95).abs() < 1e-9);
+    }
+
+    #[test]
+    fn confusion_matrix_counts_misses_and_false_positives() {
+        let mut samples = samples();
+        // 再加一个漏检的真值
+        samples[0].gt.push(rect(1, 0.5, 0.9));
+        let report = evaluate_samples(&samples, 0.75);
+        let matrix = &report.confusion.matrix;
+        let background = report.confusion.classes.len();
+        assert_eq!(matrix[0][0], 1); // 类别 0 正确检出
+        assert_eq!(matrix[0][background], 1); // 类别 0 漏检（其预测低于阈值）
+        assert_eq!(matrix[1][background], 1); // 类别 1 漏检
+        assert_eq!(matrix[background][0], 1); // 类别 0 误检
+        assert_eq!(matrix[background][1], 1); // 类别 1 误检
+    }
+}
@@ -1,29 +1,548 @@
-use std::collections::HashMap;
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 
-#[derive(Clone)]
+/// 标注形状。x/y/width/height 始终为形状的外接水平框
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Shape {
+    #[default]
+    Rect, // 水平框
+    Oriented([[f64; 2]; 4]), // 旋转框的四个角点（归一化坐标，依次相连）
+    Polygon(Vec<[f64; 2]>),  // 分割多边形的顶点（归一化坐标，首尾自动闭合）
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ShapeKind {
+    Rect,
+    Oriented,
+    Polygon,
+}
+
+impl ShapeKind {
+    pub const ALL: [ShapeKind; 3] = [ShapeKind::Rect, ShapeKind::Oriented, ShapeKind::Polygon];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ShapeKind::Rect => "水平框",
+            ShapeKind::Oriented => "旋转框",
+            ShapeKind::Polygon => "多边形",
+        }
+    }
+}
+
+/// 关键点（归一化坐标），visibility 沿用 COCO 约定：0 未标注，1 遮挡，2 可见
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Keypoint {
+    pub x: f64,
+    pub y: f64,
+    pub visibility: u8,
+}
+
+impl Keypoint {
+    pub fn is_labeled(&self) -> bool {
+        self.visibility > 0
+    }
+}
+
+/// 框属性值，JSON 中直接写为布尔、整数或字符串
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AttributeValue {
+    Bool(bool),
+    Int(i64),
+    Text(String),
+}
+
+impl AttributeValue {
+    /// 作为 VOC 的 0/1 标志使用时的值
+    pub fn as_flag(&self) -> bool {
+        match self {
+            AttributeValue::Bool(b) => *b,
+            AttributeValue::Int(i) => *i != 0,
+            AttributeValue::Text(s) => !s.is_empty() && s != "0" && s != "false",
+        }
+    }
+
+    pub fn display(&self) -> String {
+        match self {
+            AttributeValue::Bool(b) => if *b { "1" } else { "0" }.to_string(),
+            AttributeValue::Int(i) => i.to_string(),
+            AttributeValue::Text(s) => s.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct BoundingBox {
     pub class: i32,
     pub x: f64,
     pub y: f64,
     pub width: f64,
     pub height: f64,
+    pub confidence: Option<f64>, // 模型预标注的置信度，人工确认后为 None
+    pub shape: Shape,
+    pub keypoints: Vec<Keypoint>, // 关键点，按类别的关键点模板顺序排列
+    pub attributes: BTreeMap<String, AttributeValue>, // 框属性，保存在标签旁的 .attrs.json 中
 }
 
-#[derive(Clone)]
+impl BoundingBox {
+    /// 是否为低于阈值的预标注框（人工确认过的框不算）
+    pub fn is_low_confidence(&self, threshold: f64) -> bool {
+        self.confidence.is_some_and(|c| c < threshold)
+    }
+
+    /// 由形状构造，外接水平框自动计算
+    pub fn from_shape(class: i32, shape: Shape) -> Self {
+        let mut bbox = Self {
+            class,
+            x: 0.0,
+            y: 0.0,
+            width: 0.0,
+            height: 0.0,
+            confidence: None,
+            shape,
+            keypoints: Vec::new(),
+            attributes: BTreeMap::new(),
+        };
+        bbox.update_bounds();
+        bbox
+    }
+
+    pub fn kind(&self) -> ShapeKind {
+        match self.shape {
+            Shape::Rect => ShapeKind::Rect,
+            Shape::Oriented(_) => ShapeKind::Oriented,
+            Shape::Polygon(_) => ShapeKind::Polygon,
+        }
+    }
+
+    /// 四个角点：旋转框为其角点，其他形状为外接水平框的角点（左上、右上、右下、左下）
+    pub fn corners(&self) -> [[f64; 2]; 4] {
+        match &self.shape {
+            Shape::Oriented(corners) => *corners,
+            Shape::Rect | Shape::Polygon(_) => {
+                let (x1, y1) = (self.x - self.width / 2.0, self.y - self.height / 2.0);
+                let (x2, y2) = (self.x + self.width / 2.0, self.y + self.height / 2.0);
+                [[x1, y1], [x2, y1], [x2, y2], [x1, y2]]
+            }
+        }
+    }
+
+    /// 轮廓顶点，用于绘制和命中测试
+    pub fn outline(&self) -> Vec<[f64; 2]> {
+        match &self.shape {
+            Shape::Polygon(points) => points.clone(),
+            _ => self.corners().to_vec(),
+        }
+    }
+
+    /// 根据轮廓重新计算外接水平框，水平框本身不变
+    pub fn update_bounds(&mut self) {
+        let points = match &self.shape {
+            Shape::Rect => return,
+            Shape::Oriented(corners) => corners.to_vec(),
+            Shape::Polygon(points) => points.clone(),
+        };
+        if points.is_empty() {
+            return;
+        }
+        let (mut min_x, mut min_y) = (f64::MAX, f64::MAX);
+        let (mut max_x, mut max_y) = (f64::MIN, f64::MIN);
+        for [x, y] in points {
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+        }
+        self.x = (min_x + max_x) / 2.0;
+        self.y = (min_y + max_y) / 2.0;
+        self.width = max_x - min_x;
+        self.height = max_y - min_y;
+    }
+
+    /// 能否在水平框和旋转框之间转换。多边形不参与转换；
+    /// 关键点只随水平框保存，带关键点的框不能转为旋转框
+    pub fn can_convert_shape(&self, oriented: bool) -> bool {
+        match self.shape {
+            Shape::Rect => !oriented || self.keypoints.is_empty(),
+            Shape::Oriented(_) => true,
+            Shape::Polygon(_) => false,
+        }
+    }
+
+    pub fn make_oriented(&mut self) {
+        self.shape = Shape::Oriented(self.corners());
+    }
+
+    /// 转为外接水平框
+    pub fn make_axis_aligned(&mut self) {
+        self.update_bounds();
+        self.shape = Shape::Rect;
+    }
+
+    /// 平移，保证整个框不超出图像范围
+    pub fn translate(&mut self, dx: f64, dy: f64) {
+        let dx = dx.clamp(
+            -(self.x - self.width / 2.0),
+            1.0 - (self.x + self.width / 2.0),
+        );
+        let dy = dy.clamp(
+            -(self.y - self.height / 2.0),
+            1.0 - (self.y + self.height / 2.0),
+        );
+        self.x += dx;
+        self.y += dy;
+        let points: &mut [[f64; 2]] = match &mut self.shape {
+            Shape::Rect => &mut [],
+            Shape::Oriented(corners) => corners,
+            Shape::Polygon(points) => points,
+        };
+        for point in points {
+            point[0] += dx;
+            point[1] += dy;
+        }
+        for kp in self.keypoints.iter_mut().filter(|kp| kp.is_labeled()) {
+            kp.x = (kp.x + dx).clamp(0.0, 1.0);
+            kp.y = (kp.y + dy).clamp(0.0, 1.0);
+        }
+    }
+
+    /// 绕中心旋转（弧度，屏幕坐标系顺时针为正）。aspect 为图片宽高比，
+    /// 用于在像素比例下旋转，避免非正方形图片上的形变。旋转后超出图像范围时不做修改。
+    /// 多边形和带关键点的框不能旋转（关键点只随水平框保存）
+    pub fn rotate(&mut self, angle: f64, aspect: f64) {
+        if !self.can_convert_shape(true) {
+            return;
+        }
+        let (sin, cos) = angle.sin_cos();
+        let (cx, cy) = (self.x * aspect, self.y);
+        let mut rotated = self.corners();
+        for corner in rotated.iter_mut() {
+            let (px, py) = (corner[0] * aspect - cx, corner[1] - cy);
+            corner[0] = (cx + px * cos - py * sin) / aspect;
+            corner[1] = cy + px * sin + py * cos;
+        }
+        if rotated
+            .iter()
+            .flatten()
+            .all(|v| (0.0..=1.0).contains(v))
+        {
+            self.shape = Shape::Oriented(rotated);
+            self.update_bounds();
+        }
+    }
+
+    /// 第一条边相对水平方向的角度（度），用于显示
+    pub fn angle_degrees(&self, aspect: f64) -> f64 {
+        let [a, b, ..] = self.corners();
+        ((b[1] - a[1]).atan2((b[0] - a[0]) * aspect)).to_degrees()
+    }
+
+    /// 归一化坐标的点是否在形状内
+    pub fn contains(&self, px: f64, py: f64) -> bool {
+        match &self.shape {
+            Shape::Rect => {
+                (px - self.x).abs() <= self.width / 2.0 && (py - self.y).abs() <= self.height / 2.0
+            }
+            Shape::Oriented(corners) => point_in_polygon(corners, px, py),
+            Shape::Polygon(points) => point_in_polygon(points, px, py),
+        }
+    }
+
+    /// 移动多边形的一个顶点（限制在图像范围内）
+    pub fn move_vertex(&mut self, index: usize, x: f64, y: f64) {
+        if let Shape::Polygon(points) = &mut self.shape
+            && let Some(point) = points.get_mut(index)
+        {
+            *point = [x.clamp(0.0, 1.0), y.clamp(0.0, 1.0)];
+        }
+        self.update_bounds();
+    }
+
+    /// 在第 index 个顶点之后插入顶点
+    pub fn insert_vertex(&mut self, index: usize, x: f64, y: f64) {
+        if let Shape::Polygon(points) = &mut self.shape {
+            let at = (index + 1).min(points.len());
+            points.insert(at, [x.clamp(0.0, 1.0), y.clamp(0.0, 1.0)]);
+        }
+        self.update_bounds();
+    }
+
+    /// 删除多边形顶点，至少保留三个顶点，返回是否删除成功
+    pub fn remove_vertex(&mut self, index: usize) -> bool {
+        let removed = match &mut self.shape {
+            Shape::Polygon(points) if points.len() > 3 && index < points.len() => {
+                points.remove(index);
+                true
+            }
+            _ => false,
+        };
+        self.update_bounds();
+        removed
+    }
+}
+
+/// 射线法判断点是否在多边形内
+pub fn point_in_polygon(points: &[[f64; 2]], px: f64, py: f64) -> bool {
+    let mut inside = false;
+    let mut j = points.len().wrapping_sub(1);
+    for i in 0..points.len() {
+        let ([xi, yi], [xj, yj]) = (points[i], points[j]);
+        if (yi > py) != (yj > py) && px < (xj - xi) * (py - yi) / (yj - yi) + xi {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+/// 四个点依次相连是否构成平行四边形（旋转框在任意缩放下都满足）
+pub fn is_parallelogram(points: &[[f64; 2]; 4]) -> bool {
+    const EPSILON: f64 = 1e-4;
+    let [a, b, c, d] = points;
+    (a[0] + c[0] - b[0] - d[0]).abs() < EPSILON && (a[1] + c[1] - b[1] - d[1]).abs() < EPSILON
+}
+
+/// 图片级标注状态
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageStatus {
+    #[default]
+    Todo, // 未开始
+    InProgress,  // 已打开并自动保存过，但尚未确认完成
+    Labeled,     // 标注完成
+    NeedsReview, // 需要复核
+    Approved,    // 审核通过
+    Rejected,    // 审核驳回
+    Skipped,     // 跳过（无需标注）
+    PreLabeled,  // 已由模型自动标注，等待人工检查
+}
+
+impl ImageStatus {
+    pub const ALL: [ImageStatus; 8] = [
+        ImageStatus::Todo,
+        ImageStatus::InProgress,
+        ImageStatus::Labeled,
+        ImageStatus::NeedsReview,
+        ImageStatus::Approved,
+        ImageStatus::Rejected,
+        ImageStatus::Skipped,
+        ImageStatus::PreLabeled,
+    ];
+
+    /// 配置文件中使用的标识，与序列化名称一致
+    pub fn id(&self) -> &'static str {
+        match self {
+            ImageStatus::Todo => "todo",
+            ImageStatus::InProgress => "in_progress",
+            ImageStatus::Labeled => "labeled",
+            ImageStatus::NeedsReview => "needs_review",
+            ImageStatus::Approved => "approved",
+            ImageStatus::Rejected => "rejected",
+            ImageStatus::Skipped => "skipped",
+            ImageStatus::PreLabeled => "pre_labeled",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ImageStatus::Todo => "未开始",
+            ImageStatus::InProgress => "进行中",
+            ImageStatus::Labeled => "已标注",
+            ImageStatus::NeedsReview => "待复核",
+            ImageStatus::Approved => "已通过",
+            ImageStatus::Rejected => "已驳回",
+            ImageStatus::Skipped => "已跳过",
+            ImageStatus::PreLabeled => "已预标注",
+        }
+    }
+
+    pub fn color(&self) -> egui::Color32 {
+        match self {
+            ImageStatus::Todo => egui::Color32::BLACK,
+            ImageStatus::InProgress => egui::Color32::from_rgb(180, 120, 0),
+            ImageStatus::Labeled => egui::Color32::from_rgb(0, 100, 0),
+            ImageStatus::NeedsReview => egui::Color32::from_rgb(150, 0, 150),
+            ImageStatus::Approved => egui::Color32::from_rgb(0, 90, 200),
+            ImageStatus::Rejected => egui::Color32::from_rgb(200, 0, 0),
+            ImageStatus::Skipped => egui::Color32::GRAY,
+            ImageStatus::PreLabeled => egui::Color32::from_rgb(0, 140, 140),
+        }
+    }
+
+    /// 是否已完成（计入标注进度，不再出现在"下一张未修改"队列中）
+    pub fn is_done(&self) -> bool {
+        matches!(
+            self,
+            ImageStatus::Labeled
+                | ImageStatus::NeedsReview
+                | ImageStatus::Approved
+                | ImageStatus::Skipped
+        )
+    }
+}
+
+/// 审核意见，可以关联到某个边界框或整张图片
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReviewComment {
+    pub box_index: Option<usize>, // None 表示针对整张图片
+    pub text: String,
+    pub author: String,
+    pub created_at: u64,
+    pub resolved: bool,
+}
+
+/// 单张图片的状态记录
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImageRecord {
+    pub status: ImageStatus,
+    pub note: String,
+    pub updated_at: u64, // Unix 时间戳（秒）
+    pub last_editor: String,
+    pub last_edited_at: u64,
+    pub reviewer: String,
+    pub comments: Vec<ReviewComment>,
+}
+
+impl ImageRecord {
+    pub fn open_comments(&self) -> impl Iterator<Item = &ReviewComment> {
+        self.comments.iter().filter(|c| !c.resolved)
+    }
+
+    /// 删除若干框（按删除前的序号）后更新批注指向的框，
+    /// 指向被删除框的批注改为针对整张图片。返回是否有批注被修改
+    pub fn remove_comment_boxes(&mut self, removed: &[usize]) -> bool {
+        let mut changed = false;
+        for comment in &mut self.comments {
+            let Some(index) = comment.box_index else {
+                continue;
+            };
+            let updated = if removed.contains(&index) {
+                None
+            } else {
+                Some(index - removed.iter().filter(|r| **r < index).count())
+            };
+            if updated != comment.box_index {
+                comment.box_index = updated;
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+/// 按图片标签过滤
+#[derive(Clone, Default, PartialEq)]
+pub enum TagFilter {
+    #[default]
+    All,
+    Untagged,    // 没有任何标签的图片
+    Tag(String), // 带有指定标签的图片
+}
+
+/// 文件列表过滤条件
+#[derive(Clone, Default)]
+pub struct FileFilter {
+    pub status: Option<ImageStatus>, // None 表示不过滤
+    pub tag: TagFilter,
+}
+
+#[derive(Clone, Default)]
 pub struct Statistics {
     pub total_images: usize,
-    pub modified_images: usize,
+    pub completed_images: usize,
+    pub status_counts: HashMap<ImageStatus, usize>, // 各状态的图片数量
     pub total_class_counts: HashMap<i32, usize>, // 所有图片中各类型的数量
     pub current_class_counts: HashMap<i32, usize>, // 当前图片中各类型的数量
+    pub total_shape_counts: HashMap<ShapeKind, usize>, // 所有图片中各形状的数量
+    pub current_shape_counts: HashMap<ShapeKind, usize>, // 当前图片中各形状的数量
 }
 
-impl Default for Statistics {
-    fn default() -> Self {
-        Self {
-            total_images: 0,
-            modified_images: 0,
-            total_class_counts: HashMap::new(),
-            current_class_counts: HashMap::new(),
+impl Statistics {
+    /// 将一组框计入总体统计
+    pub fn add_boxes(&mut self, boxes: &[BoundingBox]) {
+        for bbox in boxes {
+            *self.total_class_counts.entry(bbox.class).or_insert(0) += 1;
+            *self.total_shape_counts.entry(bbox.kind()).or_insert(0) += 1;
+        }
+    }
+
+    /// 从总体统计中减去一组框，数量减到零的类别和形状会被移除
+    pub fn remove_boxes(&mut self, boxes: &[BoundingBox]) {
+        fn decrement<K: std::hash::Hash + Eq>(counts: &mut HashMap<K, usize>, key: K) {
+            if let Some(count) = counts.get_mut(&key) {
+                *count = count.saturating_sub(1);
+                if *count == 0 {
+                    counts.remove(&key);
+                }
+            }
+        }
+        for bbox in boxes {
+            decrement(&mut self.total_class_counts, bbox.class);
+            decrement(&mut self.total_shape_counts, bbox.kind());
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::labels::{self, KeypointLayout, LabelLayout};
+
+    fn pose_box() -> BoundingBox {
+        BoundingBox {
+            class: 0,
+            x: 0.5,
+            y: 0.5,
+            width: 0.2,
+            height: 0.4,
+            confidence: None,
+            shape: Shape::Rect,
+            keypoints: vec![
+                Keypoint {
+                    x: 0.45,
+                    y: 0.4,
+                    visibility: 2,
+                },
+                Keypoint {
+                    x: 0.55,
+                    y: 0.6,
+                    visibility: 1,
+                },
+            ],
+            attributes: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn keypoint_box_is_not_rotated() {
+        let mut bbox = pose_box();
+        assert!(!bbox.can_convert_shape(true));
+        bbox.rotate(0.3, 1.0);
+        assert_eq!(bbox.kind(), ShapeKind::Rect);
+        assert_eq!(bbox, pose_box());
+
+        let mut layout = LabelLayout::default();
+        layout.keypoints.insert(
+            0,
+            KeypointLayout {
+                count: 2,
+                with_visibility: true,
+            },
+        );
+        let mut content = Vec::new();
+        labels::write_labels(&mut content, &[bbox.clone()], &layout).unwrap();
+        let line = String::from_utf8(content).unwrap();
+        let parsed = labels::parse_line(line.trim(), &layout).unwrap();
+        assert_eq!(parsed, bbox);
+    }
+
+    #[test]
+    fn plain_box_rotates_into_oriented() {
+        let mut bbox = pose_box();
+        bbox.keypoints.clear();
+        bbox.rotate(0.3, 1.0);
+        assert_eq!(bbox.kind(), ShapeKind::Oriented);
+    }
+}
@@ -0,0 +1,313 @@
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::autolabel::AutoLabelConfig;
+use crate::dedup::DedupConfig;
+use crate::export::{CropConfig, ExportProfile};
+use crate::keymap::KeymapOverrides;
+use crate::labels::{KeypointLayout, LabelLayout};
+use crate::models::AttributeValue;
+use crate::remap::RemapConfig;
+use crate::split::SplitConfig;
+use crate::utils::auto_class_color;
+
+/// 项目配置文件名，保存在标签目录下
+pub const PROJECT_FILE_NAME: &str = "label_space_project.json";
+
+/// 标注模式，决定新绘制的框使用哪种形状
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnnotationMode {
+    #[default]
+    Detect, // 水平框
+    Obb,     // 旋转框（YOLO-OBB）
+    Segment, // 分割多边形（YOLO-seg）
+    Pose,    // 水平框 + 关键点（YOLO-pose）
+}
+
+impl AnnotationMode {
+    pub const ALL: [AnnotationMode; 4] = [
+        AnnotationMode::Detect,
+        AnnotationMode::Obb,
+        AnnotationMode::Segment,
+        AnnotationMode::Pose,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AnnotationMode::Detect => "水平框",
+            AnnotationMode::Obb => "旋转框 (OBB)",
+            AnnotationMode::Segment => "多边形 (分割)",
+            AnnotationMode::Pose => "关键点 (姿态)",
+        }
+    }
+}
+
+/// 某个类别的关键点模板
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeypointSchema {
+    pub names: Vec<String>,          // 关键点名称，顺序即标签文件中的顺序
+    pub skeleton: Vec<[usize; 2]>,   // 骨架连线（关键点序号）
+    pub flip_pairs: Vec<[usize; 2]>, // 水平翻转时互换的关键点对（如左右肩）
+    pub with_visibility: bool,       // 标签中每个关键点是否带可见性列
+}
+
+impl KeypointSchema {
+    /// Ultralytics 数据集配置中的 flip_idx：翻转后每个位置对应的原关键点序号
+    pub fn flip_index(&self) -> Vec<usize> {
+        let mut index: Vec<usize> = (0..self.names.len()).collect();
+        for &[a, b] in &self.flip_pairs {
+            if a < index.len() && b < index.len() {
+                index.swap(a, b);
+            }
+        }
+        index
+    }
+}
+
+/// 关键点模板编辑窗口中的文本草稿
+#[derive(Clone, Default)]
+pub struct KeypointSchemaDraft {
+    pub class: i32,
+    pub names: String,      // 每行一个关键点名称
+    pub skeleton: String,   // 如 `0-1, 1-2`
+    pub flip_pairs: String, // 如 `1-2, 3-4`
+    pub with_visibility: bool,
+}
+
+impl KeypointSchemaDraft {
+    pub fn new(class: i32, schema: Option<&KeypointSchema>) -> Self {
+        let format_pairs = |pairs: &[[usize; 2]]| {
+            pairs
+                .iter()
+                .map(|[a, b]| format!("{}-{}", a, b))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match schema {
+            Some(schema) => Self {
+                class,
+                names: schema.names.join("\n"),
+                skeleton: format_pairs(&schema.skeleton),
+                flip_pairs: format_pairs(&schema.flip_pairs),
+                with_visibility: schema.with_visibility,
+            },
+            None => Self {
+                class,
+                with_visibility: true,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// 解析为关键点模板，连线和翻转对中的序号必须在关键点范围内
+    pub fn parse(&self) -> Result<KeypointSchema, String> {
+        let names: Vec<String> = self
+            .names
+            .lines()
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty())
+            .collect();
+        let parse_pairs = |text: &str, what: &str| -> Result<Vec<[usize; 2]>, String> {
+            text.split([',', '，', '\n'])
+                .map(str::trim)
+                .filter(|pair| !pair.is_empty())
+                .map(|pair| {
+                    let invalid = || format!("{}格式错误: {}", what, pair);
+                    let (a, b) = pair.split_once('-').ok_or_else(invalid)?;
+                    let a: usize = a.trim().parse().map_err(|_| invalid())?;
+                    let b: usize = b.trim().parse().map_err(|_| invalid())?;
+                    if a >= names.len() || b >= names.len() {
+                        return Err(format!("{}中的序号超出关键点数量: {}", what, pair));
+                    }
+                    Ok([a, b])
+                })
+                .collect()
+        };
+        Ok(KeypointSchema {
+            skeleton: parse_pairs(&self.skeleton, "骨架连线")?,
+            flip_pairs: parse_pairs(&self.flip_pairs, "翻转对")?,
+            with_visibility: self.with_visibility,
+            names,
+        })
+    }
+}
+
+/// 图片级标签定义。分组不为空时，同组标签互斥（如 白天/夜晚）
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TagDefinition {
+    pub name: String,
+    pub group: String,
+}
+
+/// 框属性的取值类型
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttributeKind {
+    #[default]
+    Bool,
+    Int,
+    Choice, // 从预设选项中选择
+    Text,
+}
+
+impl AttributeKind {
+    pub const ALL: [AttributeKind; 4] = [
+        AttributeKind::Bool,
+        AttributeKind::Int,
+        AttributeKind::Choice,
+        AttributeKind::Text,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AttributeKind::Bool => "布尔",
+            AttributeKind::Int => "整数",
+            AttributeKind::Choice => "选项",
+            AttributeKind::Text => "文本",
+        }
+    }
+}
+
+/// 框属性定义
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AttributeDefinition {
+    pub name: String,
+    pub kind: AttributeKind,
+    pub options: Vec<String>, // 仅用于选项类型
+}
+
+impl AttributeDefinition {
+    pub fn new(name: &str, kind: AttributeKind) -> Self {
+        Self {
+            name: name.to_string(),
+            kind,
+            options: Vec::new(),
+        }
+    }
+
+    /// VOC 常用属性：遮挡、截断、困难样本，以及跟踪 ID
+    pub fn voc_presets() -> Vec<Self> {
+        vec![
+            Self::new("occluded", AttributeKind::Bool),
+            Self::new("truncated", AttributeKind::Bool),
+            Self::new("difficult", AttributeKind::Bool),
+            Self::new("track_id", AttributeKind::Int),
+        ]
+    }
+
+    /// 未设置时显示的值
+    pub fn default_value(&self) -> AttributeValue {
+        match self.kind {
+            AttributeKind::Bool => AttributeValue::Bool(false),
+            AttributeKind::Int => AttributeValue::Int(0),
+            AttributeKind::Choice => {
+                AttributeValue::Text(self.options.first().cloned().unwrap_or_default())
+            }
+            AttributeKind::Text => AttributeValue::Text(String::new()),
+        }
+    }
+}
+
+/// 随标签目录保存的项目配置
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectConfig {
+    pub class_names: BTreeMap<i32, String>,  // 类别名称
+    pub class_colors: BTreeMap<i32, [u8; 3]>, // 用户自定义的类别颜色（覆盖自动配色）
+    pub keymap: KeymapOverrides,              // 项目级快捷键，优先于用户配置
+    pub annotator: String,                    // 当前标注员/审核员名称
+    pub auto_label: AutoLabelConfig,          // 自动标注模型设置
+    pub annotation_mode: AnnotationMode,
+    pub keypoint_schemas: BTreeMap<i32, KeypointSchema>, // 各类别的关键点模板
+    pub image_tags: Vec<TagDefinition>, // 图片级标签，顺序对应 Ctrl+1-9
+    pub common_attributes: Vec<AttributeDefinition>, // 所有类别共用的框属性
+    pub class_attributes: BTreeMap<i32, Vec<AttributeDefinition>>, // 各类别特有的框属性
+    pub split: SplitConfig,                                         // 训练/验证/测试划分设置
+    pub export_profiles: Vec<ExportProfile>,                        // 导出方案
+    pub crops: CropConfig,                                          // 裁剪导出设置
+    pub remap: RemapConfig,                                         // 类别重映射设置
+    pub dedup: DedupConfig,                                         // 近似重复检测设置
+}
+
+impl ProjectConfig {
+    pub fn path(label_dir: &Path) -> PathBuf {
+        label_dir.join(PROJECT_FILE_NAME)
+    }
+
+    /// 从标签目录读取项目配置，文件不存在时返回默认配置。
+    /// 文件存在但无法读取或解析时返回错误，调用方不应再用默认配置覆盖它
+    pub fn load(label_dir: &Path) -> Result<Self, String> {
+        let path = Self::path(label_dir);
+        match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|e| format!("解析 {} 失败: {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("读取 {} 失败: {}", path.display(), e)),
+        }
+    }
+
+    pub fn save(&self, label_dir: &Path) -> Result<(), String> {
+        let content =
+            serde_json::to_string_pretty(self).map_err(|e| format!("序列化项目配置失败: {}", e))?;
+        fs::write(Self::path(label_dir), content).map_err(|e| format!("保存项目配置失败: {}", e))
+    }
+
+    /// 标注员名称，未设置时使用系统用户名
+    pub fn annotator_name(&self) -> String {
+        if !self.annotator.is_empty() {
+            return self.annotator.clone();
+        }
+        std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .unwrap_or_else(|_| "unknown".to_string())
+    }
+
+    /// 标签文件的解析布局，由各类别的关键点模板决定
+    pub fn label_layout(&self) -> LabelLayout {
+        LabelLayout {
+            keypoints: self
+                .keypoint_schemas
+                .iter()
+                .filter(|(_, schema)| !schema.names.is_empty())
+                .map(|(class, schema)| {
+                    let layout = KeypointLayout {
+                        count: schema.names.len(),
+                        with_visibility: schema.with_visibility,
+                    };
+                    (*class, layout)
+                })
+                .collect(),
+        }
+    }
+
+    /// 某个类别可用的框属性：共用属性在前，类别特有属性在后
+    pub fn attributes_for(&self, class: i32) -> Vec<&AttributeDefinition> {
+        self.common_attributes
+            .iter()
+            .chain(self.class_attributes.get(&class).into_iter().flatten())
+            .collect()
+    }
+
+    /// 类别显示名称，未命名时使用 `Class N`
+    pub fn class_name(&self, class: i32) -> String {
+        self.class_names
+            .get(&class)
+            .cloned()
+            .unwrap_or_else(|| format!("Class {}", class))
+    }
+
+    /// 类别颜色，未自定义时使用自动配色
+    pub fn class_color(&self, class: i32) -> egui::Color32 {
+        match self.class_colors.get(&class) {
+            Some([r, g, b]) => egui::Color32::from_rgb(*r, *g, *b),
+            None => auto_class_color(class),
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use crate::labels::{self, LabelLayout};
+use crate::models::BoundingBox;
+
+/// 类别重映射的目标
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RemapTarget {
+    Class(i32), // 改为新的类别编号，多个类别可以合并到同一个编号
+    Drop,       // 删除该类别的框
+}
+
+/// 原类别 -> 目标，不在表中的类别保持不变
+pub type RemapTable = BTreeMap<i32, RemapTarget>;
+
+/// 类别重映射设置，保存在项目配置中
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RemapConfig {
+    pub table: RemapTable,
+    pub on_export: bool,  // 导出时应用（不修改标签目录）
+    pub skip_empty: bool, // 导出时跳过重映射后不再有框的图片
+}
+
+impl RemapConfig {
+    /// 导出时是否需要应用
+    pub fn export_table(&self) -> Option<&RemapTable> {
+        (self.on_export && !self.table.is_empty()).then_some(&self.table)
+    }
+}
+
+/// 类别重映射后的编号，删除时返回 None
+pub fn map_class(table: &RemapTable, class: i32) -> Option<i32> {
+    match table.get(&class) {
+        Some(RemapTarget::Drop) => None,
+        Some(RemapTarget::Class(target)) => Some(*target),
+        None => Some(class),
+    }
+}
+
+/// 重映射时会被删除的框的序号
+pub fn dropped_indices(table: &RemapTable, boxes: &[BoundingBox]) -> Vec<usize> {
+    boxes
+        .iter()
+        .enumerate()
+        .filter(|(_, bbox)| map_class(table, bbox.class).is_none())
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// 对一组框应用重映射，返回（修改类别的框数，删除的框数）
+pub fn apply(table: &RemapTable, boxes: &mut Vec<BoundingBox>) -> (usize, usize) {
+    let before = boxes.len();
+    boxes.retain(|bbox| map_class(table, bbox.class).is_some());
+    let dropped = before - boxes.len();
+    let mut changed = 0;
+    for bbox in boxes.iter_mut() {
+        if let Some(class) = map_class(table, bbox.class).filter(|c| *c != bbox.class) {
+            bbox.class = class;
+            changed += 1;
+        }
+    }
+    (changed, dropped)
+}
+
+/// 重映射前后各类别的框数量（类别，之前，之后），用于预览
+pub fn preview(counts: &HashMap<i32, usize>, table: &RemapTable) -> Vec<(i32, usize, usize)> {
+    let mut rows: BTreeMap<i32, (usize, usize)> = BTreeMap::new();
+    for (class, count) in counts {
+        rows.entry(*class).or_default().0 += count;
+        if let Some(target) = map_class(table, *class) {
+            rows.entry(target).or_default().1 += count;
+        }
+    }
+    rows.into_iter()
+        .map(|(class, (before, after))| (class, before, after))
+        .collect()
+}
+
+/// 批量重映射结果
+#[derive(Default)]
+pub struct RemapReport {
+    pub files: usize, // 修改的标签文件数量
+    pub changed: usize,
+    pub dropped: usize,
+    pub dropped_boxes: Vec<(String, Vec<usize>)>, // 删除了框的图片及被删除框的原序号，用于更新批注
+    pub failures: Vec<String>,
+}
+
+/// 直接修改标签目录中的标签文件（包括属性文件），没有变化的文件不重写
+pub fn remap_label_dir(
+    images: &[PathBuf],
+    label_dir: &Path,
+    layout: &LabelLayout,
+    table: &RemapTable,
+) -> RemapReport {
+    let mut report = RemapReport::default();
+    for image in images {
+        let path = labels::label_path(label_dir, image);
+        if !path.exists() {
+            continue;
+        }
+        let mut boxes = labels::read_label_file(&path, layout);
+        let removed = dropped_indices(table, &boxes);
+        let (changed, dropped) = apply(table, &mut boxes);
+        if changed == 0 && dropped == 0 {
+            continue;
+        }
+        match labels::write_label_file(&path, &boxes, layout) {
+            Ok(()) => {
+                report.files += 1;
+                report.changed += changed;
+                report.dropped += dropped;
+                if !removed.is_empty() {
+                    let name = image
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    report.dropped_boxes.push((name, removed));
+                }
+            }
+            Err(e) => report
+                .failures
+                .push(format!("写入 {} 失败: {}", path.display(), e)),
+        }
+    }
+    report
+}
@@ -0,0 +1,365 @@
+use eframe::egui;
+use image::codecs::jpeg::JpegEncoder;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::labels;
+use crate::lint::{self, LintKind, TINY_BOX_PIXELS};
+use crate::models::{BoundingBox, ImageRecord, ImageStatus, ShapeKind};
+use crate::project::ProjectConfig;
+use crate::stats::{DatasetStats, HEATMAP_SIZE, Histogram};
+use crate::utils::{base64_encode, format_timestamp, html_escape, now_secs};
+
+/// 报告中示例图片的数量
+pub const GALLERY_SIZE: usize = 24;
+/// 示例缩略图的最大边长
+const THUMBNAIL_SIZE: u32 = 320;
+/// 报告中最多列出的问题框数量，其余只计数
+const MAX_LISTED_ISSUES: usize = 200;
+
+const STYLE: &str = "body{font-family:sans-serif;margin:24px;color:#222}
+h1{margin-bottom:4px}h2{margin-top:32px;border-bottom:1px solid #ccc}
+table{border-collapse:collapse}td,th{padding:3px 10px;text-align:left;border-bottom:1px solid #eee}
+.bar{background:#4a90d9;height:12px;display:inline-block}
+.swatch{display:inline-block;width:12px;height:12px;margin-right:4px;vertical-align:middle}
+.hist{display:inline-block;vertical-align:top;margin:0 24px 16px 0}
+.gallery{display:flex;flex-wrap:wrap;gap:12px}
+.gallery figure{margin:0;font-size:12px}.muted{color:#888}";
+
+/// 生成自包含的 HTML 数据集报告：概览、标注状态、类别分布、尺寸直方图、标注问题和示例图片。
+/// `stats` 为对 `images` 的统计结果，示例图片内嵌为 Base64
+pub fn html_report(
+    stats: &DatasetStats,
+    images: &[PathBuf],
+    label_dir: &Path,
+    project: &ProjectConfig,
+    records: &HashMap<String, ImageRecord>,
+) -> String {
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>数据集报告</title>\
+         <style>{}</style></head><body>\n<h1>数据集报告</h1>\n\
+         <p class=\"muted\">{} · 生成于 {}</p>\n",
+        STYLE,
+        html_escape(&label_dir.display().to_string()),
+        format_timestamp(now_secs())
+    );
+
+    summary_section(&mut html, stats);
+    status_section(&mut html, images, records);
+    class_section(&mut html, stats, project);
+    histogram_section(&mut html, stats);
+    lint_section(&mut html, stats, project);
+    gallery_section(&mut html, images, label_dir, project);
+
+    html.push_str("</body></html>\n");
+    html
+}
+
+fn summary_section(html: &mut String, stats: &DatasetStats) {
+    html.push_str("<h2>概览</h2>\n<table>\n");
+    let rows = [
+        ("图片", stats.images.to_string()),
+        ("有标注的图片", stats.labeled_images.to_string()),
+        ("框", stats.boxes.to_string()),
+        (
+            "平均每图框数",
+            format!("{:.2}", stats.boxes as f64 / stats.images.max(1) as f64),
+        ),
+    ];
+    for (name, value) in rows {
+        let _ = writeln!(html, "<tr><th>{}</th><td>{}</td></tr>", name, value);
+    }
+    for kind in ShapeKind::ALL {
+        let count = stats.shape_counts.get(&kind).copied().unwrap_or(0);
+        let _ = writeln!(html, "<tr><th>{}</th><td>{}</td></tr>", kind.label(), count);
+    }
+    html.push_str("</table>\n");
+}
+
+fn status_section(html: &mut String, images: &[PathBuf], records: &HashMap<String, ImageRecord>) {
+    let mut counts: HashMap<ImageStatus, usize> = HashMap::new();
+    for path in images {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let status = records
+            .get(name.as_ref())
+            .map(|record| record.status)
+            .unwrap_or_default();
+        *counts.entry(status).or_insert(0) += 1;
+    }
+    let max = counts.values().copied().max().unwrap_or(0).max(1);
+    html.push_str("<h2>标注状态</h2>\n<table>\n");
+    for status in ImageStatus::ALL {
+        let count = counts.get(&status).copied().unwrap_or(0);
+        let _ = writeln!(
+            html,
+            "<tr><td><span class=\"swatch\" style=\"background:{}\"></span>{}</td><td>{}</td><td>{}</td></tr>",
+            hex(status.color()),
+            status.label(),
+            count,
+            bar(count, max)
+        );
+    }
+    html.push_str("</table>\n");
+}
+
+fn class_section(html: &mut String, stats: &DatasetStats, project: &ProjectConfig) {
+    html.push_str("<h2>类别分布</h2>\n");
+    if stats.class_boxes.is_empty() {
+        html.push_str("<p class=\"muted\">没有标注</p>\n");
+        return;
+    }
+    let max = stats
+        .class_boxes
+        .values()
+        .copied()
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    html.push_str(
+        "<table>\n<tr><th>类别</th><th>框</th><th>图片</th><th>图片占比</th><th></th></tr>\n",
+    );
+    for (class, boxes) in &stats.class_boxes {
+        let images = stats.class_images.get(class).copied().unwrap_or(0);
+        let _ = writeln!(
+            html,
+            "<tr><td><span class=\"swatch\" style=\"background:{}\"></span>{} {}</td>\
+             <td>{}</td><td>{}</td><td>{:.1}%</td><td>{}</td></tr>",
+            hex(project.class_color(*class)),
+            class,
+            html_escape(&project.class_name(*class)),
+            boxes,
+            images,
+            images as f64 * 100.0 / stats.images.max(1) as f64,
+            bar(*boxes, max)
+        );
+    }
+    html.push_str("</table>\n");
+}
+
+fn histogram_section(html: &mut String, stats: &DatasetStats) {
+    html.push_str("<h2>尺寸分布</h2>\n");
+    if !stats.failures.is_empty() {
+        let _ = writeln!(
+            html,
+            "<p class=\"muted\">{} 张图片读取尺寸失败，未计入像素统计</p>",
+            stats.failures.len()
+        );
+    }
+    for (title, histogram) in [
+        ("框宽度（像素）", &stats.widths),
+        ("框高度（像素）", &stats.heights),
+        ("宽高比（宽 / 高）", &stats.aspect_ratios),
+        ("每张图片的框数量", &stats.boxes_per_image),
+    ] {
+        histogram_table(html, title, histogram);
+    }
+
+    // 框中心位置热力图，与统计窗口使用相同的配色
+    let max = stats.heatmap.iter().copied().max().unwrap_or(0).max(1);
+    let _ = write!(
+        html,
+        "<div class=\"hist\"><h3>框中心位置热力图</h3>\
+         <svg width=\"256\" height=\"256\" viewBox=\"0 0 {0} {0}\" shape-rendering=\"crispEdges\">",
+        HEATMAP_SIZE
+    );
+    for (index, count) in stats.heatmap.iter().enumerate() {
+        let t = (*count as f32 / max as f32).sqrt();
+        let mix = |a: f32, b: f32| (a + (b - a) * t) as u8;
+        let color = egui::Color32::from_rgb(mix(20.0, 255.0), mix(20.0, 210.0), mix(60.0, 40.0));
+        let _ = write!(
+            html,
+            "<rect x=\"{}\" y=\"{}\" width=\"1\" height=\"1\" fill=\"{}\"><title>{}</title></rect>",
+            index % HEATMAP_SIZE,
+            index / HEATMAP_SIZE,
+            hex(color),
+            count
+        );
+    }
+    html.push_str("</svg></div>\n");
+}
+
+fn histogram_table(html: &mut String, title: &str, histogram: &Histogram) {
+    let bins = histogram.bins();
+    let max = bins
+        .iter()
+        .map(|(_, count)| *count)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let _ = writeln!(html, "<div class=\"hist\"><h3>{}</h3><table>", title);
+    for (label, count) in bins {
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+            html_escape(&label),
+            count,
+            bar(count, max)
+        );
+    }
+    html.push_str("</table></div>\n");
+}
+
+fn lint_section(html: &mut String, stats: &DatasetStats, project: &ProjectConfig) {
+    html.push_str("<h2>标注问题</h2>\n<table>\n");
+    let counts = stats.issue_counts();
+    for kind in LintKind::ALL {
+        let hint = match kind {
+            LintKind::Tiny => format!("（宽或高小于 {} 像素）", TINY_BOX_PIXELS),
+            _ => String::new(),
+        };
+        let _ = writeln!(
+            html,
+            "<tr><th>{}{}</th><td>{}</td></tr>",
+            kind.label(),
+            hint,
+            counts.get(&kind).copied().unwrap_or(0)
+        );
+    }
+    html.push_str("</table>\n");
+
+    let unknown = lint::unknown_classes(&stats.class_boxes, project);
+    if !unknown.is_empty() {
+        let list: Vec<String> = unknown
+            .iter()
+            .map(|(class, boxes)| format!("{}（{} 个框）", class, boxes))
+            .collect();
+        let _ = writeln!(
+            html,
+            "<p>未在项目中命名的类别: {}</p>",
+            html_escape(&list.join("，"))
+        );
+    }
+
+    if stats.issues.is_empty() {
+        return;
+    }
+    html.push_str("<details><summary>问题列表</summary>\n<table>\n<tr><th>图片</th><th>框序号</th><th>类别</th><th>问题</th></tr>\n");
+    for issue in stats.issues.iter().take(MAX_LISTED_ISSUES) {
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{} {}</td><td>{}</td></tr>",
+            html_escape(&issue.image),
+            issue.box_index + 1,
+            issue.class,
+            html_escape(&project.class_name(issue.class)),
+            issue.kind.label()
+        );
+    }
+    html.push_str("</table>\n");
+    if stats.issues.len() > MAX_LISTED_ISSUES {
+        let _ = writeln!(
+            html,
+            "<p class=\"muted\">另有 {} 个问题未列出</p>",
+            stats.issues.len() - MAX_LISTED_ISSUES
+        );
+    }
+    html.push_str("</details>\n");
+}
+
+fn gallery_section(
+    html: &mut String,
+    images: &[PathBuf],
+    label_dir: &Path,
+    project: &ProjectConfig,
+) {
+    let layout = project.label_layout();
+    let samples = sample_images(images, label_dir, GALLERY_SIZE);
+    let _ = writeln!(html, "<h2>示例图片（{} 张）</h2>", samples.len());
+    html.push_str("<div class=\"gallery\">\n");
+    for path in samples {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let boxes = labels::read_label_file(&labels::label_path(label_dir, &path), &layout);
+        match thumbnail_svg(&path, &boxes, project) {
+            Ok(svg) => {
+                let _ = writeln!(
+                    html,
+                    "<figure>{}<figcaption>{} · {} 个框</figcaption></figure>",
+                    svg,
+                    html_escape(&name),
+                    boxes.len()
+                );
+            }
+            Err(e) => {
+                let _ = writeln!(
+                    html,
+                    "<figure class=\"muted\">{}: {}</figure>",
+                    html_escape(&name),
+                    html_escape(&e)
+                );
+            }
+        }
+    }
+    html.push_str("</div>\n");
+}
+
+/// 在有标签文件的图片中等间隔抽取示例
+fn sample_images(images: &[PathBuf], label_dir: &Path, count: usize) -> Vec<PathBuf> {
+    let labeled: Vec<&PathBuf> = images
+        .iter()
+        .filter(|path| fs::metadata(labels::label_path(label_dir, path)).is_ok_and(|m| m.len() > 0))
+        .collect();
+    if labeled.len() <= count {
+        return labeled.into_iter().cloned().collect();
+    }
+    (0..count)
+        .map(|i| labeled[i * labeled.len() / count].clone())
+        .collect()
+}
+
+/// 缩略图和框轮廓组成的 SVG，框按类别着色
+fn thumbnail_svg(
+    path: &Path,
+    boxes: &[BoundingBox],
+    project: &ProjectConfig,
+) -> Result<String, String> {
+    let image = image::open(path).map_err(|e| format!("图片加载失败: {}", e))?;
+    let thumbnail = image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE).to_rgb8();
+    let (width, height) = thumbnail.dimensions();
+    let mut jpeg = Vec::new();
+    JpegEncoder::new_with_quality(&mut jpeg, 80)
+        .encode_image(&thumbnail)
+        .map_err(|e| format!("编码缩略图失败: {}", e))?;
+
+    let mut svg = format!(
+        "<svg width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">\
+         <image width=\"{0}\" height=\"{1}\" href=\"data:image/jpeg;base64,{2}\"/>",
+        width,
+        height,
+        base64_encode(&jpeg)
+    );
+    for bbox in boxes {
+        let points: Vec<String> = bbox
+            .outline()
+            .iter()
+            .map(|[x, y]| format!("{:.1},{:.1}", x * width as f64, y * height as f64))
+            .collect();
+        let _ = write!(
+            svg,
+            "<polygon points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"2\"><title>{}</title></polygon>",
+            points.join(" "),
+            hex(project.class_color(bbox.class)),
+            html_escape(&format!(
+                "{} {}",
+                bbox.class,
+                project.class_name(bbox.class)
+            ))
+        );
+    }
+    svg.push_str("</svg>");
+    Ok(svg)
+}
+
+fn bar(count: usize, max: usize) -> String {
+    format!(
+        "<span class=\"bar\" style=\"width:{}px\"></span>",
+        count * 200 / max.max(1)
+    )
+}
+
+fn hex(color: egui::Color32) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
+}
@@ -0,0 +1,212 @@
+use chrono::Timelike;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::time::Instant;
+
+/// 标注会话日志，保存在标签目录下，每行一条 JSON 记录
+pub const SESSION_LOG_FILE_NAME: &str = "session_log.jsonl";
+/// 单次停留超过该秒数时按该值记录，避免离开电脑的时间计入标注耗时
+pub const MAX_VISIT_SECS: u64 = 600;
+
+/// 会话日志中的操作
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionAction {
+    Viewed,    // 查看后离开，没有修改标注
+    Edited,    // 停留期间修改并保存了标注
+    Completed, // 将图片标记为已完成
+}
+
+/// 会话日志的一条记录
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SessionEntry {
+    pub timestamp: u64,
+    pub image: String,
+    pub action: SessionAction,
+    #[serde(default)]
+    pub duration: u64, // 在该图片上停留的秒数，完成记录为 0
+    #[serde(default)]
+    pub boxes: usize, // 记录时图片中的框数量
+    #[serde(default)]
+    pub annotator: String,
+}
+
+/// 当前图片的停留，离开时写入日志
+#[derive(Clone)]
+pub struct ImageVisit {
+    pub image: String,
+    pub started: Instant,
+    pub edited: bool,
+}
+
+impl ImageVisit {
+    pub fn new(image: String) -> Self {
+        Self {
+            image,
+            started: Instant::now(),
+            edited: false,
+        }
+    }
+
+    /// 停留秒数，超过 MAX_VISIT_SECS 时截断
+    pub fn duration(&self) -> u64 {
+        self.started.elapsed().as_secs().min(MAX_VISIT_SECS)
+    }
+}
+
+/// 追加一条日志记录
+pub fn append_entry(label_dir: &Path, entry: &SessionEntry) -> Result<(), String> {
+    let line = serde_json::to_string(entry).map_err(|e| format!("序列化会话日志失败: {}", e))?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(label_dir.join(SESSION_LOG_FILE_NAME))
+        .map_err(|e| format!("打开会话日志失败: {}", e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("写入会话日志失败: {}", e))
+}
+
+/// 读取标签目录下的会话日志，无法解析的行忽略
+pub fn load_log(label_dir: &Path) -> Vec<SessionEntry> {
+    fs::read_to_string(label_dir.join(SESSION_LOG_FILE_NAME))
+        .map(|content| {
+            content
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 一天或一名标注员的工作量
+#[derive(Clone, Default)]
+pub struct WorkSummary {
+    pub completed: usize,     // 标记为完成的图片数
+    pub worked_images: usize, // 修改过或完成的图片数（去重）
+    pub boxes: usize,         // 这些图片最后一次记录时的框数量之和
+    pub seconds: u64,         // 停留时长之和
+}
+
+impl WorkSummary {
+    fn from_entries<'a>(entries: impl Iterator<Item = &'a SessionEntry>) -> Self {
+        let mut summary = WorkSummary::default();
+        let mut latest_boxes: BTreeMap<&str, usize> = BTreeMap::new();
+        for entry in entries {
+            summary.seconds += entry.duration;
+            match entry.action {
+                SessionAction::Viewed => {}
+                SessionAction::Edited => {
+                    latest_boxes.insert(&entry.image, entry.boxes);
+                }
+                SessionAction::Completed => {
+                    summary.completed += 1;
+                    latest_boxes.insert(&entry.image, entry.boxes);
+                }
+            }
+        }
+        summary.worked_images = latest_boxes.len();
+        summary.boxes = latest_boxes.values().sum();
+        summary
+    }
+
+    /// 每小时完成的图片数（按停留时长计）
+    pub fn images_per_hour(&self) -> Option<f64> {
+        (self.seconds > 0).then(|| self.completed as f64 * 3600.0 / self.seconds as f64)
+    }
+
+    /// 平均每张图片的停留秒数
+    pub fn seconds_per_image(&self) -> Option<f64> {
+        (self.worked_images > 0).then(|| self.seconds as f64 / self.worked_images as f64)
+    }
+
+    /// 平均每个框的停留秒数
+    pub fn seconds_per_box(&self) -> Option<f64> {
+        (self.boxes > 0).then(|| self.seconds as f64 / self.boxes as f64)
+    }
+}
+
+/// 从会话日志汇总的标注进度
+#[derive(Clone, Default)]
+pub struct ProgressReport {
+    pub total: WorkSummary,
+    pub days: BTreeMap<String, WorkSummary>, // 本地日期 -> 当天工作量
+    pub hours: [usize; 24],                  // 按本地时间的小时统计完成的图片数
+    pub annotators: BTreeMap<String, WorkSummary>,
+}
+
+impl ProgressReport {
+    /// 汇总日志，`annotator` 不为空时只统计该标注员
+    pub fn from_entries(entries: &[SessionEntry], annotator: Option<&str>) -> Self {
+        let entries: Vec<&SessionEntry> = entries
+            .iter()
+            .filter(|entry| annotator.is_none_or(|name| entry.annotator == name))
+            .collect();
+
+        let mut by_day: BTreeMap<String, Vec<&SessionEntry>> = BTreeMap::new();
+        let mut hours = [0; 24];
+        for entry in &entries {
+            let Some(time) = local_time(entry.timestamp) else {
+                continue;
+            };
+            by_day
+                .entry(time.format("%Y-%m-%d").to_string())
+                .or_default()
+                .push(entry);
+            if entry.action == SessionAction::Completed {
+                hours[time.hour() as usize] += 1;
+            }
+        }
+
+        let names: BTreeSet<&str> = entries.iter().map(|e| e.annotator.as_str()).collect();
+        let annotators = names
+            .into_iter()
+            .map(|name| {
+                let summary = WorkSummary::from_entries(
+                    entries.iter().copied().filter(|e| e.annotator == name),
+                );
+                (name.to_string(), summary)
+            })
+            .collect();
+
+        Self {
+            total: WorkSummary::from_entries(entries.iter().copied()),
+            days: by_day
+                .into_iter()
+                .map(|(day, entries)| (day, WorkSummary::from_entries(entries.into_iter())))
+                .collect(),
+            hours,
+            annotators,
+        }
+    }
+
+    /// 有完成记录的日子平均每天完成的图片数
+    pub fn images_per_day(&self) -> Option<f64> {
+        let active_days = self.days.values().filter(|day| day.completed > 0).count();
+        (active_days > 0).then(|| self.total.completed as f64 / active_days as f64)
+    }
+
+    /// 按当前速度完成剩余图片需要的（标注小时数，工作日数）
+    pub fn eta(&self, remaining: usize) -> Option<(f64, f64)> {
+        let per_hour = self.total.images_per_hour().filter(|rate| *rate > 0.0)?;
+        let per_day = self.images_per_day().filter(|rate| *rate > 0.0)?;
+        Some((remaining as f64 / per_hour, remaining as f64 / per_day))
+    }
+}
+
+fn local_time(secs: u64) -> Option<chrono::DateTime<chrono::Local>> {
+    chrono::DateTime::from_timestamp(secs as i64, 0).map(|time| time.with_timezone(&chrono::Local))
+}
+
+/// 格式化秒数，如 "1 小时 5 分"、"42 秒"
+pub fn format_duration(secs: f64) -> String {
+    let secs = secs.round() as u64;
+    if secs >= 3600 {
+        format!("{} 小时 {} 分", secs / 3600, secs % 3600 / 60)
+    } else if secs >= 60 {
+        format!("{} 分 {} 秒", secs / 60, secs % 60)
+    } else {
+        format!("{} 秒", secs)
+    }
+}
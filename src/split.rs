@@ -0,0 +1,407 @@
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::labels::{self, LabelLayout};
+use crate::project::ProjectConfig;
+
+/// 划分记录文件，保存在标签目录下，保证重复导出时已划分的图片不变
+pub const SPLIT_FILE_NAME: &str = "split.json";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Split {
+    Train,
+    Val,
+    Test,
+}
+
+impl Split {
+    pub const ALL: [Split; 3] = [Split::Train, Split::Val, Split::Test];
+
+    /// data.yaml 中的划分目录列表：训练和验证总是写入，测试集只在有图片时写入
+    pub fn yaml_dirs(used: &BTreeSet<Split>) -> Vec<(&'static str, String)> {
+        Split::ALL
+            .into_iter()
+            .filter(|s| *s != Split::Test || used.contains(s))
+            .map(|s| (s.name(), format!("images/{}", s.name())))
+            .collect()
+    }
+
+    /// 目录名，同时用于 data.yaml
+    pub fn name(&self) -> &'static str {
+        match self {
+            Split::Train => "train",
+            Split::Val => "val",
+            Split::Test => "test",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Split::Train => "训练集",
+            Split::Val => "验证集",
+            Split::Test => "测试集",
+        }
+    }
+}
+
+/// 划分设置，保存在项目配置中
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SplitConfig {
+    pub ratios: [f64; 3], // 训练/验证/测试比例，不要求和为 1
+    pub seed: u64,
+    pub stratify: bool, // 按类别分层，使每个类别在各划分中的比例接近设定比例
+    pub group_separator: String, // 按文件名中最后一个分隔符之前的前缀分组，为空时不分组
+}
+
+impl Default for SplitConfig {
+    fn default() -> Self {
+        Self {
+            ratios: [0.8, 0.1, 0.1],
+            seed: 42,
+            stratify: false,
+            group_separator: String::new(),
+        }
+    }
+}
+
+/// 图片文件名 -> 所属划分
+pub type SplitRecord = BTreeMap<String, Split>;
+
+pub fn load_record(label_dir: &Path) -> SplitRecord {
+    fs::read_to_string(label_dir.join(SPLIT_FILE_NAME))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_record(label_dir: &Path, record: &SplitRecord) -> Result<(), String> {
+    let content =
+        serde_json::to_string_pretty(record).map_err(|e| format!("序列化划分记录失败: {}", e))?;
+    fs::write(label_dir.join(SPLIT_FILE_NAME), content)
+        .map_err(|e| format!("保存划分记录失败: {}", e))
+}
+
+/// 分组键：文件名（不含扩展名）中最后一个分隔符之前的部分，如 `video01_000123` -> `video01`
+pub fn group_key(name: &str, separator: &str) -> String {
+    let stem = Path::new(name)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| name.to_string());
+    if separator.is_empty() {
+        return stem;
+    }
+    match stem.rfind(separator) {
+        Some(pos) => stem[..pos].to_string(),
+        None => stem,
+    }
+}
+
+/// 参与划分的图片及其包含的类别
+pub struct SplitInput {
+    pub name: String,
+    pub classes: BTreeSet<i32>,
+}
+
+/// 读取图片对应标签中的类别，作为划分输入
+pub fn collect_inputs(
+    images: &[PathBuf],
+    label_dir: &Path,
+    layout: &LabelLayout,
+) -> Vec<SplitInput> {
+    images
+        .iter()
+        .map(|path| SplitInput {
+            name: path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            classes: labels::read_label_file(&labels::label_path(label_dir, path), layout)
+                .iter()
+                .map(|b| b.class)
+                .collect(),
+        })
+        .collect()
+}
+
+/// 计算每张图片的划分。记录中已有的图片保持不变（同组的新图片跟随组内多数的记录），
+/// 其余分组按固定种子打乱后依次放入离目标比例差距最大的划分
+pub fn assign(inputs: &[SplitInput], config: &SplitConfig, record: &SplitRecord) -> SplitRecord {
+    let total_ratio: f64 = config.ratios.iter().map(|r| r.max(0.0)).sum();
+    let ratios: Vec<f64> = config
+        .ratios
+        .iter()
+        .map(|r| r.max(0.0) / total_ratio.max(f64::EPSILON))
+        .collect();
+
+    let mut groups: BTreeMap<String, Vec<&SplitInput>> = BTreeMap::new();
+    for input in inputs {
+        groups
+            .entry(group_key(&input.name, &config.group_separator))
+            .or_default()
+            .push(input);
+    }
+
+    // 分层时以组内最稀有的类别作为分层键，没有框的组单独作为一层
+    let mut class_frequency: HashMap<i32, usize> = HashMap::new();
+    for input in inputs {
+        for class in &input.classes {
+            *class_frequency.entry(*class).or_insert(0) += 1;
+        }
+    }
+    let stratum = |members: &[&SplitInput]| -> i32 {
+        if !config.stratify {
+            return 0;
+        }
+        members
+            .iter()
+            .flat_map(|m| m.classes.iter())
+            .min_by_key(|class| (class_frequency.get(class).copied().unwrap_or(0), **class))
+            .copied()
+            .unwrap_or(-1)
+    };
+
+    let mut assignment = SplitRecord::new();
+    let mut counts: HashMap<i32, [usize; 3]> = HashMap::new();
+    let mut pending = Vec::new();
+    for members in groups.into_values() {
+        let recorded: Vec<Split> = members
+            .iter()
+            .filter_map(|m| record.get(&m.name).copied())
+            .collect();
+        let Some(split) = Split::ALL
+            .into_iter()
+            .filter(|s| recorded.contains(s))
+            .max_by_key(|s| recorded.iter().filter(|r| *r == s).count())
+        else {
+            pending.push(members);
+            continue;
+        };
+        // 已有记录的图片保持原划分，组内新增的图片跟随多数
+        let count = counts.entry(stratum(&members)).or_default();
+        for member in members {
+            let member_split = record.get(&member.name).copied().unwrap_or(split);
+            count[member_split as usize] += 1;
+            assignment.insert(member.name.clone(), member_split);
+        }
+    }
+
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    pending.shuffle(&mut rng);
+    for members in pending {
+        let count = counts.entry(stratum(&members)).or_default();
+        let assigned = count.iter().sum::<usize>() + members.len();
+        let split = Split::ALL
+            .into_iter()
+            .max_by(|a, b| {
+                let deficit =
+                    |s: &Split| ratios[*s as usize] * assigned as f64 - count[*s as usize] as f64;
+                // 差距相同时优先训练集
+                deficit(a).total_cmp(&deficit(b)).then(b.cmp(a))
+            })
+            .unwrap_or(Split::Train);
+        count[split as usize] += members.len();
+        for member in members {
+            assignment.insert(member.name.clone(), split);
+        }
+    }
+    assignment
+}
+
+/// 划分结果统计：各划分的图片数量，以及每个类别出现在各划分中的图片数量
+#[derive(Clone, Default)]
+pub struct SplitSummary {
+    pub images: [usize; 3],
+    pub classes: BTreeMap<i32, [usize; 3]>,
+}
+
+pub fn summarize(inputs: &[SplitInput], assignment: &SplitRecord) -> SplitSummary {
+    let mut summary = SplitSummary::default();
+    for input in inputs {
+        let Some(split) = assignment.get(&input.name) else {
+            continue;
+        };
+        summary.images[*split as usize] += 1;
+        for class in &input.classes {
+            summary.classes.entry(*class).or_default()[*split as usize] += 1;
+        }
+    }
+    summary
+}
+
+/// Ultralytics 数据集配置。`splits` 为划分名和相对图片目录，`root` 为空时不写 `path`
+/// （Ultralytics 以 data.yaml 所在目录为根目录，用于压缩包）。
+/// 类别名需要从 0 开始连续编号，缺少名称的类别使用 `Class N`
+pub fn data_yaml(
+    project: &ProjectConfig,
+    root: Option<&Path>,
+    splits: &[(&str, String)],
+    max_class: Option<i32>,
+) -> String {
+    let mut yaml = String::new();
+    if let Some(root) = root {
+        let _ = writeln!(yaml, "path: {}", root.display());
+    }
+    for (name, dir) in splits {
+        let _ = writeln!(yaml, "{}: {}", name, dir);
+    }
+
+    // 所有类别的关键点数量一致时写出 kpt_shape 和 flip_idx
+    let schemas: Vec<_> = project
+        .keypoint_schemas
+        .values()
+        .filter(|s| !s.names.is_empty())
+        .collect();
+    if let Some(first) = schemas.first() {
+        let consistent = schemas.iter().all(|s| {
+            s.names.len() == first.names.len() && s.with_visibility == first.with_visibility
+        });
+        if consistent {
+            let dims = if first.with_visibility { 3 } else { 2 };
+            let _ = writeln!(yaml, "kpt_shape: [{}, {}]", first.names.len(), dims);
+            let flip_idx: Vec<String> = first.flip_index().iter().map(|i| i.to_string()).collect();
+            let _ = writeln!(yaml, "flip_idx: [{}]", flip_idx.join(", "));
+        }
+    }
+
+    let max_class = project
+        .class_names
+        .keys()
+        .copied()
+        .chain(max_class)
+        .max()
+        .unwrap_or(-1);
+    yaml.push_str("names:\n");
+    for class in 0..=max_class {
+        let _ = writeln!(
+            yaml,
+            "  {}: {}",
+            class,
+            yaml_string(&project.class_name(class))
+        );
+    }
+    yaml
+}
+
+/// YAML 1.1 中会被解析为布尔值或空值的纯量
+const YAML_KEYWORDS: [&str; 12] = [
+    "y", "n", "yes", "no", "true", "false", "on", "off", "null", "~", "nan", ".inf",
+];
+
+/// 名称中含有 YAML 特殊字符、以指示符或空白开头结尾、是关键字或数字时加引号，
+/// 避免类别名 `no`、`on` 等被解析为布尔值
+fn yaml_string(value: &str) -> String {
+    let lower = value.to_ascii_lowercase();
+    if value.is_empty()
+        || value.contains([':', '#', '\'', '"', '[', ']', '{', '}', ','])
+        || value.starts_with(['-', '?', '&', '*', '!', '|', '>', '%', '@', '`'])
+        || value.trim() != value
+        || YAML_KEYWORDS.contains(&lower.as_str())
+        || value.parse::<f64>().is_ok()
+    {
+        format!("'{}'", value.replace('\'', "''"))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inputs(names: &[&str]) -> Vec<SplitInput> {
+        names
+            .iter()
+            .map(|name| SplitInput {
+                name: name.to_string(),
+                classes: BTreeSet::new(),
+            })
+            .collect()
+    }
+
+    fn numbered(prefix: &str, groups: usize, per_group: usize) -> Vec<String> {
+        (0..groups)
+            .flat_map(|g| (0..per_group).map(move |i| format!("{prefix}{g:02}_{i:03}.jpg")))
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_gives_same_split() {
+        let names = numbered("video", 40, 1);
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        let inputs = inputs(&names);
+        let config = SplitConfig::default();
+        let first = assign(&inputs, &config, &SplitRecord::new());
+        assert_eq!(first, assign(&inputs, &config, &SplitRecord::new()));
+        assert_eq!(first.len(), inputs.len());
+
+        let other = SplitConfig {
+            seed: 7,
+            ..SplitConfig::default()
+        };
+        assert_ne!(first, assign(&inputs, &other, &SplitRecord::new()));
+    }
+
+    #[test]
+    fn groups_never_span_splits() {
+        let names = numbered("video", 20, 5);
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        let inputs = inputs(&names);
+        let config = SplitConfig {
+            group_separator: "_".to_string(),
+            ..SplitConfig::default()
+        };
+        let assignment = assign(&inputs, &config, &SplitRecord::new());
+        let mut group_splits: BTreeMap<String, BTreeSet<Split>> = BTreeMap::new();
+        for (name, split) in &assignment {
+            group_splits
+                .entry(group_key(name, "_"))
+                .or_default()
+                .insert(*split);
+        }
+        assert_eq!(group_splits.len(), 20);
+        assert!(group_splits.values().all(|splits| splits.len() == 1));
+        let used: BTreeSet<Split> = assignment.values().copied().collect();
+        assert!(used.len() > 1);
+    }
+
+    #[test]
+    fn recorded_assignments_are_kept() {
+        let config = SplitConfig {
+            group_separator: "_".to_string(),
+            ..SplitConfig::default()
+        };
+        let old = numbered("video", 10, 2);
+        let old: Vec<&str> = old.iter().map(String::as_str).collect();
+        // 记录中全部放在验证集，与比例无关
+        let record: SplitRecord = old.iter().map(|n| (n.to_string(), Split::Val)).collect();
+
+        let mut names = old.clone();
+        names.extend(["video03_999.jpg", "fresh00_000.jpg", "fresh01_000.jpg"]);
+        let assignment = assign(&inputs(&names), &config, &record);
+        for name in &old {
+            assert_eq!(assignment[*name], Split::Val);
+        }
+        // 已记录分组中的新图片跟随分组
+        assert_eq!(assignment["video03_999.jpg"], Split::Val);
+        assert_eq!(assignment.len(), names.len());
+    }
+
+    #[test]
+    fn yaml_string_quotes_keywords() {
+        for name in ["yes", "No", "ON", "off", "y", "N", "True", "FALSE", "null", "~", "1"] {
+            assert_eq!(yaml_string(name), format!("'{}'", name));
+        }
+        assert_eq!(yaml_string("it's"), "'it''s'");
+        assert_eq!(yaml_string("- dash"), "'- dash'");
+        assert_eq!(yaml_string("person"), "person");
+        assert_eq!(yaml_string("行人"), "行人");
+    }
+}
@@ -0,0 +1,298 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::labels::{self, LabelLayout};
+use crate::lint::{self, LintIssue, LintKind};
+use crate::models::{BoundingBox, ShapeKind};
+use crate::project::ProjectConfig;
+use crate::utils::csv_field;
+
+/// 框中心热力图的边长（格数）
+pub const HEATMAP_SIZE: usize = 32;
+
+/// 直方图，每个区间以下界表示，最后一个区间没有上界
+#[derive(Clone)]
+pub struct Histogram {
+    pub edges: Vec<f64>,
+    pub counts: Vec<usize>,
+    pub integer: bool, // 每个区间只包含一个整数，标签只显示下界
+}
+
+impl Histogram {
+    fn new(edges: Vec<f64>, integer: bool) -> Self {
+        Self {
+            counts: vec![0; edges.len()],
+            edges,
+            integer,
+        }
+    }
+
+    /// 框宽高（像素），按 2 的幂分段，与 COCO 的小/中/大目标划分对应
+    fn pixels() -> Self {
+        Self::new(
+            vec![0.0, 8.0, 16.0, 32.0, 64.0, 128.0, 256.0, 512.0, 1024.0],
+            false,
+        )
+    }
+
+    /// 宽高比（宽 / 高），按对数分段
+    fn aspect_ratios() -> Self {
+        Self::new(vec![0.0, 0.125, 0.25, 0.5, 1.0, 2.0, 4.0, 8.0], false)
+    }
+
+    /// 每张图片的框数量，20 个及以上合并
+    fn per_image() -> Self {
+        Self::new((0..=20).map(|n| n as f64).collect(), true)
+    }
+
+    fn add(&mut self, value: f64) {
+        let index = self
+            .edges
+            .iter()
+            .rposition(|edge| value >= *edge)
+            .unwrap_or(0);
+        self.counts[index] += 1;
+    }
+
+    pub fn label(&self, index: usize) -> String {
+        let low = self.edges[index];
+        match self.edges.get(index + 1) {
+            None => format!("≥{}", low),
+            Some(_) if self.integer => format!("{}", low),
+            Some(high) => format!("{}-{}", low, high),
+        }
+    }
+
+    /// （区间标签，数量）
+    pub fn bins(&self) -> Vec<(String, usize)> {
+        (0..self.counts.len())
+            .map(|i| (self.label(i), self.counts[i]))
+            .collect()
+    }
+}
+
+/// 从标签目录统计的数据集分布
+#[derive(Clone)]
+pub struct DatasetStats {
+    pub images: usize,
+    pub labeled_images: usize, // 至少有一个框的图片
+    pub boxes: usize,
+    pub widths: Histogram, // 外接水平框的像素宽度
+    pub heights: Histogram,
+    pub aspect_ratios: Histogram,
+    pub boxes_per_image: Histogram,
+    pub class_boxes: BTreeMap<i32, usize>,
+    pub class_images: BTreeMap<i32, usize>, // 包含该类别的图片数量
+    pub shape_counts: HashMap<ShapeKind, usize>,
+    pub heatmap: Vec<usize>, // 框中心位置，HEATMAP_SIZE x HEATMAP_SIZE，按行存储
+    pub failures: Vec<String>, // 读取图片尺寸失败的图片
+    pub issues: Vec<LintIssue>, // 退化、越界、过小或重复的框
+}
+
+impl Default for DatasetStats {
+    fn default() -> Self {
+        Self {
+            images: 0,
+            labeled_images: 0,
+            boxes: 0,
+            widths: Histogram::pixels(),
+            heights: Histogram::pixels(),
+            aspect_ratios: Histogram::aspect_ratios(),
+            boxes_per_image: Histogram::per_image(),
+            class_boxes: BTreeMap::new(),
+            class_images: BTreeMap::new(),
+            shape_counts: HashMap::new(),
+            heatmap: vec![0; HEATMAP_SIZE * HEATMAP_SIZE],
+            failures: Vec::new(),
+            issues: Vec::new(),
+        }
+    }
+}
+
+impl DatasetStats {
+    /// 读取一张图片的标签并计入统计。`read_sizes` 为假或读取图片尺寸失败时只统计类别和数量
+    pub fn add_image(
+        &mut self,
+        image_path: &Path,
+        label_dir: &Path,
+        layout: &LabelLayout,
+        read_sizes: bool,
+    ) {
+        let boxes = labels::read_label_file(&labels::label_path(label_dir, image_path), layout);
+        let size = if !read_sizes {
+            None
+        } else {
+            match image::image_dimensions(image_path) {
+                Ok(size) => Some(size),
+                Err(e) => {
+                    if !boxes.is_empty() {
+                        self.failures
+                            .push(format!("读取图片尺寸失败 {}: {}", image_path.display(), e));
+                    }
+                    None
+                }
+            }
+        };
+        let image = image_path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        for (box_index, kind) in lint::check_boxes(&boxes, size) {
+            self.issues.push(LintIssue {
+                image: image.clone(),
+                box_index,
+                class: boxes[box_index].class,
+                kind,
+            });
+        }
+        self.add_boxes(&boxes, size);
+    }
+
+    /// 各类问题的数量
+    pub fn issue_counts(&self) -> BTreeMap<LintKind, usize> {
+        let mut counts = BTreeMap::new();
+        for issue in &self.issues {
+            *counts.entry(issue.kind).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// 计入一张图片的框，`size` 为图片像素尺寸
+    pub fn add_boxes(&mut self, boxes: &[BoundingBox], size: Option<(u32, u32)>) {
+        self.images += 1;
+        self.boxes += boxes.len();
+        self.boxes_per_image.add(boxes.len() as f64);
+        if !boxes.is_empty() {
+            self.labeled_images += 1;
+        }
+
+        let mut classes: Vec<i32> = boxes.iter().map(|b| b.class).collect();
+        classes.sort_unstable();
+        classes.dedup();
+        for class in classes {
+            *self.class_images.entry(class).or_insert(0) += 1;
+        }
+
+        for bbox in boxes {
+            *self.class_boxes.entry(bbox.class).or_insert(0) += 1;
+            *self.shape_counts.entry(bbox.kind()).or_insert(0) += 1;
+            let cell = |v: f64| ((v * HEATMAP_SIZE as f64) as usize).min(HEATMAP_SIZE - 1);
+            let (col, row) = (cell(bbox.x.clamp(0.0, 1.0)), cell(bbox.y.clamp(0.0, 1.0)));
+            self.heatmap[row * HEATMAP_SIZE + col] += 1;
+
+            if let Some((width, height)) = size {
+                let box_width = bbox.width * width as f64;
+                let box_height = bbox.height * height as f64;
+                self.widths.add(box_width);
+                self.heights.add(box_height);
+                if box_height > 0.0 {
+                    self.aspect_ratios.add(box_width / box_height);
+                }
+            }
+        }
+    }
+
+    /// 导出为 CSV：每行一个（统计项，区间/类别，数量）
+    pub fn to_csv(&self, project: &ProjectConfig) -> String {
+        let mut csv = String::from("section,bin,count\n");
+        for (section, value) in [
+            ("images", self.images),
+            ("labeled_images", self.labeled_images),
+            ("boxes", self.boxes),
+        ] {
+            let _ = writeln!(csv, "summary,{},{}", section, value);
+        }
+        for (section, histogram) in [
+            ("box_width_px", &self.widths),
+            ("box_height_px", &self.heights),
+            ("aspect_ratio", &self.aspect_ratios),
+            ("boxes_per_image", &self.boxes_per_image),
+        ] {
+            for (label, count) in histogram.bins() {
+                let _ = writeln!(csv, "{},{},{}", section, csv_field(&label), count);
+            }
+        }
+        for (section, counts) in [
+            ("class_boxes", &self.class_boxes),
+            ("class_images", &self.class_images),
+        ] {
+            for (class, count) in counts {
+                let name = format!("{} {}", class, project.class_name(*class));
+                let _ = writeln!(csv, "{},{},{}", section, csv_field(&name), count);
+            }
+        }
+        for kind in ShapeKind::ALL {
+            let count = self.shape_counts.get(&kind).copied().unwrap_or(0);
+            let _ = writeln!(csv, "shape,{},{}", kind.label(), count);
+        }
+        let issue_counts = self.issue_counts();
+        for kind in LintKind::ALL {
+            let count = issue_counts.get(&kind).copied().unwrap_or(0);
+            let _ = writeln!(csv, "lint,{},{}", kind.label(), count);
+        }
+        // 热力图按"列,行"（从左上角开始）
+        for (index, count) in self.heatmap.iter().enumerate() {
+            let cell = format!("{},{}", index % HEATMAP_SIZE, index / HEATMAP_SIZE);
+            let _ = writeln!(csv, "center_heatmap,{},{}", csv_field(&cell), count);
+        }
+        csv
+    }
+}
+
+/// 统计一组图片
+pub fn compute(images: &[PathBuf], label_dir: &Path, layout: &LabelLayout) -> DatasetStats {
+    let mut stats = DatasetStats::default();
+    for image in images {
+        stats.add_image(image, label_dir, layout, true);
+    }
+    stats
+}
+
+/// 后台统计的进度，主线程每帧读取
+#[derive(Default)]
+pub struct StatsProgress {
+    pub total: usize,
+    pub processed: usize,
+    pub result: Option<DatasetStats>,
+    pub cancelled: bool,
+    pub finished: bool,
+}
+
+pub type SharedStatsProgress = Arc<Mutex<StatsProgress>>;
+
+/// 在后台线程中统计。`read_sizes` 为真时读取每张图片的尺寸，图片较多时耗时较长
+pub fn spawn_stats_job(
+    images: Vec<PathBuf>,
+    label_dir: PathBuf,
+    layout: LabelLayout,
+    read_sizes: bool,
+) -> SharedStatsProgress {
+    let progress = Arc::new(Mutex::new(StatsProgress {
+        total: images.len(),
+        ..Default::default()
+    }));
+    let shared = progress.clone();
+
+    std::thread::spawn(move || {
+        let mut stats = DatasetStats::default();
+        for image in &images {
+            if shared.lock().map_or(true, |p| p.cancelled) {
+                break;
+            }
+            stats.add_image(image, &label_dir, &layout, read_sizes);
+            if let Ok(mut p) = shared.lock() {
+                p.processed += 1;
+            }
+        }
+        if let Ok(mut p) = shared.lock() {
+            if !p.cancelled {
+                p.result = Some(stats);
+            }
+            p.finished = true;
+        }
+    });
+
+    progress
+}
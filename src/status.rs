@@ -0,0 +1,59 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, ErrorKind};
+use std::path::Path;
+
+use crate::models::{ImageRecord, ImageStatus};
+
+/// 图片状态记录文件，保存在标签目录下
+pub const STATUS_FILE_NAME: &str = "image_status.json";
+/// 旧版本的修改记录文件，每行一个已修改的图片文件名
+pub const LEGACY_RECORD_FILE_NAME: &str = "modified_records.txt";
+
+/// 读取标签目录下的图片状态记录。
+/// 若只存在旧版 `modified_records.txt`，其中的图片迁移为"已标注"状态。
+/// 记录文件存在但无法读取或解析时返回错误，调用方不应再用空记录覆盖它
+pub fn load_records(label_dir: &Path) -> Result<HashMap<String, ImageRecord>, String> {
+    let status_path = label_dir.join(STATUS_FILE_NAME);
+    match fs::read_to_string(&status_path) {
+        Ok(content) => {
+            return serde_json::from_str(&content)
+                .map_err(|e| format!("解析 {} 失败: {}", status_path.display(), e));
+        }
+        Err(e) if e.kind() != ErrorKind::NotFound => {
+            return Err(format!("读取 {} 失败: {}", status_path.display(), e));
+        }
+        Err(_) => {}
+    }
+
+    let mut records = HashMap::new();
+    if let Ok(file) = File::open(label_dir.join(LEGACY_RECORD_FILE_NAME)) {
+        let reader = BufReader::new(file);
+        for filename in reader.lines().map_while(Result::ok) {
+            let filename = filename.trim();
+            if filename.is_empty() {
+                continue;
+            }
+            records.insert(
+                filename.to_string(),
+                ImageRecord {
+                    status: ImageStatus::Labeled,
+                    ..Default::default()
+                },
+            );
+        }
+    }
+    Ok(records)
+}
+
+/// 保存图片状态记录，未开始且没有备注的图片不写入文件
+pub fn save_records(label_dir: &Path, records: &HashMap<String, ImageRecord>) -> Result<(), String> {
+    let sorted: BTreeMap<&String, &ImageRecord> = records
+        .iter()
+        .filter(|(_, record)| record.status != ImageStatus::Todo || !record.note.is_empty())
+        .collect();
+    let content =
+        serde_json::to_string_pretty(&sorted).map_err(|e| format!("序列化状态记录失败: {}", e))?;
+    fs::write(label_dir.join(STATUS_FILE_NAME), content)
+        .map_err(|e| format!("保存状态记录失败: {}", e))
+}
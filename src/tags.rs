@@ -0,0 +1,94 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use crate::project::TagDefinition;
+use crate::utils::csv_field;
+
+/// 图片标签文件，保存在标签目录下
+pub const TAGS_FILE_NAME: &str = "image_tags.json";
+
+/// 图片文件名 -> 标签名称
+pub type ImageTags = BTreeMap<String, BTreeSet<String>>;
+
+/// 读取标签目录下的图片标签，文件不存在或解析失败时返回空表
+pub fn load_tags(label_dir: &Path) -> ImageTags {
+    fs::read_to_string(label_dir.join(TAGS_FILE_NAME))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// 保存图片标签，没有任何标签的图片不写入文件
+pub fn save_tags(label_dir: &Path, tags: &ImageTags) -> Result<(), String> {
+    let non_empty: BTreeMap<&String, &BTreeSet<String>> =
+        tags.iter().filter(|(_, set)| !set.is_empty()).collect();
+    let content = serde_json::to_string_pretty(&non_empty)
+        .map_err(|e| format!("序列化图片标签失败: {}", e))?;
+    fs::write(label_dir.join(TAGS_FILE_NAME), content)
+        .map_err(|e| format!("保存图片标签失败: {}", e))
+}
+
+/// 切换一个标签，返回切换后是否带有该标签。
+/// 同一分组内的标签互斥（如 白天/夜晚），打上新标签时移除同组的其他标签
+pub fn toggle_tag(
+    tags: &mut BTreeSet<String>,
+    definitions: &[TagDefinition],
+    index: usize,
+) -> bool {
+    let Some(definition) = definitions.get(index) else {
+        return false;
+    };
+    if tags.remove(&definition.name) {
+        return false;
+    }
+    if !definition.group.is_empty() {
+        for other in definitions.iter().filter(|d| d.group == definition.group) {
+            tags.remove(&other.name);
+        }
+    }
+    tags.insert(definition.name.clone());
+    true
+}
+
+/// 各标签在给定图片中的数量
+pub fn tag_counts(tags: &ImageTags, images: &[String]) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    for set in images.iter().filter_map(|name| tags.get(name)) {
+        for tag in set {
+            *counts.entry(tag.clone()).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+/// CSV 导出：每张图片一行，每个定义的标签一列（1 表示带有该标签）
+pub fn to_csv(tags: &ImageTags, images: &[String], definitions: &[TagDefinition]) -> String {
+    let mut csv = String::from("image");
+    for definition in definitions {
+        csv.push(',');
+        csv.push_str(&csv_field(&definition.name));
+    }
+    csv.push('\n');
+    for name in images {
+        csv.push_str(&csv_field(name));
+        let set = tags.get(name);
+        for definition in definitions {
+            let has = set.is_some_and(|s| s.contains(&definition.name));
+            let _ = write!(csv, ",{}", u8::from(has));
+        }
+        csv.push('\n');
+    }
+    csv
+}
+
+/// JSON 导出：图片文件名 -> 标签列表，包含没有标签的图片
+pub fn to_json(tags: &ImageTags, images: &[String]) -> Result<String, String> {
+    let empty = BTreeSet::new();
+    let export: BTreeMap<&String, &BTreeSet<String>> = images
+        .iter()
+        .map(|name| (name, tags.get(name).unwrap_or(&empty)))
+        .collect();
+    serde_json::to_string_pretty(&export).map_err(|e| format!("序列化图片标签失败: {}", e))
+}
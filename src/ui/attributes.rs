@@ -0,0 +1,252 @@
+use eframe::egui;
+use std::collections::BTreeSet;
+
+use crate::app::AnnotationApp;
+use crate::models::AttributeValue;
+use crate::project::{AttributeDefinition, AttributeKind};
+
+/// 选中框的属性检查器：按项目中的属性定义编辑，文本属性在输入框失去焦点时保存
+pub fn attribute_inspector(app: &mut AnnotationApp, ui: &mut egui::Ui) {
+    let Some(index) = app.selected_box else {
+        ui.small("选中边界框后可编辑属性");
+        return;
+    };
+    let Some(bbox) = app.bounding_boxes.get(index) else {
+        return;
+    };
+    let definitions: Vec<AttributeDefinition> = app
+        .project
+        .attributes_for(bbox.class)
+        .into_iter()
+        .cloned()
+        .collect();
+    let mut attributes = bbox.attributes.clone();
+    let mut changed = false;
+    let mut commit = false;
+
+    if definitions.is_empty() {
+        ui.small("该类别没有定义属性");
+    }
+    egui::Grid::new("attribute_inspector").show(ui, |ui| {
+        for definition in &definitions {
+            ui.label(&definition.name);
+            let before = attributes
+                .get(&definition.name)
+                .map(|value| coerce(definition, value))
+                .unwrap_or_else(|| definition.default_value());
+            let mut value = before.clone();
+            match &mut value {
+                AttributeValue::Bool(b) => {
+                    ui.checkbox(b, "");
+                }
+                AttributeValue::Int(i) => {
+                    ui.add(egui::DragValue::new(i));
+                }
+                AttributeValue::Text(text) if definition.kind == AttributeKind::Choice => {
+                    egui::ComboBox::from_id_salt(("attribute_choice", &definition.name))
+                        .selected_text(text.as_str())
+                        .show_ui(ui, |ui| {
+                            for option in &definition.options {
+                                ui.selectable_value(text, option.clone(), option);
+                            }
+                        });
+                }
+                AttributeValue::Text(text) => {
+                    commit |= ui.text_edit_singleline(text).lost_focus();
+                }
+            }
+            if value != before {
+                changed = true;
+                commit |= definition.kind != AttributeKind::Text;
+                attributes.insert(definition.name.clone(), value);
+            }
+            ui.end_row();
+        }
+    });
+
+    // 文件中存在但项目中未定义的属性只读显示
+    let known: BTreeSet<&str> = definitions.iter().map(|d| d.name.as_str()).collect();
+    let mut remove = None;
+    for (name, value) in attributes
+        .iter()
+        .filter(|(name, _)| !known.contains(name.as_str()))
+    {
+        ui.horizontal(|ui| {
+            ui.label(format!("{} = {}（未定义）", name, value.display()));
+            if ui.small_button("删除").clicked() {
+                remove = Some(name.clone());
+            }
+        });
+    }
+    if let Some(name) = remove {
+        attributes.remove(&name);
+        changed = true;
+        commit = true;
+    }
+
+    if !attributes.is_empty() && ui.small_button("清除全部属性").clicked() {
+        attributes.clear();
+        changed = true;
+        commit = true;
+    }
+
+    if changed {
+        app.bounding_boxes[index].attributes = attributes;
+    }
+    if commit {
+        app.save_annotations();
+        app.show_status("已保存框属性");
+    }
+}
+
+/// 将文件中读取的值转换为定义的类型
+fn coerce(definition: &AttributeDefinition, value: &AttributeValue) -> AttributeValue {
+    match definition.kind {
+        AttributeKind::Bool => AttributeValue::Bool(value.as_flag()),
+        AttributeKind::Int => AttributeValue::Int(match value {
+            AttributeValue::Bool(b) => *b as i64,
+            AttributeValue::Int(i) => *i,
+            AttributeValue::Text(s) => s.trim().parse().unwrap_or(0),
+        }),
+        AttributeKind::Choice | AttributeKind::Text => AttributeValue::Text(value.display()),
+    }
+}
+
+/// 框属性定义窗口：所有类别共用的属性和各类别特有的属性
+pub fn attribute_definitions_window(app: &mut AnnotationApp, ctx: &egui::Context) {
+    if !app.show_attribute_dialog {
+        return;
+    }
+
+    let mut open = true;
+    let mut changed = false;
+    egui::Window::new("框属性定义")
+        .open(&mut open)
+        .collapsible(false)
+        .default_width(380.0)
+        .show(ctx, |ui| {
+            ui.label("所有类别共用:");
+            changed |= definition_list(ui, "common", &mut app.project.common_attributes);
+            let classes: Vec<i32> = app.project.class_attributes.keys().copied().collect();
+            for class in classes {
+                ui.label(format!(
+                    "类别 {} ({}):",
+                    class,
+                    app.project.class_name(class)
+                ));
+                if let Some(list) = app.project.class_attributes.get_mut(&class) {
+                    changed |= definition_list(ui, &class.to_string(), list);
+                }
+            }
+            app.project
+                .class_attributes
+                .retain(|_, list| !list.is_empty());
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("添加到:");
+                let target = app.attribute_target;
+                let text = |class: Option<i32>| match class {
+                    None => "所有类别".to_string(),
+                    Some(class) => format!("{} ({})", class, app.project.class_name(class)),
+                };
+                let classes: BTreeSet<i32> = app
+                    .project
+                    .class_names
+                    .keys()
+                    .copied()
+                    .chain([app.selected_class])
+                    .collect();
+                egui::ComboBox::from_id_salt("attribute_target")
+                    .selected_text(text(target))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut app.attribute_target, None, text(None));
+                        for class in classes {
+                            ui.selectable_value(
+                                &mut app.attribute_target,
+                                Some(class),
+                                text(Some(class)),
+                            );
+                        }
+                    });
+            });
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut app.new_attribute.name)
+                        .hint_text("属性名")
+                        .desired_width(100.0),
+                );
+                egui::ComboBox::from_id_salt("attribute_kind")
+                    .selected_text(app.new_attribute.kind.label())
+                    .show_ui(ui, |ui| {
+                        for kind in AttributeKind::ALL {
+                            ui.selectable_value(&mut app.new_attribute.kind, kind, kind.label());
+                        }
+                    });
+                if app.new_attribute.kind == AttributeKind::Choice {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut app.new_attribute_options)
+                            .hint_text("选项，逗号分隔")
+                            .desired_width(120.0),
+                    );
+                }
+            });
+            ui.horizontal(|ui| {
+                if ui.button("添加").clicked() {
+                    match app.add_attribute_definition() {
+                        Ok(()) => changed = true,
+                        Err(e) => app.show_status(&e),
+                    }
+                }
+                if ui
+                    .button("添加 VOC 常用属性")
+                    .on_hover_text(
+                        "occluded / truncated / difficult / track_id，添加为所有类别共用",
+                    )
+                    .clicked()
+                {
+                    for preset in AttributeDefinition::voc_presets() {
+                        if !app
+                            .project
+                            .common_attributes
+                            .iter()
+                            .any(|d| d.name == preset.name)
+                        {
+                            app.project.common_attributes.push(preset);
+                        }
+                    }
+                    changed = true;
+                }
+            });
+            ui.small("属性保存在标签文件旁的 .attrs.json 中，YOLO 标签文件本身不变");
+        });
+
+    if changed {
+        app.save_project();
+    }
+    if !open {
+        app.show_attribute_dialog = false;
+    }
+}
+
+/// 显示一组属性定义，返回是否删除了其中的定义
+fn definition_list(ui: &mut egui::Ui, id: &str, list: &mut Vec<AttributeDefinition>) -> bool {
+    let mut remove = None;
+    egui::Grid::new(("attribute_definitions", id))
+        .striped(true)
+        .show(ui, |ui| {
+            for (index, definition) in list.iter().enumerate() {
+                ui.label(&definition.name);
+                ui.label(definition.kind.label());
+                ui.label(definition.options.join(", "));
+                if ui.small_button("删除").clicked() {
+                    remove = Some(index);
+                }
+                ui.end_row();
+            }
+        });
+    if let Some(index) = remove {
+        list.remove(index);
+    }
+    remove.is_some()
+}
@@ -0,0 +1,155 @@
+use eframe::egui;
+
+use crate::app::AnnotationApp;
+
+/// 自动标注窗口：模型设置、类别映射以及单张/批量标注
+pub fn auto_label_window(app: &mut AnnotationApp, ctx: &egui::Context) {
+    app.poll_auto_label();
+    if app.auto_label_job.is_some() {
+        ctx.request_repaint();
+    }
+
+    if !app.show_auto_label_dialog {
+        return;
+    }
+
+    let mut open = true;
+    egui::Window::new("自动标注")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            let mut reload = false;
+            let mut changed = false;
+
+            ui.horizontal(|ui| {
+                ui.label("模型:");
+                let model = &app.project.auto_label.model_path;
+                ui.label(if model.is_empty() { "未选择" } else { model });
+                if ui.button("选择模型").clicked()
+                    && let Some(path) = rfd::FileDialog::new()
+                        .add_filter("ONNX", &["onnx"])
+                        .pick_file()
+                    {
+                        app.project.auto_label.model_path = path.display().to_string();
+                        reload = true;
+                    }
+            });
+
+            egui::Grid::new("auto_label_settings").show(ui, |ui| {
+                ui.label("输入尺寸:");
+                reload |= ui
+                    .add(
+                        egui::DragValue::new(&mut app.project.auto_label.input_size)
+                            .range(32..=2048)
+                            .speed(32),
+                    )
+                    .changed();
+                ui.end_row();
+
+                ui.label("置信度阈值:");
+                changed |= ui
+                    .add(egui::Slider::new(
+                        &mut app.project.auto_label.conf_threshold,
+                        0.0..=1.0,
+                    ))
+                    .changed();
+                ui.end_row();
+
+                ui.label("NMS IoU 阈值:");
+                changed |= ui
+                    .add(egui::Slider::new(
+                        &mut app.project.auto_label.iou_threshold,
+                        0.0..=1.0,
+                    ))
+                    .changed();
+                ui.end_row();
+            });
+
+            ui.separator();
+            ui.label("类别映射（模型类别 -> 项目类别）");
+            if let Some(detector) = &app.detector
+                && !detector.class_names.is_empty() {
+                    ui.label(format!(
+                        "模型类别: {}",
+                        detector
+                            .class_names
+                            .iter()
+                            .enumerate()
+                            .map(|(i, name)| format!("{}:{}", i, name))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ));
+                }
+            ui.small("未设置映射时按与项目类别同名匹配，都没有时按序号一一对应；设置后未映射的类别会被丢弃");
+
+            let mut remove = None;
+            let mut class_map: Vec<(usize, i32)> = app
+                .project
+                .auto_label
+                .class_map
+                .iter()
+                .map(|(k, v)| (*k, *v))
+                .collect();
+            for (index, (model_class, class)) in class_map.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    changed |= ui.add(egui::DragValue::new(model_class)).changed();
+                    ui.label("->");
+                    changed |= ui.add(egui::DragValue::new(class)).changed();
+                    ui.label(app.project.class_name(*class));
+                    if ui.small_button("删除").clicked() {
+                        remove = Some(index);
+                    }
+                });
+            }
+            if let Some(index) = remove {
+                class_map.remove(index);
+                changed = true;
+            }
+            if ui.small_button("添加映射").clicked() {
+                let next = class_map.iter().map(|(k, _)| k + 1).max().unwrap_or(0);
+                class_map.push((next, next as i32));
+                changed = true;
+            }
+            app.project.auto_label.class_map = class_map.into_iter().collect();
+
+            if reload {
+                app.detector = None;
+            }
+            if reload || changed {
+                app.save_project();
+            }
+
+            ui.separator();
+            if let Some(job) = app.auto_label_job.clone() {
+                let (processed, total, boxes) = job
+                    .lock()
+                    .map(|p| (p.processed, p.total, p.boxes))
+                    .unwrap_or_default();
+                ui.add(
+                    egui::ProgressBar::new(processed as f32 / total.max(1) as f32)
+                        .text(format!("{} / {}，共 {} 个框", processed, total, boxes)),
+                );
+                if ui.button("取消").clicked() {
+                    app.cancel_auto_label();
+                }
+            } else {
+                ui.horizontal(|ui| {
+                    if ui.button("标注当前图片").clicked() {
+                        app.auto_label_current();
+                    }
+                    if ui
+                        .button("标注整个文件夹")
+                        .on_hover_text("只处理\"未开始\"和\"已预标注\"的图片，人工确认过的框会保留")
+                        .clicked()
+                    {
+                        app.auto_label_folder();
+                    }
+                });
+            }
+        });
+
+    if !open {
+        app.show_auto_label_dialog = false;
+    }
+}
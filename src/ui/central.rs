@@ -1,35 +1,14 @@
 use eframe::egui;
 
 use crate::app::AnnotationApp;
-use crate::models::BoundingBox;
+use crate::diff::DiffKind;
+use crate::keymap::Action;
+use crate::models::{BoundingBox, Keypoint, Shape};
+use crate::project::AnnotationMode;
 
 pub fn central_panel(app: &mut AnnotationApp, ctx: &egui::Context) {
     egui::CentralPanel::default().show(ctx, |ui| {
-        if ui.input(|i| i.key_pressed(egui::Key::W) || i.key_pressed(egui::Key::A)) {
-            app.switch_image(false, false);
-        }
-        if ui.input(|i| i.key_pressed(egui::Key::S) || i.key_pressed(egui::Key::D)) {
-            app.switch_image(true, false);
-        }
-        if ui.input(|i| i.key_pressed(egui::Key::Space)) {
-            app.switch_image(false, true);
-        }
-        if ui.input(|i| i.key_pressed(egui::Key::B)) {
-            app.go_back();
-        }
-        if ui.input(|i| i.key_pressed(egui::Key::N)) {
-            app.switch_to_next_unmodified();
-        }
-        if ui.input(|i| i.key_pressed(egui::Key::E)) {
-            app.is_drawing = !app.is_drawing;
-            app.drawing_start = None;
-            app.selected_box = None;
-            app.show_status(if app.is_drawing {
-                "已进入绘制模式"
-            } else {
-                "已退出绘制模式"
-            });
-        }
+        handle_shortcuts(app, ctx);
 
         if let Some(image) = &app.current_image {
             let available_size = ui.available_size();
@@ -60,16 +39,9 @@ pub fn central_panel(app: &mut AnnotationApp, ctx: &egui::Context) {
             let offset_x = image_rect.min.x + (available_size.x - displayed_size.x) / 2.0;
             let offset_y = image_rect.min.y + (available_size.y - displayed_size.y) / 2.0;
 
-            if app.is_drawing {
-                // 监听数字键输入
-                for key_num in 0..10 {
-                    let key = egui::Key::from_name(&format!("{}", key_num)).unwrap();
-                    if ui.input(|i| i.key_pressed(key)) {
-                        app.selected_class = key_num as i32 - 1;
-                        app.show_status(&format!("已切换到类别 {}", &app.selected_class));
-                    }
-                }
-
+            if app.is_drawing && app.project.annotation_mode == AnnotationMode::Segment {
+                polygon_tool(app, ui, offset_x, offset_y, displayed_size);
+            } else if app.is_drawing {
                 if let Some(pointer) = ui.input(|i| i.pointer.hover_pos()) {
                     if ui.input(|i| i.pointer.primary_pressed()) {
                         app.drawing_start = Some(pointer);
@@ -90,21 +62,47 @@ pub fn central_panel(app: &mut AnnotationApp, ctx: &egui::Context) {
                         ui.painter().rect_stroke(
                             rect,
                             0.0,
-                            egui::Stroke::new(2.0, egui::Color32::YELLOW),
+                            egui::Stroke::new(2.0, app.class_color(app.selected_class)),
                             egui::StrokeKind::Middle,
                         );
 
                         if ui.input(|i| i.pointer.primary_released()) {
                             if min_x < max_x && min_y < max_y {
-                                app.bounding_boxes.push(BoundingBox {
+                                let mut bbox = BoundingBox {
                                     class: app.selected_class,
                                     x: ((min_x + max_x) / 2.0) as f64,
                                     y: ((min_y + max_y) / 2.0) as f64,
                                     width: (max_x - min_x) as f64,
                                     height: (max_y - min_y) as f64,
-                                });
+                                    confidence: None,
+                                    shape: Shape::Rect,
+                                    keypoints: Vec::new(),
+                                    attributes: Default::default(),
+                                };
+                                // 旋转框模式下新框以水平放置的旋转框开始，之后可拖动旋转手柄
+                                if app.project.annotation_mode == AnnotationMode::Obb {
+                                    bbox.make_oriented();
+                                }
+                                let keypoint_count = app
+                                    .keypoint_schema(bbox.class)
+                                    .map_or(0, |schema| schema.names.len());
+                                app.bounding_boxes.push(bbox);
                                 app.save_annotations();
-                                app.show_status("已添加新边界框");
+                                if app.project.annotation_mode == AnnotationMode::Pose
+                                    && keypoint_count > 0
+                                {
+                                    // 关键点模式下画完框即选中它，依次单击放置关键点
+                                    let index = app.bounding_boxes.len() - 1;
+                                    app.bounding_boxes[index]
+                                        .keypoints
+                                        .resize(keypoint_count, Keypoint::default());
+                                    app.is_drawing = false;
+                                    app.selected_box = Some(index);
+                                    app.active_keypoint = Some(0);
+                                    app.show_status("已添加新边界框，请依次单击放置关键点");
+                                } else {
+                                    app.show_status("已添加新边界框");
+                                }
                             }
                             app.drawing_start = None;
                         }
@@ -113,36 +111,149 @@ pub fn central_panel(app: &mut AnnotationApp, ctx: &egui::Context) {
             } else {
                 if let Some(pointer) = ui.input(|i| i.pointer.hover_pos()) {
                     let mut hovered_box = None;
+                    let px = ((pointer.x - offset_x) / displayed_size.x) as f64;
+                    let py = ((pointer.y - offset_y) / displayed_size.y) as f64;
 
                     for (i, bbox) in app.bounding_boxes.iter().enumerate().rev() {
-                        let box_width = bbox.width as f32 * displayed_size.x;
-                        let box_height = bbox.height as f32 * displayed_size.y;
-                        let center_x = offset_x + (bbox.x as f32 * displayed_size.x);
-                        let center_y = offset_y + (bbox.y as f32 * displayed_size.y);
-
-                        let rect = egui::Rect::from_min_size(
-                            egui::pos2(center_x - box_width / 2.0, center_y - box_height / 2.0),
-                            egui::vec2(box_width, box_height),
-                        );
-
-                        if rect.contains(pointer) {
+                        if !app.is_box_visible(bbox) {
+                            continue;
+                        }
+                        if bbox.contains(px, py) {
                             hovered_box = Some(i);
                             break;
                         }
                     }
 
-                    if ui.input(|i| i.pointer.primary_clicked()) {
-                        if app.selected_box != hovered_box {
-                            app.selected_box = hovered_box;
-                            if let Some(idx) = hovered_box {
-                                app.show_status(&format!("已选中边界框 {}", idx));
-                            } else {
-                                app.show_status("取消选中");
-                            }
+                    // 选中多边形的顶点和边（屏幕距离），落在上面时视为仍指向该多边形
+                    let (vertex_under_pointer, edge_under_pointer) =
+                        polygon_hit(app, pointer, offset_x, offset_y, displayed_size);
+                    let keypoint_under_pointer =
+                        keypoint_hit(app, pointer, offset_x, offset_y, displayed_size);
+                    if vertex_under_pointer.is_some()
+                        || edge_under_pointer.is_some()
+                        || keypoint_under_pointer.is_some()
+                    {
+                        hovered_box = app.selected_box;
+                    }
+                    let alt = ui.input(|i| i.modifiers.alt);
+
+                    // 按下旋转手柄时进入旋转状态，此时不移动也不改变选中
+                    if ui.input(|i| i.pointer.primary_pressed()) {
+                        app.rotating_box = app
+                            .selected_box
+                            .and_then(|idx| app.bounding_boxes.get(idx))
+                            .filter(|bbox| shows_rotate_handle(app, bbox))
+                            .is_some_and(|bbox| {
+                                let (_, handle) =
+                                    rotate_handle(bbox, offset_x, offset_y, displayed_size);
+                                handle.distance(pointer) <= ROTATE_HANDLE_RADIUS + 3.0
+                            });
+                        // 按下多边形顶点时开始拖动该顶点（Alt+单击为删除顶点）
+                        if !alt {
+                            app.dragging_vertex = vertex_under_pointer;
+                            app.dragging_keypoint = keypoint_under_pointer;
+                        }
+                    }
+
+                    // 放置关键点时不拖动框
+                    let rotating = app.rotating_box
+                        || app.dragging_vertex.is_some()
+                        || app.dragging_keypoint.is_some()
+                        || app.active_keypoint.is_some();
+                    if let Some(index) = app.dragging_keypoint {
+                        if let Some(kp) = app
+                            .selected_box
+                            .and_then(|idx| app.bounding_boxes.get_mut(idx))
+                            .and_then(|bbox| bbox.keypoints.get_mut(index))
+                            && ui.input(|i| i.pointer.primary_down())
+                        {
+                            kp.x = px.clamp(0.0, 1.0);
+                            kp.y = py.clamp(0.0, 1.0);
+                        }
+                        if ui.input(|i| i.pointer.primary_released()) {
+                            app.dragging_keypoint = None;
+                            app.save_annotations();
+                            app.show_status("已保存关键点位置");
+                        }
+                    } else if let (Some(index), true) = (
+                        keypoint_under_pointer,
+                        alt && ui.input(|i| i.pointer.primary_clicked()),
+                    ) {
+                        app.toggle_keypoint_visibility(index);
+                    } else if app.active_keypoint.is_some()
+                        && ui.input(|i| i.pointer.primary_clicked())
+                    {
+                        app.place_keypoint(px, py);
+                    } else if let Some(vertex) = app.dragging_vertex {
+                        if let Some(bbox) = app
+                            .selected_box
+                            .and_then(|idx| app.bounding_boxes.get_mut(idx))
+                            && ui.input(|i| i.pointer.primary_down())
+                        {
+                            bbox.move_vertex(vertex, px, py);
+                        }
+                        if ui.input(|i| i.pointer.primary_released()) {
+                            app.dragging_vertex = None;
+                            app.save_annotations();
+                            app.show_status("已保存顶点位置");
+                        }
+                    } else if app.rotating_box {
+                        let aspect = (displayed_size.x / displayed_size.y) as f64;
+                        if let Some(bbox) = app
+                            .selected_box
+                            .and_then(|idx| app.bounding_boxes.get_mut(idx))
+                            && ui.input(|i| i.pointer.primary_down())
+                        {
+                            let center =
+                                to_screen([bbox.x, bbox.y], offset_x, offset_y, displayed_size);
+                            let previous = pointer - ui.input(|i| i.pointer.delta());
+                            let angle = (pointer - center).angle() - (previous - center).angle();
+                            bbox.rotate(angle as f64, aspect);
+                        }
+                        if ui.input(|i| i.pointer.primary_released()) {
+                            app.rotating_box = false;
+                            app.save_annotations();
+                            app.show_status("已保存旋转角度");
+                        }
+                    } else if let (Some(vertex), true) = (
+                        vertex_under_pointer,
+                        alt && ui.input(|i| i.pointer.primary_clicked()),
+                    ) {
+                        let removed = app
+                            .selected_box
+                            .and_then(|idx| app.bounding_boxes.get_mut(idx))
+                            .is_some_and(|bbox| bbox.remove_vertex(vertex));
+                        if removed {
+                            app.save_annotations();
+                            app.show_status("已删除顶点");
+                        } else {
+                            app.show_status("多边形至少需要三个顶点");
+                        }
+                    } else if let (Some(edge), true) = (
+                        edge_under_pointer,
+                        ui.input(|i| i.pointer.button_double_clicked(egui::PointerButton::Primary)),
+                    ) {
+                        if let Some(bbox) = app
+                            .selected_box
+                            .and_then(|idx| app.bounding_boxes.get_mut(idx))
+                        {
+                            bbox.insert_vertex(edge, px, py);
+                        }
+                        app.save_annotations();
+                        app.show_status("已插入顶点");
+                    } else if ui.input(|i| i.pointer.primary_clicked())
+                        && app.selected_box != hovered_box
+                    {
+                        app.selected_box = hovered_box;
+                        app.active_keypoint = None;
+                        if let Some(idx) = hovered_box {
+                            app.show_status(&format!("已选中边界框 {}", idx));
+                        } else {
+                            app.show_status("取消选中");
                         }
                     }
 
-                    if let Some(selected_idx) = app.selected_box {
+                    if let (Some(selected_idx), false) = (app.selected_box, rotating) {
                         if ui.input(|i| i.pointer.primary_down()) {
                             let delta = ui.input(|i| i.pointer.delta());
                             let dx = (delta.x as f64) / (displayed_size.x as f64);
@@ -150,13 +261,7 @@ pub fn central_panel(app: &mut AnnotationApp, ctx: &egui::Context) {
 
                             if let Some(bbox) = app.bounding_boxes.get_mut(selected_idx) {
                                 // 确保边界框不会超出图像范围
-                                let new_x = (bbox.x + dx)
-                                    .clamp(bbox.width / 2.0, 1.0 - bbox.width / 2.0);
-                                let new_y = (bbox.y + dy)
-                                    .clamp(bbox.height / 2.0, 1.0 - bbox.height / 2.0);
-                                // 进行舍入处理
-                                bbox.x = new_x;
-                                bbox.y = new_y;
+                                bbox.translate(dx, dy);
                             }
                         }
 
@@ -172,10 +277,22 @@ pub fn central_panel(app: &mut AnnotationApp, ctx: &egui::Context) {
                 app.is_drawing = false;
                 app.drawing_start = None;
                 app.selected_box = None;
+                app.polygon_draft.clear();
+                app.active_keypoint = None;
                 app.show_status("已退出绘制模式");
             }
 
+            let commented_boxes: Vec<usize> = app
+                .current_image_name
+                .as_ref()
+                .and_then(|name| app.image_records.get(name))
+                .map(|record| record.open_comments().filter_map(|c| c.box_index).collect())
+                .unwrap_or_default();
+
             for (i, bbox) in app.bounding_boxes.iter().enumerate() {
+                if !app.is_box_visible(bbox) {
+                    continue;
+                }
                 let box_width = bbox.width as f32 * displayed_size.x;
                 let box_height = bbox.height as f32 * displayed_size.y;
                 let center_x = offset_x + (bbox.x as f32 * displayed_size.x);
@@ -186,30 +303,110 @@ pub fn central_panel(app: &mut AnnotationApp, ctx: &egui::Context) {
                     egui::vec2(box_width, box_height),
                 );
 
-                let box_color = if Some(i) == app.selected_box {
-                    egui::Color32::GREEN
+                // 低置信度的预标注框半透明显示
+                let box_color = if bbox.is_low_confidence(app.confidence_threshold) {
+                    app.class_color(bbox.class).gamma_multiply(0.4)
                 } else {
-                    egui::Color32::RED
+                    app.class_color(bbox.class)
                 };
+                let is_selected = Some(i) == app.selected_box;
 
-                ui.painter().rect_stroke(
-                    rect,
-                    0.0,
-                    egui::Stroke::new(2.0, box_color),
-                    egui::StrokeKind::Middle,
-                );
+                let outline: Vec<egui::Pos2> = bbox
+                    .outline()
+                    .iter()
+                    .map(|p| to_screen(*p, offset_x, offset_y, displayed_size))
+                    .collect();
+
+                if is_selected {
+                    // 选中框：白色外描边 + 加粗类别色 + 四角手柄，与类别颜色无关地保持醒目
+                    ui.painter().add(egui::Shape::closed_line(
+                        outline.clone(),
+                        egui::Stroke::new(5.0, egui::Color32::WHITE),
+                    ));
+                    ui.painter().add(egui::Shape::closed_line(
+                        outline.clone(),
+                        egui::Stroke::new(3.0, box_color),
+                    ));
+                    for corner in &outline {
+                        ui.painter().rect_filled(
+                            egui::Rect::from_center_size(*corner, egui::vec2(6.0, 6.0)),
+                            0.0,
+                            egui::Color32::WHITE,
+                        );
+                    }
+                    if shows_rotate_handle(app, bbox) {
+                        let (anchor, handle) =
+                            rotate_handle(bbox, offset_x, offset_y, displayed_size);
+                        ui.painter().line_segment(
+                            [anchor, handle],
+                            egui::Stroke::new(1.5, egui::Color32::WHITE),
+                        );
+                        ui.painter()
+                            .circle_filled(handle, ROTATE_HANDLE_RADIUS, egui::Color32::WHITE);
+                        ui.painter().circle_stroke(
+                            handle,
+                            ROTATE_HANDLE_RADIUS,
+                            egui::Stroke::new(2.0, box_color),
+                        );
+                        if app.rotating_box {
+                            ui.painter().text(
+                                handle + egui::vec2(ROTATE_HANDLE_RADIUS + 4.0, 0.0),
+                                egui::Align2::LEFT_CENTER,
+                                format!(
+                                    "{:.1}°",
+                                    bbox.angle_degrees((displayed_size.x / displayed_size.y) as f64)
+                                ),
+                                egui::FontId::proportional(12.0),
+                                egui::Color32::WHITE,
+                            );
+                        }
+                    }
+                } else {
+                    ui.painter().add(egui::Shape::closed_line(
+                        outline,
+                        egui::Stroke::new(2.0, box_color),
+                    ));
+                }
 
                 // 根据设置显示或隐藏标签
                 if app.show_labels {
-                    ui.painter().text(
-                        rect.min,
-                        egui::Align2::LEFT_TOP,
-                        format!("Class {}", bbox.class),
+                    let text = match bbox.confidence {
+                        Some(confidence) => {
+                            format!("{} {:.2}", app.project.class_name(bbox.class), confidence)
+                        }
+                        None => app.project.class_name(bbox.class),
+                    };
+                    let galley = ui.painter().layout_no_wrap(
+                        text,
                         egui::FontId::default(),
-                        box_color,
+                        egui::Color32::WHITE,
+                    );
+                    let label_rect = egui::Rect::from_min_size(
+                        rect.min - egui::vec2(0.0, galley.size().y),
+                        galley.size() + egui::vec2(4.0, 0.0),
+                    );
+                    ui.painter().rect_filled(label_rect, 0.0, box_color);
+                    ui.painter().galley(
+                        label_rect.min + egui::vec2(2.0, 0.0),
+                        galley,
+                        egui::Color32::WHITE,
                     );
                 }
                 
+                // 有未处理审核意见的边界框显示标记
+                if commented_boxes.contains(&i) {
+                    let marker = rect.right_top();
+                    ui.painter()
+                        .circle_filled(marker, 8.0, egui::Color32::from_rgb(255, 140, 0));
+                    ui.painter().text(
+                        marker,
+                        egui::Align2::CENTER_CENTER,
+                        "!",
+                        egui::FontId::proportional(12.0),
+                        egui::Color32::WHITE,
+                    );
+                }
+
                 // 根据设置显示或隐藏中心点
                 if app.show_center_points {
                     // 绘制中心点
@@ -239,29 +436,427 @@ pub fn central_panel(app: &mut AnnotationApp, ctx: &egui::Context) {
                         egui::Stroke::new(1.0, box_color),
                     );
                 }
+
+                if !bbox.keypoints.is_empty() {
+                    draw_keypoints(app, ui, bbox, box_color, offset_x, offset_y, displayed_size);
+                }
+            }
+
+            // 放置关键点时在指针旁提示当前关键点名称
+            if let (Some(index), Some(bbox)) = (
+                app.active_keypoint,
+                app.selected_box.and_then(|idx| app.bounding_boxes.get(idx)),
+            ) && let (Some(pointer), Some(name)) = (
+                ui.input(|i| i.pointer.hover_pos()),
+                app.keypoint_schema(bbox.class)
+                    .and_then(|schema| schema.names.get(index)),
+            ) {
+                ui.painter().text(
+                    pointer + egui::vec2(12.0, 12.0),
+                    egui::Align2::LEFT_TOP,
+                    format!("{}: {}", index, name),
+                    egui::FontId::proportional(13.0),
+                    egui::Color32::WHITE,
+                );
             }
-        }
 
-        if ui.input(|i| i.key_pressed(egui::Key::Q)) {
-            app.save_annotations();
-            app.save_modified_records();
+            if app.compare_dir.is_some() {
+                draw_diff_overlay(app, ui, offset_x, offset_y, displayed_size);
+            }
         }
+    });
+}
 
-        if ui.input(|i| i.key_pressed(egui::Key::Delete)) {
-            if let Some(selected) = app.selected_box {
-                app.bounding_boxes.remove(selected);
-                app.selected_box = None;
-                app.save_annotations();
-                app.update_total_statistics();
-                app.show_status("已删除选中的边界框");
+/// 旋转手柄半径（像素）
+const ROTATE_HANDLE_RADIUS: f32 = 6.0;
+
+/// 归一化坐标 -> 屏幕坐标
+fn to_screen(
+    point: [f64; 2],
+    offset_x: f32,
+    offset_y: f32,
+    displayed_size: egui::Vec2,
+) -> egui::Pos2 {
+    egui::pos2(
+        offset_x + point[0] as f32 * displayed_size.x,
+        offset_y + point[1] as f32 * displayed_size.y,
+    )
+}
+
+/// 吸附到多边形顶点或边的距离（像素）
+const VERTEX_SNAP_DISTANCE: f32 = 8.0;
+/// 与上一个顶点相距不超过该像素数的点击不添加顶点：与 egui 判定双击的最大移动距离一致，
+/// 在最后一个顶点上双击结束时不会重复添加该顶点
+const DUPLICATE_VERTEX_DISTANCE: f32 = 6.0;
+
+/// 旋转框，或旋转框模式下不带关键点的水平框，选中时显示旋转手柄
+fn shows_rotate_handle(app: &AnnotationApp, bbox: &BoundingBox) -> bool {
+    if !bbox.can_convert_shape(true) {
+        return false;
+    }
+    match bbox.shape {
+        Shape::Oriented(_) => true,
+        Shape::Rect => app.project.annotation_mode == AnnotationMode::Obb,
+        Shape::Polygon(_) => false,
+    }
+}
+
+/// 点到线段的距离
+fn distance_to_segment(p: egui::Pos2, a: egui::Pos2, b: egui::Pos2) -> f32 {
+    let ab = b - a;
+    let t = if ab.length_sq() > 0.0 {
+        ((p - a).dot(ab) / ab.length_sq()).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    p.distance(a + ab * t)
+}
+
+/// 选中多边形上指针附近的顶点序号和边序号（边 i 连接顶点 i 和 i+1）
+fn polygon_hit(
+    app: &AnnotationApp,
+    pointer: egui::Pos2,
+    offset_x: f32,
+    offset_y: f32,
+    displayed_size: egui::Vec2,
+) -> (Option<usize>, Option<usize>) {
+    let Some(Shape::Polygon(points)) = app
+        .selected_box
+        .and_then(|idx| app.bounding_boxes.get(idx))
+        .map(|bbox| &bbox.shape)
+    else {
+        return (None, None);
+    };
+    let screen: Vec<egui::Pos2> = points
+        .iter()
+        .map(|p| to_screen(*p, offset_x, offset_y, displayed_size))
+        .collect();
+
+    let vertex = screen
+        .iter()
+        .position(|p| p.distance(pointer) <= VERTEX_SNAP_DISTANCE);
+    let edge = (0..screen.len()).find(|&i| {
+        let next = screen[(i + 1) % screen.len()];
+        distance_to_segment(pointer, screen[i], next) <= VERTEX_SNAP_DISTANCE / 2.0
+    });
+    (vertex, edge)
+}
+
+/// 选中框上指针附近的已标注关键点序号
+fn keypoint_hit(
+    app: &AnnotationApp,
+    pointer: egui::Pos2,
+    offset_x: f32,
+    offset_y: f32,
+    displayed_size: egui::Vec2,
+) -> Option<usize> {
+    let bbox = app.selected_box.and_then(|idx| app.bounding_boxes.get(idx))?;
+    bbox.keypoints.iter().position(|kp| {
+        kp.is_labeled()
+            && to_screen([kp.x, kp.y], offset_x, offset_y, displayed_size).distance(pointer)
+                <= VERTEX_SNAP_DISTANCE
+    })
+}
+
+/// 关键点半径（像素）
+const KEYPOINT_RADIUS: f32 = 4.0;
+
+/// 绘制骨架连线和关键点：可见为实心，遮挡为空心，未标注不绘制
+fn draw_keypoints(
+    app: &AnnotationApp,
+    ui: &egui::Ui,
+    bbox: &BoundingBox,
+    color: egui::Color32,
+    offset_x: f32,
+    offset_y: f32,
+    displayed_size: egui::Vec2,
+) {
+    let points: Vec<Option<egui::Pos2>> = bbox
+        .keypoints
+        .iter()
+        .map(|kp| {
+            kp.is_labeled()
+                .then(|| to_screen([kp.x, kp.y], offset_x, offset_y, displayed_size))
+        })
+        .collect();
+
+    if let Some(schema) = app.keypoint_schema(bbox.class) {
+        for &[a, b] in &schema.skeleton {
+            if let (Some(Some(a)), Some(Some(b))) = (points.get(a), points.get(b)) {
+                ui.painter()
+                    .line_segment([*a, *b], egui::Stroke::new(2.0, color));
             }
         }
+    }
 
-        if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
-            app.is_drawing = false;
-            app.drawing_start = None;
-            app.selected_box = None;
-            app.show_status("已退出绘制模式");
+    for (index, point) in points.iter().enumerate() {
+        let Some(point) = point else {
+            continue;
+        };
+        if bbox.keypoints[index].visibility == 2 {
+            ui.painter().circle_filled(*point, KEYPOINT_RADIUS, color);
+            ui.painter().circle_stroke(
+                *point,
+                KEYPOINT_RADIUS,
+                egui::Stroke::new(1.0, egui::Color32::WHITE),
+            );
+        } else {
+            ui.painter()
+                .circle_stroke(*point, KEYPOINT_RADIUS, egui::Stroke::new(2.0, color));
         }
+    }
+}
+
+/// 多边形绘制：单击添加顶点，单击第一个顶点、双击或按 Enter 闭合
+fn polygon_tool(
+    app: &mut AnnotationApp,
+    ui: &egui::Ui,
+    offset_x: f32,
+    offset_y: f32,
+    displayed_size: egui::Vec2,
+) {
+    let Some(pointer) = ui.input(|i| i.pointer.hover_pos()) else {
+        return;
+    };
+    let image_area = egui::Rect::from_min_size(egui::pos2(offset_x, offset_y), displayed_size);
+    let (clicked, double_clicked) = ui.input(|i| {
+        (
+            i.pointer.primary_clicked(),
+            i.pointer.button_double_clicked(egui::PointerButton::Primary),
+        )
     });
-}
\ No newline at end of file
+
+    let near_first = app.polygon_draft.len() >= 3
+        && app.polygon_draft.first().is_some_and(|p| {
+            to_screen(*p, offset_x, offset_y, displayed_size).distance(pointer)
+                <= VERTEX_SNAP_DISTANCE
+        });
+
+    if image_area.contains(pointer) {
+        if double_clicked || (clicked && near_first) {
+            app.finish_polygon();
+            return;
+        }
+        let near_last = app.polygon_draft.last().is_some_and(|p| {
+            to_screen(*p, offset_x, offset_y, displayed_size).distance(pointer)
+                <= DUPLICATE_VERTEX_DISTANCE
+        });
+        if clicked && !near_last {
+            app.polygon_draft.push([
+                ((pointer.x - offset_x) / displayed_size.x).clamp(0.0, 1.0) as f64,
+                ((pointer.y - offset_y) / displayed_size.y).clamp(0.0, 1.0) as f64,
+            ]);
+        }
+    }
+
+    if app.polygon_draft.is_empty() {
+        return;
+    }
+    let color = app.class_color(app.selected_class);
+    let mut points: Vec<egui::Pos2> = app
+        .polygon_draft
+        .iter()
+        .map(|p| to_screen(*p, offset_x, offset_y, displayed_size))
+        .collect();
+    for point in &points {
+        ui.painter().circle_filled(*point, 3.0, egui::Color32::WHITE);
+    }
+    if near_first {
+        ui.painter().circle_stroke(
+            points[0],
+            VERTEX_SNAP_DISTANCE,
+            egui::Stroke::new(2.0, egui::Color32::WHITE),
+        );
+    }
+    points.push(pointer);
+    ui.painter()
+        .add(egui::Shape::line(points, egui::Stroke::new(2.0, color)));
+}
+
+/// 旋转手柄：从第一条边的中点向框外延伸，返回（边中点，手柄位置）
+fn rotate_handle(
+    bbox: &BoundingBox,
+    offset_x: f32,
+    offset_y: f32,
+    displayed_size: egui::Vec2,
+) -> (egui::Pos2, egui::Pos2) {
+    let [a, b, ..] = bbox.corners();
+    let anchor = to_screen(
+        [(a[0] + b[0]) / 2.0, (a[1] + b[1]) / 2.0],
+        offset_x,
+        offset_y,
+        displayed_size,
+    );
+    let center = to_screen([bbox.x, bbox.y], offset_x, offset_y, displayed_size);
+    let direction = (anchor - center).normalized();
+    let direction = if direction.is_finite() && direction != egui::Vec2::ZERO {
+        direction
+    } else {
+        -egui::Vec2::Y
+    };
+    (anchor, anchor + direction * 20.0)
+}
+
+/// 归一化边界框在屏幕上的矩形
+fn box_rect(bbox: &BoundingBox, offset_x: f32, offset_y: f32, displayed_size: egui::Vec2) -> egui::Rect {
+    egui::Rect::from_center_size(
+        egui::pos2(
+            offset_x + bbox.x as f32 * displayed_size.x,
+            offset_y + bbox.y as f32 * displayed_size.y,
+        ),
+        egui::vec2(
+            bbox.width as f32 * displayed_size.x,
+            bbox.height as f32 * displayed_size.y,
+        ),
+    )
+}
+
+fn dashed_rect(ui: &egui::Ui, rect: egui::Rect, stroke: egui::Stroke) {
+    dashed_outline(
+        ui,
+        &[
+            rect.left_top(),
+            rect.right_top(),
+            rect.right_bottom(),
+            rect.left_bottom(),
+        ],
+        stroke,
+    );
+}
+
+/// 闭合的虚线轮廓
+fn dashed_outline(ui: &egui::Ui, outline: &[egui::Pos2], stroke: egui::Stroke) {
+    let mut points = outline.to_vec();
+    if let Some(first) = outline.first() {
+        points.push(*first);
+    }
+    ui.painter()
+        .extend(egui::Shape::dashed_line(&points, stroke, 6.0, 4.0));
+}
+
+/// 对比目录叠加层：用虚线绘制对比目录中的边界框，并按差异类型着色
+fn draw_diff_overlay(
+    app: &AnnotationApp,
+    ui: &egui::Ui,
+    offset_x: f32,
+    offset_y: f32,
+    displayed_size: egui::Vec2,
+) {
+    for diff in &app.box_diffs {
+        if diff.kind == DiffKind::Unchanged {
+            continue;
+        }
+        let stroke = egui::Stroke::new(2.0, diff.kind.color());
+
+        match (diff.ours, diff.theirs) {
+            (_, Some(theirs)) => {
+                let bbox = &app.compare_boxes[theirs];
+                if !app.is_class_visible(bbox.class) {
+                    continue;
+                }
+                let rect = box_rect(bbox, offset_x, offset_y, displayed_size);
+                let outline: Vec<egui::Pos2> = bbox
+                    .outline()
+                    .iter()
+                    .map(|p| to_screen(*p, offset_x, offset_y, displayed_size))
+                    .collect();
+                dashed_outline(ui, &outline, stroke);
+                ui.painter().text(
+                    rect.left_bottom(),
+                    egui::Align2::LEFT_TOP,
+                    format!("{} {}", diff.kind.label(), app.project.class_name(bbox.class)),
+                    egui::FontId::proportional(12.0),
+                    diff.kind.color(),
+                );
+            }
+            (Some(ours), None) => {
+                let bbox = &app.bounding_boxes[ours];
+                if !app.is_box_visible(bbox) {
+                    continue;
+                }
+                let rect = box_rect(bbox, offset_x, offset_y, displayed_size).expand(3.0);
+                dashed_rect(ui, rect, stroke);
+                ui.painter().text(
+                    rect.left_bottom(),
+                    egui::Align2::LEFT_TOP,
+                    diff.kind.label(),
+                    egui::FontId::proportional(12.0),
+                    diff.kind.color(),
+                );
+            }
+            (None, None) => {}
+        }
+    }
+}
+/// 根据快捷键表分发本帧触发的操作
+fn handle_shortcuts(app: &mut AnnotationApp, ctx: &egui::Context) {
+    // 正在录入新快捷键时不触发任何操作
+    if app.rebinding_action.is_some() {
+        return;
+    }
+
+    for action in app.keymap.triggered(ctx) {
+        match action {
+            Action::PrevImage if app.review_mode => app.switch_review_image(false),
+            Action::NextImage if app.review_mode => app.switch_review_image(true),
+            Action::PrevImage => app.switch_image(false, false),
+            Action::NextImage => app.switch_image(true, false),
+            Action::RandomUnmodified => app.switch_image(false, true),
+            Action::GoBack => app.go_back(),
+            Action::NextUnmodified => app.switch_to_next_unmodified(),
+            Action::ToggleDrawing => {
+                app.is_drawing = !app.is_drawing;
+                app.drawing_start = None;
+                app.selected_box = None;
+                app.polygon_draft.clear();
+                app.active_keypoint = None;
+                app.show_status(if app.is_drawing {
+                    "已进入绘制模式"
+                } else {
+                    "已退出绘制模式"
+                });
+            }
+            Action::Save => {
+                app.save_annotations();
+                app.save_image_records();
+            }
+            Action::DeleteBox => {
+                if let Some(selected) = app.selected_box {
+                    app.bounding_boxes.remove(selected);
+                    app.remove_comment_boxes(&[selected]);
+                    app.selected_box = None;
+                    app.active_keypoint = None;
+                    app.save_annotations();
+                    app.show_status("已删除选中的边界框");
+                }
+            }
+            Action::Cancel => {
+                app.is_drawing = false;
+                app.drawing_start = None;
+                app.selected_box = None;
+                app.polygon_draft.clear();
+                app.active_keypoint = None;
+                app.show_status("已退出绘制模式");
+            }
+            Action::SelectClass(class) => {
+                app.selected_class = class;
+                app.show_status(&format!(
+                    "已切换到类别 {} ({})",
+                    class,
+                    app.project.class_name(class)
+                ));
+            }
+            Action::ToggleReviewMode => app.toggle_review_mode(),
+            Action::Approve => app.approve_current(),
+            Action::PromoteBox => app.promote_selected_box(),
+            Action::NextLowConfidence => app.switch_to_low_confidence_image(),
+            Action::ClosePolygon => {
+                if app.is_drawing && !app.polygon_draft.is_empty() {
+                    app.finish_polygon();
+                }
+            }
+            Action::SetStatus(status) => app.set_current_status(status),
+            Action::ToggleTag(index) => app.toggle_current_tag(index),
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use eframe::egui;
+
+use crate::app::AnnotationApp;
+use crate::export::CropMode;
+
+/// 裁剪导出窗口：将每个框裁剪为分类数据集（ImageFolder 结构）
+pub fn crop_export_window(app: &mut AnnotationApp, ctx: &egui::Context) {
+    if !app.show_crop_dialog {
+        return;
+    }
+
+    let mut open = true;
+    egui::Window::new("导出裁剪图")
+        .open(&mut open)
+        .collapsible(false)
+        .default_width(320.0)
+        .show(ctx, |ui| {
+            let mut changed = false;
+            let config = &mut app.project.crops;
+            egui::Grid::new("crop_settings").show(ui, |ui| {
+                ui.label("边距:");
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut config.padding)
+                            .range(0.0..=2.0)
+                            .speed(0.01),
+                    )
+                    .on_hover_text("每边外扩的比例（相对框的宽高）")
+                    .changed();
+                ui.end_row();
+
+                ui.label("形状:");
+                ui.vertical(|ui| {
+                    for mode in CropMode::ALL {
+                        changed |= ui
+                            .radio_value(&mut config.mode, mode, mode.label())
+                            .changed();
+                    }
+                });
+                ui.end_row();
+
+                ui.label("最小尺寸:");
+                changed |= ui
+                    .add(egui::DragValue::new(&mut config.min_size).suffix(" px"))
+                    .on_hover_text("框的短边小于该值时跳过")
+                    .changed();
+                ui.end_row();
+
+                ui.label("输出长边:");
+                changed |= ui
+                    .add(egui::DragValue::new(&mut config.output_size).suffix(" px"))
+                    .on_hover_text("0 表示保持原始分辨率")
+                    .changed();
+                ui.end_row();
+
+                ui.label("JPEG 质量:");
+                changed |= ui
+                    .add(egui::DragValue::new(&mut config.jpeg_quality).range(1..=100))
+                    .changed();
+                ui.end_row();
+            });
+            if changed {
+                app.save_project();
+            }
+
+            ui.small(format!(
+                "从原始分辨率的图片中裁剪左侧文件列表中 {} 张图片的所有框",
+                app.filtered_image_files().len()
+            ));
+            if ui.button("选择目录并导出").clicked()
+                && let Some(dir) = rfd::FileDialog::new().pick_folder()
+            {
+                app.export_result_info = match app.export_crops(&dir) {
+                    Ok(report) => {
+                        let mut info = format!(
+                            "已导出 {} 张裁剪图到 {}，跳过 {} 个过小的框",
+                            report.crops,
+                            dir.display(),
+                            report.skipped
+                        );
+                        for failure in report.failures.iter().take(10) {
+                            info.push('\n');
+                            info.push_str(failure);
+                        }
+                        info
+                    }
+                    Err(e) => format!("导出失败: {}", e),
+                };
+                app.show_export_result_dialog = true;
+            }
+        });
+
+    if !open {
+        app.show_crop_dialog = false;
+    }
+}
@@ -0,0 +1,352 @@
+use eframe::egui;
+
+use crate::app::AnnotationApp;
+use crate::lint::{self, LintKind};
+use crate::models::ShapeKind;
+use crate::project::ProjectConfig;
+use crate::stats::{DatasetStats, HEATMAP_SIZE};
+
+/// 数据集分布窗口：框尺寸、宽高比、每图框数、各类别图片数和框中心热力图
+pub fn dataset_stats_window(app: &mut AnnotationApp, ctx: &egui::Context) {
+    app.poll_dataset_stats();
+    if app.dataset_stats_job.is_some() {
+        ctx.request_repaint();
+    }
+
+    if !app.show_dataset_stats {
+        return;
+    }
+
+    let mut open = true;
+    let mut open_issue = None;
+    egui::Window::new("数据集分布")
+        .open(&mut open)
+        .collapsible(false)
+        .default_width(560.0)
+        .show(ctx, |ui| {
+            if let Some(job) = app.dataset_stats_job.clone() {
+                let (processed, total) = job
+                    .lock()
+                    .map(|p| (p.processed, p.total))
+                    .unwrap_or_default();
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::ProgressBar::new(processed as f32 / total.max(1) as f32)
+                            .text(format!("{} / {}", processed, total)),
+                    );
+                    if ui.button("取消").clicked() {
+                        app.cancel_dataset_stats();
+                    }
+                });
+            } else {
+                ui.horizontal(|ui| {
+                    if ui
+                        .button(if app.dataset_stats.is_some() {
+                            "重新统计"
+                        } else {
+                            "开始统计"
+                        })
+                        .on_hover_text("在后台读取所有图片的尺寸和标签")
+                        .clicked()
+                    {
+                        app.start_dataset_stats();
+                    }
+                    if ui
+                        .add_enabled(app.dataset_stats.is_some(), egui::Button::new("导出 CSV"))
+                        .clicked()
+                        && let Some(path) = rfd::FileDialog::new()
+                            .add_filter("CSV", &["csv"])
+                            .set_file_name("dataset_stats.csv")
+                            .save_file()
+                    {
+                        match app.export_dataset_stats_csv(&path) {
+                            Ok(()) => app.show_status("已导出数据集分布"),
+                            Err(e) => app.show_status(&e),
+                        }
+                    }
+                    if ui
+                        .add_enabled(
+                            app.dataset_stats.is_some(),
+                            egui::Button::new("导出 HTML 报告"),
+                        )
+                        .on_hover_text("包含类别分布、尺寸直方图、标注问题、状态和示例图片的单文件报告")
+                        .clicked()
+                        && let Some(path) = rfd::FileDialog::new()
+                            .add_filter("HTML", &["html"])
+                            .set_file_name("dataset_report.html")
+                            .save_file()
+                    {
+                        match app.export_dataset_report(&path) {
+                            Ok(()) => app.show_status("已导出数据集报告"),
+                            Err(e) => app.show_status(&e),
+                        }
+                    }
+                });
+            }
+
+            let Some(stats) = &app.dataset_stats else {
+                ui.label("尚未统计");
+                return;
+            };
+            ui.label(format!(
+                "图片: {}  有标注: {}  框: {}  平均每图 {:.2} 个框",
+                stats.images,
+                stats.labeled_images,
+                stats.boxes,
+                stats.boxes as f64 / stats.images.max(1) as f64
+            ));
+            let shapes: Vec<String> = ShapeKind::ALL
+                .iter()
+                .map(|kind| {
+                    format!(
+                        "{}: {}",
+                        kind.label(),
+                        stats.shape_counts.get(kind).copied().unwrap_or(0)
+                    )
+                })
+                .collect();
+            ui.label(shapes.join("  "));
+            if !stats.failures.is_empty() {
+                ui.colored_label(
+                    egui::Color32::YELLOW,
+                    format!(
+                        "{} 张图片读取尺寸失败，未计入像素统计",
+                        stats.failures.len()
+                    ),
+                )
+                .on_hover_text(
+                    stats
+                        .failures
+                        .iter()
+                        .take(20)
+                        .cloned()
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                );
+            }
+
+            egui::ScrollArea::vertical()
+                .max_height(600.0)
+                .show(ui, |ui| {
+                    egui::CollapsingHeader::new("框宽度（像素）")
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            bar_chart(ui, &stats.widths.bins(), egui::Color32::LIGHT_BLUE)
+                        });
+                    egui::CollapsingHeader::new("框高度（像素）")
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            bar_chart(ui, &stats.heights.bins(), egui::Color32::LIGHT_GREEN)
+                        });
+                    egui::CollapsingHeader::new("宽高比（宽 / 高）")
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            bar_chart(ui, &stats.aspect_ratios.bins(), egui::Color32::GOLD)
+                        });
+                    egui::CollapsingHeader::new("每张图片的框数量")
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            bar_chart(ui, &stats.boxes_per_image.bins(), egui::Color32::LIGHT_RED)
+                        });
+                    egui::CollapsingHeader::new("各类别的图片数量")
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            egui::Grid::new("class_images_grid")
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    ui.strong("类别");
+                                    ui.strong("框");
+                                    ui.strong("图片");
+                                    ui.strong("占比");
+                                    ui.end_row();
+                                    for (class, images) in &stats.class_images {
+                                        ui.colored_label(
+                                            app.class_color(*class),
+                                            format!("{} {}", class, app.project.class_name(*class)),
+                                        );
+                                        ui.label(
+                                            stats
+                                                .class_boxes
+                                                .get(class)
+                                                .copied()
+                                                .unwrap_or(0)
+                                                .to_string(),
+                                        );
+                                        ui.label(images.to_string());
+                                        ui.label(format!(
+                                            "{:.1}%",
+                                            *images as f64 * 100.0 / stats.images.max(1) as f64
+                                        ));
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+                    egui::CollapsingHeader::new("框中心位置热力图")
+                        .default_open(true)
+                        .show(ui, |ui| heatmap(ui, &stats.heatmap));
+                    egui::CollapsingHeader::new(format!("标注问题（{}）", stats.issues.len()))
+                        .default_open(!stats.issues.is_empty())
+                        .show(ui, |ui| {
+                            if let Some(issue) = lint_list(ui, stats, &app.project) {
+                                open_issue = Some(issue);
+                            }
+                        });
+                });
+        });
+
+    if let Some((image, box_index)) = open_issue
+        && let Some(image_dir) = app.image_dir.clone()
+    {
+        app.load_image(&image_dir.join(image));
+        if box_index < app.bounding_boxes.len() {
+            app.selected_box = Some(box_index);
+        }
+    }
+    if !open {
+        app.show_dataset_stats = false;
+    }
+}
+
+/// 问题框列表，返回被点击的（图片，框序号）
+fn lint_list(
+    ui: &mut egui::Ui,
+    stats: &DatasetStats,
+    project: &ProjectConfig,
+) -> Option<(String, usize)> {
+    let issue_counts = stats.issue_counts();
+    let counts: Vec<String> = LintKind::ALL
+        .iter()
+        .map(|kind| {
+            format!(
+                "{}: {}",
+                kind.label(),
+                issue_counts.get(kind).copied().unwrap_or(0)
+            )
+        })
+        .collect();
+    ui.label(counts.join("  "));
+    let unknown = lint::unknown_classes(&stats.class_boxes, project);
+    if !unknown.is_empty() {
+        let classes: Vec<String> = unknown.keys().map(|class| class.to_string()).collect();
+        ui.colored_label(
+            egui::Color32::YELLOW,
+            format!("未在项目中命名的类别: {}", classes.join(", ")),
+        );
+    }
+    let mut clicked = None;
+    egui::ScrollArea::vertical()
+        .id_salt("lint_issues")
+        .max_height(200.0)
+        .show(ui, |ui| {
+            for issue in &stats.issues {
+                let text = format!(
+                    "{} #{} 类别 {}: {}",
+                    issue.image,
+                    issue.box_index + 1,
+                    issue.class,
+                    issue.kind.label()
+                );
+                if ui
+                    .selectable_label(false, text)
+                    .on_hover_text("打开图片并选中该框")
+                    .clicked()
+                {
+                    clicked = Some((issue.image.clone(), issue.box_index));
+                }
+            }
+        });
+    clicked
+}
+
+/// 简单柱状图，悬停显示区间和数量
+pub fn bar_chart(ui: &mut egui::Ui, bins: &[(String, usize)], color: egui::Color32) {
+    let chart_height = 100.0;
+    let label_height = 14.0;
+    let width = ui.available_width().clamp(200.0, 520.0);
+    let (rect, response) = ui.allocate_exact_size(
+        egui::vec2(width, chart_height + label_height),
+        egui::Sense::hover(),
+    );
+    if bins.is_empty() {
+        return;
+    }
+    let painter = ui.painter_at(rect);
+    let max = bins
+        .iter()
+        .map(|(_, count)| *count)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let bar_width = width / bins.len() as f32;
+    // 标签太密时隔几个显示一个
+    let label_step = ((bins.len() as f32 * 36.0 / width).ceil() as usize).max(1);
+    for (index, (label, count)) in bins.iter().enumerate() {
+        let left = rect.left() + index as f32 * bar_width;
+        let height = *count as f32 / max as f32 * chart_height;
+        let bar = egui::Rect::from_min_max(
+            egui::pos2(left + 1.0, rect.top() + chart_height - height),
+            egui::pos2(left + bar_width - 1.0, rect.top() + chart_height),
+        );
+        painter.rect_filled(bar, 0.0, color);
+        if index % label_step == 0 {
+            painter.text(
+                egui::pos2(left + bar_width / 2.0, rect.top() + chart_height + 1.0),
+                egui::Align2::CENTER_TOP,
+                label,
+                egui::FontId::proportional(9.0),
+                ui.visuals().weak_text_color(),
+            );
+        }
+    }
+    painter.line_segment(
+        [
+            egui::pos2(rect.left(), rect.top() + chart_height),
+            egui::pos2(rect.right(), rect.top() + chart_height),
+        ],
+        ui.visuals().widgets.noninteractive.bg_stroke,
+    );
+    if let Some(pos) = response.hover_pos() {
+        let index = (((pos.x - rect.left()) / bar_width) as usize).min(bins.len() - 1);
+        let (label, count) = &bins[index];
+        response.on_hover_text(format!("{}: {}", label, count));
+    }
+}
+
+/// 框中心位置热力图（图片坐标，左上角为原点）
+fn heatmap(ui: &mut egui::Ui, cells: &[usize]) {
+    let side = 256.0;
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(side, side), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    let cell = side / HEATMAP_SIZE as f32;
+    let max = cells.iter().copied().max().unwrap_or(0).max(1);
+    let low = egui::Color32::from_rgb(20, 20, 60);
+    let high = egui::Color32::from_rgb(255, 210, 40);
+    for (index, count) in cells.iter().enumerate() {
+        let t = (*count as f32 / max as f32).sqrt();
+        let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t) as u8;
+        let color = egui::Color32::from_rgb(
+            mix(low.r(), high.r()),
+            mix(low.g(), high.g()),
+            mix(low.b(), high.b()),
+        );
+        let (col, row) = (index % HEATMAP_SIZE, index / HEATMAP_SIZE);
+        let min = rect.min + egui::vec2(col as f32 * cell, row as f32 * cell);
+        painter.rect_filled(
+            egui::Rect::from_min_size(min, egui::vec2(cell, cell)),
+            0.0,
+            color,
+        );
+    }
+    if let Some(pos) = response.hover_pos() {
+        let col = (((pos.x - rect.left()) / cell) as usize).min(HEATMAP_SIZE - 1);
+        let row = (((pos.y - rect.top()) / cell) as usize).min(HEATMAP_SIZE - 1);
+        response.on_hover_text(format!(
+            "x {:.2}-{:.2}, y {:.2}-{:.2}: {} 个框",
+            col as f32 / HEATMAP_SIZE as f32,
+            (col + 1) as f32 / HEATMAP_SIZE as f32,
+            row as f32 / HEATMAP_SIZE as f32,
+            (row + 1) as f32 / HEATMAP_SIZE as f32,
+            cells[row * HEATMAP_SIZE + col]
+        ));
+    }
+}
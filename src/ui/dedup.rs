@@ -0,0 +1,213 @@
+use eframe::egui;
+
+use crate::app::AnnotationApp;
+use crate::dedup::{self, HashKind};
+
+/// 近似重复检测窗口：计算感知哈希、按相似度分组、并排对比并删除或标记为跳过
+pub fn duplicate_window(app: &mut AnnotationApp, ctx: &egui::Context) {
+    app.poll_hash_job();
+    app.poll_group_job();
+    if app.dedup_job.is_some() || app.dedup_group_job.is_some() {
+        ctx.request_repaint();
+    }
+
+    if !app.show_dedup_dialog {
+        return;
+    }
+
+    let mut open = true;
+    egui::Window::new("重复图片检测")
+        .open(&mut open)
+        .collapsible(false)
+        .default_width(820.0)
+        .show(ctx, |ui| {
+            let mut changed = false;
+            ui.horizontal(|ui| {
+                ui.label("算法:");
+                egui::ComboBox::from_id_salt("dedup_kind")
+                    .selected_text(app.project.dedup.kind.label())
+                    .show_ui(ui, |ui| {
+                        for kind in HashKind::ALL {
+                            changed |= ui
+                                .selectable_value(&mut app.project.dedup.kind, kind, kind.label())
+                                .changed();
+                        }
+                    });
+                ui.label("阈值:");
+                changed |= ui
+                    .add(egui::DragValue::new(&mut app.project.dedup.threshold).range(0..=32))
+                    .on_hover_text(
+                        "64 位哈希的汉明距离不超过该值视为近似重复，0 只找完全相同的哈希",
+                    )
+                    .changed();
+            });
+            if changed {
+                app.save_project();
+                app.regroup_duplicates();
+            }
+
+            if let Some(job) = app.dedup_job.clone() {
+                let (processed, total) = job
+                    .lock()
+                    .map(|p| (p.processed, p.total))
+                    .unwrap_or_default();
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::ProgressBar::new(processed as f32 / total.max(1) as f32)
+                            .text(format!("{} / {}", processed, total)),
+                    );
+                    if ui.button("取消").clicked() {
+                        app.cancel_hash_job();
+                    }
+                });
+            } else if ui
+                .button("计算哈希")
+                .on_hover_text("在后台计算文件列表中所有图片的哈希，未变化的图片使用缓存")
+                .clicked()
+            {
+                app.start_hash_job();
+            }
+
+            if app.dedup_hashes.is_empty() {
+                ui.label("尚未计算哈希");
+                return;
+            }
+            ui.horizontal(|ui| {
+                ui.label(format!(
+                    "{} 张图片中发现 {} 组近似重复，共 {} 张",
+                    app.dedup_hashes.len(),
+                    app.dedup_groups.len(),
+                    app.dedup_groups.iter().map(|g| g.len()).sum::<usize>()
+                ));
+                if app.dedup_group_job.is_some() {
+                    ui.spinner().on_hover_text("正在后台重新分组");
+                }
+            });
+            ui.separator();
+
+            ui.horizontal_top(|ui| {
+                ui.vertical(|ui| {
+                    ui.set_width(200.0);
+                    egui::ScrollArea::vertical()
+                        .id_salt("dedup_groups")
+                        .max_height(420.0)
+                        .show(ui, |ui| {
+                            for (index, group) in app.dedup_groups.iter().enumerate() {
+                                let mut text = format!("第 {} 组：{} 张", index + 1, group.len());
+                                if dedup::spans_splits(group, &app.split_record) {
+                                    text.push_str("（跨集合）");
+                                }
+                                if ui
+                                    .selectable_label(app.dedup_selected == Some(index), text)
+                                    .on_hover_text(group.join("\n"))
+                                    .clicked()
+                                {
+                                    app.dedup_selected = Some(index);
+                                    app.dedup_compare = (0, 1);
+                                }
+                            }
+                        });
+                });
+                ui.separator();
+                ui.vertical(|ui| compare_view(app, ui));
+            });
+        });
+
+    if !open {
+        app.show_dedup_dialog = false;
+        app.dedup_confirm_delete = None;
+        app.dedup_textures.clear();
+    }
+}
+
+/// 选中组中两张图片的并排对比
+fn compare_view(app: &mut AnnotationApp, ui: &mut egui::Ui) {
+    let Some(group) = app
+        .dedup_selected
+        .and_then(|index| app.dedup_groups.get(index))
+        .cloned()
+    else {
+        ui.label("在左侧选择一组进行对比");
+        return;
+    };
+
+    let (left, right) = app.dedup_compare;
+    let left = left.min(group.len() - 1);
+    let right = right.min(group.len() - 1);
+    let kind = app.project.dedup.kind;
+    let distance = match (
+        app.dedup_hashes.get(&group[left]),
+        app.dedup_hashes.get(&group[right]),
+    ) {
+        (Some(a), Some(b)) => dedup::hamming(a.get(kind), b.get(kind)),
+        _ => 0,
+    };
+    ui.horizontal(|ui| {
+        ui.label(format!("汉明距离: {}", distance));
+        if ui
+            .button("保留第一张，其余标记为跳过")
+            .on_hover_text(group[0].as_str())
+            .clicked()
+        {
+            app.mark_duplicates_skipped(&group[1..]);
+        }
+    });
+
+    let mut selection = [left, right];
+    let textures: Vec<Option<egui::TextureHandle>> = selection
+        .iter()
+        .map(|&index| app.dedup_texture(ui.ctx(), &group[index]))
+        .collect();
+    ui.columns(2, |columns| {
+        for (side, ui) in columns.iter_mut().enumerate() {
+            egui::ComboBox::from_id_salt(("dedup_compare", side))
+                .selected_text(group[selection[side]].as_str())
+                .show_ui(ui, |ui| {
+                    for (index, name) in group.iter().enumerate() {
+                        ui.selectable_value(&mut selection[side], index, name);
+                    }
+                });
+            let name = &group[selection[side]];
+            match &textures[side] {
+                Some(texture) => {
+                    ui.add(egui::Image::new(texture).max_size(egui::vec2(360.0, 360.0)));
+                }
+                None => {
+                    ui.label("图片加载失败");
+                }
+            }
+            ui.label(format!("状态: {}", app.image_status(name).label()));
+            ui.horizontal(|ui| {
+                if ui.button("打开").clicked()
+                    && let Some(image_dir) = app.image_dir.clone()
+                {
+                    app.load_image(&image_dir.join(name));
+                }
+                if ui.button("标记为跳过").clicked() {
+                    app.mark_duplicates_skipped(std::slice::from_ref(name));
+                }
+                if ui.button("删除").clicked() {
+                    app.dedup_confirm_delete = Some(name.clone());
+                }
+            });
+        }
+    });
+    app.dedup_compare = (selection[0], selection[1]);
+
+    if let Some(name) = app.dedup_confirm_delete.clone() {
+        ui.separator();
+        ui.colored_label(
+            egui::Color32::LIGHT_RED,
+            format!("确定删除 {} 及其标签吗？", name),
+        );
+        ui.horizontal(|ui| {
+            if ui.button("确定").clicked() {
+                app.dedup_confirm_delete = None;
+                app.delete_duplicate(&name);
+            }
+            if ui.button("取消").clicked() {
+                app.dedup_confirm_delete = None;
+            }
+        });
+    }
+}
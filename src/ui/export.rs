@@ -0,0 +1,289 @@
+use eframe::egui;
+
+use crate::app::AnnotationApp;
+use crate::export::{
+    ArchiveFormat, CollisionPolicy, ExportContent, ExportProfile, ExportScope, FileKind,
+    PlanAction, TransferMode,
+};
+use crate::models::ImageStatus;
+
+/// 预览列表最多显示的文件数量
+const PREVIEW_LIMIT: usize = 500;
+
+/// 导出方案窗口：选择/编辑导出方案，预览并导出图片和标签
+pub fn export_profile_window(app: &mut AnnotationApp, ctx: &egui::Context) {
+    if !app.show_export_dialog {
+        return;
+    }
+
+    let mut open = true;
+    egui::Window::new("导出图片/标签")
+        .open(&mut open)
+        .collapsible(false)
+        .default_width(420.0)
+        .show(ctx, |ui| {
+            let mut changed = false;
+            let profiles = &mut app.project.export_profiles;
+            app.export_profile_index = app
+                .export_profile_index
+                .min(profiles.len().saturating_sub(1));
+
+            ui.horizontal(|ui| {
+                ui.label("导出方案:");
+                egui::ComboBox::from_id_salt("export_profile")
+                    .selected_text(
+                        profiles
+                            .get(app.export_profile_index)
+                            .map(|p| p.name.as_str())
+                            .unwrap_or("无"),
+                    )
+                    .show_ui(ui, |ui| {
+                        for (index, profile) in profiles.iter().enumerate() {
+                            changed |= ui
+                                .selectable_value(
+                                    &mut app.export_profile_index,
+                                    index,
+                                    &profile.name,
+                                )
+                                .changed();
+                        }
+                    });
+                if ui.button("新建").clicked() {
+                    let mut profile = profiles
+                        .get(app.export_profile_index)
+                        .cloned()
+                        .unwrap_or_else(|| ExportProfile::presets().remove(0));
+                    profile.name = format!("方案 {}", profiles.len() + 1);
+                    profiles.push(profile);
+                    app.export_profile_index = profiles.len() - 1;
+                    changed = true;
+                }
+                if ui
+                    .add_enabled(profiles.len() > 1, egui::Button::new("删除"))
+                    .clicked()
+                {
+                    profiles.remove(app.export_profile_index);
+                    app.export_profile_index = app.export_profile_index.saturating_sub(1);
+                    changed = true;
+                }
+            });
+
+            if let Some(profile) = profiles.get_mut(app.export_profile_index) {
+                changed |= profile_editor(ui, profile);
+            }
+            if changed {
+                app.save_project();
+                app.export_preview = None;
+            }
+            ui.checkbox(&mut app.export_as_boxes, "多边形/旋转框导出为外接水平框");
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("选择导出目录").clicked()
+                    && let Some(dir) = rfd::FileDialog::new().pick_folder()
+                {
+                    app.export_target = Some(dir);
+                    app.export_preview = None;
+                }
+                match &app.export_target {
+                    Some(dir) => ui.label(dir.display().to_string()),
+                    None => ui.weak("未选择"),
+                };
+            });
+
+            ui.horizontal(|ui| {
+                let ready = app.export_target.is_some();
+                if ui
+                    .add_enabled(ready, egui::Button::new("预览"))
+                    .on_hover_text("只列出将要写出的文件，不修改磁盘")
+                    .clicked()
+                {
+                    match app.plan_export() {
+                        Ok(planned) => app.export_preview = Some(planned),
+                        Err(e) => {
+                            app.export_preview = None;
+                            app.show_status(&e);
+                        }
+                    }
+                }
+                if ui.add_enabled(ready, egui::Button::new("导出")).clicked() {
+                    let target = app
+                        .export_target
+                        .as_ref()
+                        .map(|dir| dir.display().to_string())
+                        .unwrap_or_default();
+                    app.export_result_info = match app.run_export() {
+                        Ok(report) => format!("导出到 {}：{}", target, report.summary()),
+                        Err(e) => format!("导出失败: {}", e),
+                    };
+                    app.show_export_result_dialog = true;
+                }
+            });
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("压缩包:");
+                for format in ArchiveFormat::ALL {
+                    ui.radio_value(&mut app.archive_format, format, format.extension());
+                }
+                ui.checkbox(&mut app.archive_split, "按训练/验证/测试划分")
+                    .on_hover_text("使用「划分数据集」中的比例和划分记录");
+            });
+            if ui
+                .button("导出压缩包")
+                .on_hover_text(
+                    "按当前方案的范围和内容直接写入压缩包，包含 data.yaml、导出清单和统计快照",
+                )
+                .clicked()
+            {
+                let extension = app.archive_format.extension();
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter(
+                        extension,
+                        &[extension.rsplit('.').next().unwrap_or(extension)],
+                    )
+                    .set_file_name(format!("dataset.{}", extension))
+                    .save_file()
+                {
+                    app.export_result_info = match app.export_archive(&path) {
+                        Ok(count) => format!("已将 {} 张图片导出到 {}", count, path.display()),
+                        Err(e) => format!("导出失败: {}", e),
+                    };
+                    app.show_export_result_dialog = true;
+                }
+            }
+
+            let Some(planned) = &app.export_preview else {
+                return;
+            };
+            let count = |action: PlanAction| planned.iter().filter(|f| f.action == action).count();
+            ui.label(format!(
+                "共 {} 个文件：写入 {}，覆盖 {}，重命名 {}，未变化 {}，跳过 {}，删除 {}",
+                planned.len(),
+                count(PlanAction::Write),
+                count(PlanAction::Overwrite),
+                count(PlanAction::Renamed),
+                count(PlanAction::Unchanged),
+                count(PlanAction::Skip),
+                count(PlanAction::Remove)
+            ));
+            egui::ScrollArea::vertical()
+                .max_height(300.0)
+                .show(ui, |ui| {
+                    egui::Grid::new("export_preview")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for file in planned.iter().take(PREVIEW_LIMIT) {
+                                ui.label(file.action.label());
+                                ui.label(match file.kind {
+                                    FileKind::Image => "图片",
+                                    FileKind::Label => "标签",
+                                });
+                                let source = file
+                                    .source
+                                    .file_name()
+                                    .map(|n| n.to_string_lossy().to_string())
+                                    .unwrap_or_default();
+                                let target = file
+                                    .target
+                                    .strip_prefix(
+                                        app.export_target.as_deref().unwrap_or(&file.target),
+                                    )
+                                    .unwrap_or(&file.target)
+                                    .display()
+                                    .to_string();
+                                ui.label(format!("{} -> {}", source, target));
+                                ui.end_row();
+                            }
+                        });
+                    if planned.len() > PREVIEW_LIMIT {
+                        ui.weak(format!(
+                            "…… 另有 {} 个文件未显示",
+                            planned.len() - PREVIEW_LIMIT
+                        ));
+                    }
+                });
+        });
+
+    if !open {
+        app.show_export_dialog = false;
+    }
+}
+
+/// 编辑单个导出方案，返回是否有修改
+fn profile_editor(ui: &mut egui::Ui, profile: &mut ExportProfile) -> bool {
+    let before = profile.clone();
+    egui::Grid::new("export_profile_editor").show(ui, |ui| {
+        ui.label("名称:");
+        ui.text_edit_singleline(&mut profile.name);
+        ui.end_row();
+
+        ui.label("范围:");
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_salt("export_scope")
+                .selected_text(match profile.scope {
+                    ExportScope::Status(_) => "指定状态".to_string(),
+                    scope => scope.label(),
+                })
+                .show_ui(ui, |ui| {
+                    for scope in [
+                        ExportScope::All,
+                        ExportScope::Modified,
+                        ExportScope::Filtered,
+                    ] {
+                        ui.selectable_value(&mut profile.scope, scope, scope.label());
+                    }
+                    if ui
+                        .selectable_label(
+                            matches!(profile.scope, ExportScope::Status(_)),
+                            "指定状态",
+                        )
+                        .clicked()
+                        && !matches!(profile.scope, ExportScope::Status(_))
+                    {
+                        profile.scope = ExportScope::Status(ImageStatus::Labeled);
+                    }
+                });
+            if let ExportScope::Status(status) = &mut profile.scope {
+                egui::ComboBox::from_id_salt("export_scope_status")
+                    .selected_text(status.label())
+                    .show_ui(ui, |ui| {
+                        for option in ImageStatus::ALL {
+                            ui.selectable_value(status, option, option.label());
+                        }
+                    });
+            }
+        });
+        ui.end_row();
+
+        ui.label("内容:");
+        ui.horizontal(|ui| {
+            for content in ExportContent::ALL {
+                ui.radio_value(&mut profile.content, content, content.label());
+            }
+        });
+        ui.end_row();
+
+        ui.label("写出方式:");
+        ui.horizontal(|ui| {
+            for transfer in TransferMode::ALL {
+                ui.radio_value(&mut profile.transfer, transfer, transfer.label());
+            }
+        });
+        ui.end_row();
+
+        ui.label("文件已存在:");
+        ui.horizontal(|ui| {
+            for collision in CollisionPolicy::ALL {
+                ui.radio_value(&mut profile.collision, collision, collision.label());
+            }
+        });
+        ui.end_row();
+
+        ui.label("");
+        ui.checkbox(&mut profile.remove_stale, "删除不再导出的旧文件")
+            .on_hover_text("只删除导出清单中记录的、上次导出过但本次不在范围内的文件");
+        ui.end_row();
+    });
+    *profile != before
+}
@@ -0,0 +1,115 @@
+use eframe::egui;
+
+use crate::app::AnnotationApp;
+use crate::keymap::{Action, KeyBinding, Keymap, event_key};
+
+pub fn keymap_window(app: &mut AnnotationApp, ctx: &egui::Context) {
+    if !app.show_keymap_dialog {
+        return;
+    }
+
+    // 录入新按键：取本帧第一个按下的按键，Esc 取消
+    if let Some(action) = app.rebinding_action {
+        let pressed = ctx.input(|i| {
+            i.events.iter().find_map(|event| match event {
+                egui::Event::Key {
+                    key,
+                    physical_key,
+                    pressed: true,
+                    repeat: false,
+                    modifiers,
+                } => Some((event_key(*key, *physical_key), *modifiers)),
+                _ => None,
+            })
+        });
+        if let Some((key, modifiers)) = pressed {
+            if key != egui::Key::Escape {
+                app.keymap
+                    .bindings
+                    .insert(action, vec![KeyBinding::from_event(key, modifiers)]);
+            }
+            app.rebinding_action = None;
+        }
+    }
+
+    let mut open = true;
+    egui::Window::new("快捷键设置")
+        .open(&mut open)
+        .collapsible(false)
+        .default_height(500.0)
+        .show(ctx, |ui| {
+            if let Some(action) = app.rebinding_action {
+                ui.colored_label(
+                    egui::Color32::YELLOW,
+                    format!("请为「{}」按下新的按键组合（Esc 取消）", action.label()),
+                );
+            } else {
+                ui.label("文本输入框获得焦点时快捷键不会生效");
+            }
+            ui.separator();
+
+            egui::ScrollArea::vertical()
+                .max_height(400.0)
+                .show(ui, |ui| {
+                    egui::Grid::new("keymap_grid")
+                        .striped(true)
+                        .num_columns(4)
+                        .show(ui, |ui| {
+                            for action in Action::all() {
+                                ui.label(action.label());
+
+                                let conflicts = app.keymap.conflicts(action);
+                                let text = egui::RichText::new(app.keymap.display(action));
+                                if conflicts.is_empty() {
+                                    ui.label(text);
+                                } else {
+                                    let names: Vec<String> =
+                                        conflicts.iter().map(|a| a.label()).collect();
+                                    ui.label(text.color(egui::Color32::RED)).on_hover_text(
+                                        format!("与以下操作冲突: {}", names.join("、")),
+                                    );
+                                }
+
+                                ui.horizontal(|ui| {
+                                    if ui.small_button("修改").clicked() {
+                                        app.rebinding_action = Some(action);
+                                    }
+                                    if ui.small_button("清除").clicked() {
+                                        app.keymap.bindings.insert(action, Vec::new());
+                                    }
+                                    if ui.small_button("默认").clicked() {
+                                        let defaults = Keymap::default();
+                                        app.keymap.bindings.insert(
+                                            action,
+                                            defaults.bindings_of(action).to_vec(),
+                                        );
+                                    }
+                                });
+                                ui.end_row();
+                            }
+                        });
+                });
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("保存为用户配置").clicked() {
+                    app.save_keymap_to_user();
+                }
+                if ui
+                    .button("保存为项目配置")
+                    .on_hover_text("保存到标签目录，优先于用户配置")
+                    .clicked()
+                {
+                    app.save_keymap_to_project();
+                }
+                if ui.button("全部恢复默认").clicked() {
+                    app.keymap = Keymap::default();
+                }
+            });
+        });
+
+    if !open {
+        app.show_keymap_dialog = false;
+        app.rebinding_action = None;
+    }
+}
@@ -0,0 +1,107 @@
+use eframe::egui;
+use std::collections::BTreeSet;
+
+use crate::app::AnnotationApp;
+use crate::project::KeypointSchemaDraft;
+
+/// 关键点模板编辑窗口：按类别设置关键点名称、骨架连线和翻转对
+pub fn keypoint_schema_window(app: &mut AnnotationApp, ctx: &egui::Context) {
+    if !app.show_keypoint_schema_dialog {
+        return;
+    }
+
+    let mut open = true;
+    egui::Window::new("关键点模板")
+        .open(&mut open)
+        .collapsible(false)
+        .default_width(360.0)
+        .show(ctx, |ui| {
+            let classes: BTreeSet<i32> = app
+                .project
+                .class_names
+                .keys()
+                .chain(app.project.keypoint_schemas.keys())
+                .copied()
+                .chain([app.keypoint_schema_draft.class])
+                .collect();
+            let current = app.keypoint_schema_draft.class;
+            ui.horizontal(|ui| {
+                ui.label("类别:");
+                egui::ComboBox::from_id_salt("keypoint_schema_class")
+                    .selected_text(format!("{} ({})", current, app.project.class_name(current)))
+                    .show_ui(ui, |ui| {
+                        for class in classes {
+                            let text = format!("{} ({})", class, app.project.class_name(class));
+                            if ui.selectable_label(class == current, text).clicked() {
+                                app.keypoint_schema_draft = KeypointSchemaDraft::new(
+                                    class,
+                                    app.project.keypoint_schemas.get(&class),
+                                );
+                            }
+                        }
+                    });
+            });
+
+            let draft = &mut app.keypoint_schema_draft;
+            ui.label("关键点名称（每行一个，顺序即标签中的顺序）:");
+            ui.add(
+                egui::TextEdit::multiline(&mut draft.names)
+                    .desired_rows(6)
+                    .desired_width(f32::INFINITY),
+            );
+            ui.label("骨架连线（关键点序号，如 0-1, 1-2）:");
+            ui.add(egui::TextEdit::singleline(&mut draft.skeleton).desired_width(f32::INFINITY));
+            ui.label("左右翻转对（如 1-2, 3-4）:");
+            ui.add(egui::TextEdit::singleline(&mut draft.flip_pairs).desired_width(f32::INFINITY));
+            ui.checkbox(
+                &mut draft.with_visibility,
+                "标签中写出可见性（每个关键点 3 列）",
+            );
+
+            ui.separator();
+            match draft.parse() {
+                Ok(schema) if !schema.names.is_empty() => {
+                    // Ultralytics 数据集配置片段
+                    let flip_idx = schema
+                        .flip_index()
+                        .iter()
+                        .map(|i| i.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    let dims = if schema.with_visibility { 3 } else { 2 };
+                    let mut snippet = format!(
+                        "kpt_shape: [{}, {}]\nflip_idx: [{}]",
+                        schema.names.len(),
+                        dims,
+                        flip_idx
+                    );
+                    ui.label("数据集配置（data.yaml）:");
+                    ui.add(
+                        egui::TextEdit::multiline(&mut snippet)
+                            .font(egui::TextStyle::Monospace)
+                            .desired_rows(2)
+                            .desired_width(f32::INFINITY)
+                            .interactive(false),
+                    );
+                }
+                Ok(_) => {
+                    ui.small("关键点名称为空，保存后将删除该类别的模板");
+                }
+                Err(e) => {
+                    ui.colored_label(egui::Color32::RED, e);
+                }
+            }
+            ui.small("修改关键点数量后，按旧模板写入的标签行将无法识别为关键点（内容不会丢失）");
+
+            if ui.button("保存模板").clicked() {
+                match app.apply_keypoint_schema() {
+                    Ok(()) => app.show_status("已保存关键点模板"),
+                    Err(e) => app.show_status(&e),
+                }
+            }
+        });
+
+    if !open {
+        app.show_keypoint_schema_dialog = false;
+    }
+}
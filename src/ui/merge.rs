@@ -0,0 +1,142 @@
+use eframe::egui;
+
+use crate::app::AnnotationApp;
+use crate::merge::MergeSource;
+
+/// 合并项目窗口：将多个图片/标签目录合并为一个项目
+pub fn merge_window(app: &mut AnnotationApp, ctx: &egui::Context) {
+    if !app.show_merge_dialog {
+        return;
+    }
+
+    let mut open = true;
+    egui::Window::new("合并项目")
+        .open(&mut open)
+        .collapsible(false)
+        .default_width(420.0)
+        .show(ctx, |ui| {
+            ui.label("按列表顺序合并，重名图片按项目序号改名，类别按名称统一编号");
+            let mut remove = None;
+            egui::Grid::new("merge_sources")
+                .striped(true)
+                .show(ui, |ui| {
+                    for (index, source) in app.merge_sources.iter().enumerate() {
+                        ui.label(format!("{}", index + 1));
+                        ui.vertical(|ui| {
+                            ui.label(format!("图片: {}", source.image_dir.display()));
+                            ui.label(format!("标签: {}", source.label_dir.display()));
+                        });
+                        if ui.small_button("移除").clicked() {
+                            remove = Some(index);
+                        }
+                        ui.end_row();
+                    }
+                });
+            if let Some(index) = remove {
+                app.merge_sources.remove(index);
+            }
+            ui.horizontal(|ui| {
+                if ui
+                    .button("添加项目")
+                    .on_hover_text("依次选择图片目录和标签目录")
+                    .clicked()
+                    && let Some(image_dir) = rfd::FileDialog::new()
+                        .set_title("选择图片目录")
+                        .pick_folder()
+                    && let Some(label_dir) = rfd::FileDialog::new()
+                        .set_title("选择标签目录")
+                        .set_directory(&image_dir)
+                        .pick_folder()
+                {
+                    app.merge_sources.push(MergeSource {
+                        image_dir,
+                        label_dir,
+                    });
+                }
+                if let (Some(image_dir), Some(label_dir)) = (&app.image_dir, &app.label_dir) {
+                    let current = MergeSource {
+                        image_dir: image_dir.clone(),
+                        label_dir: label_dir.clone(),
+                    };
+                    if ui
+                        .add_enabled(
+                            !app.merge_sources.contains(&current),
+                            egui::Button::new("添加当前项目"),
+                        )
+                        .clicked()
+                    {
+                        app.merge_sources.push(current);
+                    }
+                }
+            });
+
+            ui.separator();
+            egui::Grid::new("merge_output").show(ui, |ui| {
+                ui.label("合并后图片目录:");
+                let text = app
+                    .merge_image_out
+                    .as_ref()
+                    .map_or("未选择".to_string(), |p| p.display().to_string());
+                if ui.button(text).clicked()
+                    && let Some(dir) = rfd::FileDialog::new().pick_folder()
+                {
+                    app.merge_image_out = Some(dir);
+                }
+                ui.end_row();
+                ui.label("合并后标签目录:");
+                let text = app
+                    .merge_label_out
+                    .as_ref()
+                    .map_or("未选择".to_string(), |p| p.display().to_string());
+                if ui.button(text).clicked()
+                    && let Some(dir) = rfd::FileDialog::new().pick_folder()
+                {
+                    app.merge_label_out = Some(dir);
+                }
+                ui.end_row();
+            });
+            ui.checkbox(&mut app.merge_skip_duplicates, "跳过内容重复的图片")
+                .on_hover_text("按 SHA-1 判断，未勾选时重复图片仍会合并，只在报告中列出");
+
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(app.merge_sources.len() >= 2, egui::Button::new("合并"))
+                    .clicked()
+                {
+                    let result = app.merge_projects();
+                    app.export_result_info = match result {
+                        Ok(report) => {
+                            let mut info = report.summary();
+                            for duplicate in report.duplicates.iter().take(10) {
+                                info.push_str(&format!(
+                                    "\n重复: {} 与 {} 内容相同",
+                                    duplicate.source.display(),
+                                    duplicate.duplicate_of
+                                ));
+                            }
+                            for failure in report.failures.iter().take(10) {
+                                info.push('\n');
+                                info.push_str(failure);
+                            }
+                            info
+                        }
+                        Err(e) => format!("合并失败: {}", e),
+                    };
+                    app.show_export_result_dialog = true;
+                }
+                if ui
+                    .add_enabled(
+                        app.merge_image_out.is_some() && app.merge_label_out.is_some(),
+                        egui::Button::new("打开合并结果"),
+                    )
+                    .clicked()
+                {
+                    app.open_merged_dirs();
+                }
+            });
+        });
+
+    if !open {
+        app.show_merge_dialog = false;
+    }
+}
@@ -0,0 +1,134 @@
+use eframe::egui;
+
+use crate::app::AnnotationApp;
+
+/// 检测指标面板：以标签目录为真值、对比目录为预测结果计算指标
+pub fn metrics_panel(app: &mut AnnotationApp, ctx: &egui::Context) {
+    if !app.show_metrics_panel {
+        return;
+    }
+
+    egui::SidePanel::right("metrics_panel")
+        .default_width(320.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.heading("评估指标");
+                if ui.small_button("关闭").clicked() {
+                    app.show_metrics_panel = false;
+                }
+            });
+            ui.label("真值: 标签目录  预测: 对比目录");
+
+            if app.label_dir.is_none() || app.compare_dir.is_none() {
+                ui.label("请先选择标签目录和对比目录");
+                return;
+            }
+
+            ui.horizontal(|ui| {
+                ui.label("置信度阈值:");
+                ui.add(egui::Slider::new(
+                    &mut app.metrics_conf_threshold,
+                    0.0..=1.0,
+                ));
+            });
+            if ui.button("计算指标").clicked() {
+                app.run_evaluation();
+            }
+
+            let Some(report) = app.eval_report.clone() else {
+                return;
+            };
+
+            ui.separator();
+            ui.label(format!("图片数: {}", report.images));
+            ui.label(format!("mAP@0.5: {:.4}", report.map50));
+            ui.label(format!("mAP@0.5:0.95: {:.4}", report.map50_95));
+
+            ui.horizontal(|ui| {
+                if ui.button("导出 JSON").clicked()
+                    && let Some(path) = rfd::FileDialog::new()
+                        .add_filter("JSON", &["json"])
+                        .set_file_name("metrics.json")
+                        .save_file()
+                {
+                    let result = report
+                        .to_json()
+                        .and_then(|json| std::fs::write(&path, json).map_err(|e| e.to_string()));
+                    match result {
+                        Ok(()) => app.show_status("已导出评估结果"),
+                        Err(e) => app.show_status(&format!("导出失败: {}", e)),
+                    }
+                }
+                if ui.button("导出 CSV").clicked()
+                    && let Some(path) = rfd::FileDialog::new()
+                        .add_filter("CSV", &["csv"])
+                        .set_file_name("metrics.csv")
+                        .save_file()
+                {
+                    match std::fs::write(&path, report.to_csv()) {
+                        Ok(()) => app.show_status("已导出评估结果"),
+                        Err(e) => app.show_status(&format!("导出失败: {}", e)),
+                    }
+                }
+            });
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                ui.separator();
+                ui.label("各类别指标（P/R/F1 为 IoU 0.5 且置信度不低于阈值时的结果）");
+                egui::Grid::new("metrics_grid")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for header in ["类别", "真值", "预测", "AP50", "AP50-95", "P", "R", "F1"]
+                        {
+                            ui.strong(header);
+                        }
+                        ui.end_row();
+                        for m in &report.classes {
+                            ui.colored_label(
+                                app.class_color(m.class),
+                                app.project.class_name(m.class),
+                            );
+                            ui.label(m.gt_count.to_string());
+                            ui.label(m.pred_count.to_string());
+                            ui.label(format!("{:.3}", m.ap50));
+                            ui.label(format!("{:.3}", m.ap50_95));
+                            ui.label(format!("{:.3}", m.precision));
+                            ui.label(format!("{:.3}", m.recall));
+                            ui.label(format!("{:.3}", m.f1));
+                            ui.end_row();
+                        }
+                    });
+
+                ui.separator();
+                ui.label("混淆矩阵（行: 真值，列: 预测，背景表示漏检/误检）");
+                let confusion = &report.confusion;
+                let names: Vec<String> = confusion
+                    .classes
+                    .iter()
+                    .map(|c| c.to_string())
+                    .chain(std::iter::once("背景".to_string()))
+                    .collect();
+                egui::Grid::new("confusion_grid")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("");
+                        for name in &names {
+                            ui.strong(name);
+                        }
+                        ui.end_row();
+                        for (row, name) in names.iter().enumerate() {
+                            ui.strong(name);
+                            for (col, count) in confusion.matrix[row].iter().enumerate() {
+                                let text = egui::RichText::new(count.to_string());
+                                if *count > 0 && row != col {
+                                    ui.label(text.color(egui::Color32::RED));
+                                } else {
+                                    ui.label(text);
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+        });
+}
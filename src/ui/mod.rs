@@ -1,4 +1,18 @@
+pub mod attributes;
+pub mod autolabel;
 pub mod central;
+pub mod crops;
+pub mod dataset_stats;
+pub mod dedup;
+pub mod export;
+pub mod keypoints;
+pub mod keymap;
+pub mod merge;
+pub mod metrics;
+pub mod progress;
+pub mod remap;
+pub mod review;
 pub mod side;
+pub mod split;
 pub mod statistics;
 pub mod top;
@@ -0,0 +1,186 @@
+use eframe::egui;
+
+use crate::app::AnnotationApp;
+use crate::session::{WorkSummary, format_duration};
+use crate::ui::dataset_stats::bar_chart;
+
+/// 标注进度窗口：按会话日志统计每天/每小时的完成数量、平均耗时和预计完成时间
+pub fn progress_window(app: &mut AnnotationApp, ctx: &egui::Context) {
+    if !app.show_progress_dialog {
+        return;
+    }
+
+    let mut open = true;
+    egui::Window::new("标注进度")
+        .open(&mut open)
+        .collapsible(false)
+        .default_width(520.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("标注员:");
+                let mut names: Vec<String> = app
+                    .progress_log
+                    .iter()
+                    .map(|entry| entry.annotator.clone())
+                    .collect();
+                names.sort();
+                names.dedup();
+                let mut selected = app.progress_annotator.clone();
+                egui::ComboBox::from_id_salt("progress_annotator")
+                    .selected_text(selected.as_deref().unwrap_or("全部"))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut selected, None, "全部");
+                        for name in names {
+                            ui.selectable_value(&mut selected, Some(name.clone()), name);
+                        }
+                    });
+                if selected != app.progress_annotator {
+                    app.progress_annotator = selected;
+                    app.update_progress_report();
+                }
+                if ui
+                    .button("刷新")
+                    .on_hover_text("重新读取标签目录下的会话日志")
+                    .clicked()
+                {
+                    app.refresh_progress();
+                }
+            });
+
+            let report = &app.progress_report;
+            if app.progress_log.is_empty() {
+                ui.label("暂无会话记录，切换图片和标记完成时会自动记录");
+                return;
+            }
+
+            let total = &report.total;
+            let rate = |value: Option<f64>, unit: &str| {
+                value.map_or("-".to_string(), |v| format!("{:.1} {}", v, unit))
+            };
+            egui::Grid::new("progress_summary")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("完成图片:");
+                    ui.label(total.completed.to_string());
+                    ui.end_row();
+                    ui.label("修改过的图片:");
+                    ui.label(total.worked_images.to_string());
+                    ui.end_row();
+                    ui.label("标注时长:");
+                    ui.label(format_duration(total.seconds as f64));
+                    ui.end_row();
+                    ui.label("每小时完成:");
+                    ui.label(rate(total.images_per_hour(), "张"));
+                    ui.end_row();
+                    ui.label("每天完成:");
+                    ui.label(rate(report.images_per_day(), "张"));
+                    ui.end_row();
+                    ui.label("平均每张图片:");
+                    ui.label(
+                        total
+                            .seconds_per_image()
+                            .map_or("-".to_string(), format_duration),
+                    );
+                    ui.end_row();
+                    ui.label("平均每个框:");
+                    ui.label(
+                        total
+                            .seconds_per_box()
+                            .map_or("-".to_string(), format_duration),
+                    );
+                    ui.end_row();
+                });
+
+            let remaining = app
+                .statistics
+                .total_images
+                .saturating_sub(app.statistics.completed_images);
+            ui.separator();
+            match report.eta(remaining) {
+                _ if remaining == 0 => {
+                    ui.label("所有图片均已完成");
+                }
+                Some((hours, days)) => {
+                    ui.label(format!(
+                        "剩余 {} 张，按当前速度还需约 {} 标注时长，约 {:.1} 个工作日",
+                        remaining,
+                        format_duration(hours * 3600.0),
+                        days
+                    ));
+                }
+                None => {
+                    ui.label(format!("剩余 {} 张，完成记录不足，无法估算", remaining));
+                }
+            }
+
+            egui::ScrollArea::vertical()
+                .max_height(480.0)
+                .show(ui, |ui| {
+                    egui::CollapsingHeader::new("每日进度")
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            summary_grid(ui, "progress_days", "日期", report.days.iter().rev())
+                        });
+                    egui::CollapsingHeader::new("各时段完成数量")
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            let bins: Vec<(String, usize)> = report
+                                .hours
+                                .iter()
+                                .enumerate()
+                                .map(|(hour, count)| (format!("{}时", hour), *count))
+                                .collect();
+                            bar_chart(ui, &bins, egui::Color32::LIGHT_BLUE);
+                        });
+                    egui::CollapsingHeader::new("标注员")
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            summary_grid(
+                                ui,
+                                "progress_annotators",
+                                "标注员",
+                                report.annotators.iter(),
+                            )
+                        });
+                });
+        });
+
+    if !open {
+        app.show_progress_dialog = false;
+    }
+}
+
+/// 每行一个日期或标注员的工作量表格
+fn summary_grid<'a>(
+    ui: &mut egui::Ui,
+    id: &str,
+    heading: &str,
+    rows: impl Iterator<Item = (&'a String, &'a WorkSummary)>,
+) {
+    egui::Grid::new(id).striped(true).show(ui, |ui| {
+        ui.strong(heading);
+        ui.strong("完成");
+        ui.strong("修改");
+        ui.strong("时长");
+        ui.strong("每小时");
+        ui.strong("每张");
+        ui.end_row();
+        for (name, summary) in rows {
+            ui.label(if name.is_empty() { "未设置" } else { name });
+            ui.label(summary.completed.to_string());
+            ui.label(summary.worked_images.to_string());
+            ui.label(format_duration(summary.seconds as f64));
+            ui.label(
+                summary
+                    .images_per_hour()
+                    .map_or("-".to_string(), |v| format!("{:.1}", v)),
+            );
+            ui.label(
+                summary
+                    .seconds_per_image()
+                    .map_or("-".to_string(), format_duration),
+            );
+            ui.end_row();
+        }
+    });
+}
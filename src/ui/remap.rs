@@ -0,0 +1,186 @@
+use eframe::egui;
+use std::collections::BTreeSet;
+
+use crate::app::AnnotationApp;
+use crate::remap::{self, RemapTarget};
+
+/// 类别重映射窗口：编辑重映射表、预览各类别框数量的变化，导出时应用或直接修改标签目录
+pub fn class_remap_window(app: &mut AnnotationApp, ctx: &egui::Context) {
+    if !app.show_remap_dialog {
+        return;
+    }
+
+    let mut open = true;
+    egui::Window::new("类别重映射")
+        .open(&mut open)
+        .collapsible(false)
+        .default_width(380.0)
+        .show(ctx, |ui| {
+            let mut changed = false;
+            let classes: BTreeSet<i32> = app
+                .project
+                .class_names
+                .keys()
+                .chain(app.statistics.total_class_counts.keys())
+                .chain(app.project.remap.table.keys())
+                .copied()
+                .collect();
+
+            ui.label("不在表中的类别保持不变，多个类别改为同一编号即合并");
+            egui::ScrollArea::vertical()
+                .id_salt("remap_table")
+                .max_height(240.0)
+                .show(ui, |ui| {
+                    egui::Grid::new("remap_table_grid")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.strong("类别");
+                            ui.strong("操作");
+                            ui.strong("新编号");
+                            ui.end_row();
+                            for class in classes {
+                                let name = app.project.class_name(class);
+                                let mut target = app.project.remap.table.get(&class).copied();
+                                let before = target;
+                                ui.label(format!("{} {}", class, name));
+                                egui::ComboBox::from_id_salt(("remap_target", class))
+                                    .selected_text(match target {
+                                        None => "保持",
+                                        Some(RemapTarget::Class(_)) => "改为",
+                                        Some(RemapTarget::Drop) => "删除",
+                                    })
+                                    .show_ui(ui, |ui| {
+                                        ui.selectable_value(&mut target, None, "保持");
+                                        if ui
+                                            .selectable_label(
+                                                matches!(target, Some(RemapTarget::Class(_))),
+                                                "改为",
+                                            )
+                                            .clicked()
+                                        {
+                                            target = Some(RemapTarget::Class(class));
+                                        }
+                                        ui.selectable_value(
+                                            &mut target,
+                                            Some(RemapTarget::Drop),
+                                            "删除",
+                                        );
+                                    });
+                                if let Some(RemapTarget::Class(new_class)) = &mut target {
+                                    ui.add(egui::DragValue::new(new_class).range(0..=i32::MAX));
+                                } else {
+                                    ui.label("");
+                                }
+                                ui.end_row();
+
+                                if target != before {
+                                    changed = true;
+                                    match target {
+                                        Some(target) => {
+                                            app.project.remap.table.insert(class, target);
+                                        }
+                                        None => {
+                                            app.project.remap.table.remove(&class);
+                                        }
+                                    }
+                                }
+                            }
+                        });
+                });
+
+            ui.separator();
+            let config = &mut app.project.remap;
+            changed |= ui
+                .checkbox(&mut config.on_export, "导出时应用（不修改标签目录）")
+                .on_hover_text("对 COCO、VOC、按方案导出、划分数据集、压缩包和裁剪图生效")
+                .changed();
+            changed |= ui
+                .add_enabled(
+                    config.on_export,
+                    egui::Checkbox::new(&mut config.skip_empty, "导出时跳过重映射后没有框的图片"),
+                )
+                .changed();
+            if changed {
+                app.save_project();
+            }
+
+            ui.separator();
+            ui.label("预览（所有图片的框数量）:");
+            let rows = remap::preview(&app.statistics.total_class_counts, &app.project.remap.table);
+            egui::Grid::new("remap_preview")
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.strong("类别");
+                    ui.strong("之前");
+                    ui.strong("之后");
+                    ui.end_row();
+                    for (class, before, after) in rows {
+                        ui.label(format!("{} {}", class, app.project.class_name(class)));
+                        ui.label(before.to_string());
+                        let text = after.to_string();
+                        if after == before {
+                            ui.label(text);
+                        } else {
+                            ui.colored_label(egui::Color32::YELLOW, text);
+                        }
+                        ui.end_row();
+                    }
+                });
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                let has_table = !app.project.remap.table.is_empty();
+                if ui
+                    .add_enabled(has_table, egui::Button::new("清空重映射表"))
+                    .clicked()
+                {
+                    app.project.remap.table.clear();
+                    app.save_project();
+                }
+                if ui
+                    .add_enabled(has_table, egui::Button::new("应用到标签目录"))
+                    .on_hover_text("直接修改所有图片的标签文件，无法撤销")
+                    .clicked()
+                {
+                    app.confirm_remap_apply = true;
+                }
+            });
+            if app.confirm_remap_apply {
+                ui.colored_label(
+                    egui::Color32::LIGHT_RED,
+                    format!(
+                        "将修改 {} 张图片的标签文件，无法撤销，确定吗？",
+                        app.cached_image_files.len()
+                    ),
+                );
+                ui.horizontal(|ui| {
+                    if ui.button("确定").clicked() {
+                        app.confirm_remap_apply = false;
+                        match app.apply_remap_to_labels() {
+                            Ok(report) => {
+                                let mut info = format!(
+                                    "已修改 {} 个标签文件：{} 个框改为新类别，删除 {} 个框",
+                                    report.files, report.changed, report.dropped
+                                );
+                                for failure in report.failures.iter().take(10) {
+                                    info.push('\n');
+                                    info.push_str(failure);
+                                }
+                                app.export_result_info = info;
+                                app.show_export_result_dialog = true;
+                            }
+                            Err(e) => app.show_status(&e),
+                        }
+                    }
+                    if ui.button("取消").clicked() {
+                        app.confirm_remap_apply = false;
+                    }
+                });
+            }
+        });
+
+    if !open {
+        app.show_remap_dialog = false;
+        app.confirm_remap_apply = false;
+    }
+}
@@ -0,0 +1,128 @@
+use eframe::egui;
+
+use crate::app::AnnotationApp;
+use crate::keymap::Action;
+use crate::utils::format_timestamp;
+
+/// 审核窗口：审核模式下显示，或当前图片存在未处理的审核意见时显示（供标注员返工）
+pub fn review_window(app: &mut AnnotationApp, ctx: &egui::Context) {
+    let Some(name) = app.current_image_name.clone() else {
+        return;
+    };
+    let record = app.image_records.get(&name).cloned().unwrap_or_default();
+    let has_open_comments = record.open_comments().next().is_some();
+    if !app.review_mode && !has_open_comments {
+        return;
+    }
+
+    egui::Window::new(if app.review_mode { "审核" } else { "审核意见" })
+        .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-220.0, 40.0))
+        .collapsible(true)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(format!("图片: {}", name));
+            ui.colored_label(
+                record.status.color(),
+                format!("状态: {}", record.status.label()),
+            );
+            if record.last_editor.is_empty() {
+                ui.label("最后编辑: -");
+            } else {
+                ui.label(format!(
+                    "最后编辑: {} ({})",
+                    record.last_editor,
+                    format_timestamp(record.last_edited_at)
+                ));
+            }
+            if !record.reviewer.is_empty() {
+                ui.label(format!("审核人: {}", record.reviewer));
+            }
+            if app.review_mode {
+                ui.label(format!("待审核队列: {} 张", app.review_queue_len()));
+            }
+
+            if !record.comments.is_empty() {
+                ui.separator();
+                ui.label("审核意见:");
+                for (index, comment) in record.comments.iter().enumerate() {
+                    ui.horizontal_wrapped(|ui| {
+                        let mut resolved = comment.resolved;
+                        if ui
+                            .checkbox(&mut resolved, "")
+                            .on_hover_text("标记为已处理")
+                            .changed()
+                        {
+                            app.set_comment_resolved(&name, index, resolved);
+                        }
+                        let target = match comment.box_index {
+                            Some(box_index) => format!("框 #{}", box_index),
+                            None => "整图".to_string(),
+                        };
+                        let text = format!(
+                            "[{}] {}: {} ({})",
+                            target,
+                            comment.author,
+                            comment.text,
+                            format_timestamp(comment.created_at)
+                        );
+                        let text = if comment.resolved {
+                            egui::RichText::new(text).strikethrough().weak()
+                        } else {
+                            egui::RichText::new(text)
+                        };
+                        let response = ui.label(text);
+                        // 点击关联到边界框的意见可选中该框
+                        if let Some(box_index) = comment.box_index
+                            && response.interact(egui::Sense::click()).clicked()
+                            && box_index < app.bounding_boxes.len()
+                        {
+                            app.selected_box = Some(box_index);
+                        }
+                    });
+                }
+            }
+
+            if !app.review_mode {
+                return;
+            }
+
+            ui.separator();
+            if ui
+                .button("✔ 通过")
+                .on_hover_text(format!(
+                    "快捷键: {}",
+                    app.keymap.display(Action::Approve)
+                ))
+                .clicked()
+            {
+                app.approve_current();
+            }
+
+            ui.label("驳回意见:");
+            ui.add(
+                egui::TextEdit::multiline(&mut app.review_comment_draft)
+                    .desired_rows(2)
+                    .hint_text("说明需要修改的地方"),
+            );
+            match app.selected_box {
+                Some(idx) => {
+                    ui.checkbox(
+                        &mut app.review_pin_to_box,
+                        format!("关联到选中的边界框 #{}", idx),
+                    );
+                }
+                None => {
+                    ui.label("未选中边界框，意见将针对整张图片");
+                }
+            }
+            if ui.button("✘ 驳回").clicked() {
+                let box_index = if app.review_pin_to_box {
+                    app.selected_box
+                } else {
+                    None
+                };
+                let text = std::mem::take(&mut app.review_comment_draft);
+                app.reject_current(text, box_index);
+            }
+        });
+}
@@ -1,30 +1,85 @@
 use eframe::egui;
-use std::fs;
 
 use crate::app::AnnotationApp;
+use crate::models::{ImageStatus, TagFilter};
 
 pub fn side_panel(app: &mut AnnotationApp, ctx: &egui::Context) {
     egui::SidePanel::left("side_panel").show(ctx, |ui| {
-        if let Some(image_dir) = &app.image_dir {
-            let entries = fs::read_dir(image_dir)
-                .unwrap_or_else(|_| return std::fs::read_dir(".").unwrap());
-            let mut image_files: Vec<_> = entries
-                .filter_map(|entry| entry.ok())
-                .filter(|entry| {
-                    entry
-                        .path()
-                        .extension()
-                        .map_or(false, |ext| ext == "jpg" || ext == "png")
-                })
-                .collect();
+        if app.image_dir.is_some() {
+            ui.horizontal(|ui| {
+                ui.label("状态过滤:");
+                egui::ComboBox::from_id_salt("status_filter")
+                    .selected_text(app.file_filter.status.map_or("全部", |s| s.label()))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut app.file_filter.status, None, "全部");
+                        for status in ImageStatus::ALL {
+                            ui.selectable_value(
+                                &mut app.file_filter.status,
+                                Some(status),
+                                status.label(),
+                            );
+                        }
+                    });
+                if ui.small_button("刷新").clicked() {
+                    app.update_file_list();
+                }
+            });
+            if !app.project.image_tags.is_empty() {
+                ui.horizontal(|ui| {
+                    ui.label("标签过滤:");
+                    let selected = match &app.file_filter.tag {
+                        TagFilter::All => "全部".to_string(),
+                        TagFilter::Untagged => "未打标签".to_string(),
+                        TagFilter::Tag(tag) => tag.clone(),
+                    };
+                    egui::ComboBox::from_id_salt("tag_filter")
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut app.file_filter.tag, TagFilter::All, "全部");
+                            ui.selectable_value(
+                                &mut app.file_filter.tag,
+                                TagFilter::Untagged,
+                                "未打标签",
+                            );
+                            for definition in &app.project.image_tags {
+                                ui.selectable_value(
+                                    &mut app.file_filter.tag,
+                                    TagFilter::Tag(definition.name.clone()),
+                                    &definition.name,
+                                );
+                            }
+                        });
+                });
+            }
+
+            let image_files = app.filtered_image_files();
 
-            image_files.sort_by_key(|entry| entry.path());
+            ui.horizontal(|ui| {
+                ui.label("批量设为:");
+                egui::ComboBox::from_id_salt("bulk_status")
+                    .selected_text(app.bulk_status.label())
+                    .show_ui(ui, |ui| {
+                        for status in ImageStatus::ALL {
+                            ui.selectable_value(&mut app.bulk_status, status, status.label());
+                        }
+                    });
+                if ui
+                    .button("应用")
+                    .on_hover_text(format!("应用到当前列表中的 {} 张图片", image_files.len()))
+                    .clicked()
+                {
+                    let status = app.bulk_status;
+                    let count = app.bulk_set_status(status);
+                    app.show_status(&format!("已将 {} 张图片标记为「{}」", count, status.label()));
+                }
+            });
+            ui.label(format!("共 {} 张", image_files.len()));
+            ui.separator();
 
             let scroll_area = egui::ScrollArea::vertical().auto_shrink([false; 2]);
 
             scroll_area.show(ui, |ui| {
-                for entry in image_files {
-                    let path = entry.path();
+                for path in image_files {
                     let file_name = path
                         .file_name()
                         .unwrap_or_default()
@@ -34,17 +89,15 @@ pub fn side_panel(app: &mut AnnotationApp, ctx: &egui::Context) {
                     let is_selected = app
                         .current_image_name
                         .as_ref()
-                        .map_or(false, |current| current == &file_name);
+                        .is_some_and(|current| current == &file_name);
 
-                    let is_modified = app.modified_images.contains(&file_name);
+                    let status = app.image_status(&file_name);
 
                     let button = egui::Button::new(egui::RichText::new(&file_name).color(
                         if is_selected {
                             egui::Color32::YELLOW
-                        } else if is_modified {
-                            egui::Color32::from_rgb(0, 100, 0)
                         } else {
-                            egui::Color32::BLACK
+                            status.color()
                         },
                     ))
                     .fill(if is_selected {
@@ -53,7 +106,7 @@ pub fn side_panel(app: &mut AnnotationApp, ctx: &egui::Context) {
                         egui::Color32::from_gray(230)
                     });
 
-                    let response = ui.add(button);
+                    let response = ui.add(button).on_hover_text(status.label());
                     if response.clicked() {
                         app.load_image(&path);
                     }
@@ -66,4 +119,4 @@ pub fn side_panel(app: &mut AnnotationApp, ctx: &egui::Context) {
             });
         }
     });
-}
\ No newline at end of file
+}
@@ -0,0 +1,110 @@
+use eframe::egui;
+
+use crate::app::AnnotationApp;
+use crate::split::Split;
+
+/// 数据集划分窗口：按比例划分训练/验证/测试集并导出为 Ultralytics 目录结构
+pub fn split_window(app: &mut AnnotationApp, ctx: &egui::Context) {
+    if !app.show_split_dialog {
+        return;
+    }
+
+    let mut open = true;
+    egui::Window::new("数据集划分")
+        .open(&mut open)
+        .collapsible(false)
+        .default_width(360.0)
+        .show(ctx, |ui| {
+            let mut changed = false;
+            let config = &mut app.project.split;
+            egui::Grid::new("split_settings").show(ui, |ui| {
+                for split in Split::ALL {
+                    ui.label(format!("{}比例:", split.label()));
+                    changed |= ui
+                        .add(
+                            egui::DragValue::new(&mut config.ratios[split as usize])
+                                .range(0.0..=1.0)
+                                .speed(0.01),
+                        )
+                        .changed();
+                    ui.end_row();
+                }
+                ui.label("随机种子:");
+                changed |= ui.add(egui::DragValue::new(&mut config.seed)).changed();
+                ui.end_row();
+                ui.label("分组分隔符:");
+                changed |= ui
+                    .add(egui::TextEdit::singleline(&mut config.group_separator).desired_width(60.0))
+                    .on_hover_text("文件名中最后一个分隔符之前的部分相同的图片划入同一集合，如 video01_000123 按 _ 分组为 video01")
+                    .changed();
+                ui.end_row();
+            });
+            changed |= ui
+                .checkbox(&mut config.stratify, "按类别分层")
+                .on_hover_text("按每组图片中最稀有的类别分层，使各类别在各集合中的比例接近设定比例")
+                .changed();
+            if changed {
+                app.save_project();
+                app.split_preview = None;
+            }
+
+            ui.small(format!(
+                "使用左侧文件列表中的图片（{} 张），已记录划分的 {} 张图片保持不变",
+                app.filtered_image_files().len(),
+                app.split_record.len()
+            ));
+
+            ui.horizontal(|ui| {
+                if ui.button("预览").clicked() {
+                    app.preview_split();
+                }
+                if ui.button("导出").clicked()
+                    && let Some(dir) = rfd::FileDialog::new().pick_folder() {
+                        match app.export_split(&dir) {
+                            Ok(count) => app.show_status(&format!(
+                                "已导出 {} 张图片到 {}",
+                                count,
+                                dir.display()
+                            )),
+                            Err(e) => app.show_status(&format!("导出失败: {}", e)),
+                        }
+                    }
+                if ui
+                    .add_enabled(!app.split_record.is_empty(), egui::Button::new("清除划分记录"))
+                    .clicked()
+                {
+                    app.clear_split_record();
+                }
+            });
+
+            let Some(summary) = app.split_preview.clone() else {
+                return;
+            };
+            ui.separator();
+            egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                egui::Grid::new("split_summary").striped(true).show(ui, |ui| {
+                    ui.label("");
+                    for split in Split::ALL {
+                        ui.label(split.label());
+                    }
+                    ui.end_row();
+                    ui.label("图片");
+                    for count in summary.images {
+                        ui.label(count.to_string());
+                    }
+                    ui.end_row();
+                    for (class, counts) in &summary.classes {
+                        ui.label(app.project.class_name(*class));
+                        for count in counts {
+                            ui.label(count.to_string());
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+        });
+
+    if !open {
+        app.show_split_dialog = false;
+    }
+}
@@ -1,24 +1,102 @@
 use core::f32;
 
 use eframe::egui;
+use std::collections::HashMap;
 
 use crate::app::AnnotationApp;
+use crate::diff::{BoxDiff, DiffKind, DiffSummary};
+use crate::export::ExportProfile;
+use crate::keymap::{Action, CLASS_HOTKEY_COUNT, TAG_HOTKEY_COUNT};
+use crate::models::{ImageStatus, ShapeKind};
+use crate::tags;
+use crate::ui::attributes::attribute_inspector;
+use crate::project::{AnnotationMode, KeypointSchemaDraft};
 
 pub fn statistics_panel(app: &mut AnnotationApp, ctx: &egui::Context) {
+    app.poll_total_statistics();
+    if app.totals_job.is_some() {
+        ctx.request_repaint();
+    }
+
     egui::SidePanel::right("statistics_panel")
         .default_width(200.0)
         .show(ctx, |ui| {
             ui.heading("统计信息");
             ui.label(format!("总图片数: {}", app.statistics.total_images));
-            ui.label(format!("已标注图片: {}", app.statistics.modified_images));
+            ui.label(format!("已完成图片: {}", app.statistics.completed_images));
             ui.label(format!(
                 "完成进度: {:.1}%",
-                (app.statistics.modified_images as f32 / app.statistics.total_images as f32 * 100.0)
+                (app.statistics.completed_images as f32 / app.statistics.total_images as f32 * 100.0)
                     .max(0.0)
             ));
+            for status in ImageStatus::ALL {
+                let count = app.statistics.status_counts.get(&status).copied().unwrap_or(0);
+                if count > 0 {
+                    ui.colored_label(status.color(), format!("{}: {}", status.label(), count));
+                }
+            }
+
+            if let Some(name) = app.current_image_name.clone() {
+                ui.separator();
+                ui.heading("当前图片状态");
+                let current = app.image_status(&name);
+                ui.horizontal_wrapped(|ui| {
+                    for status in ImageStatus::ALL {
+                        let text = egui::RichText::new(status.label()).color(status.color());
+                        if ui
+                            .selectable_label(current == status, text)
+                            .on_hover_text(format!(
+                                "快捷键: {}",
+                                app.keymap.display(Action::SetStatus(status))
+                            ))
+                            .clicked()
+                        {
+                            app.set_current_status(status);
+                        }
+                    }
+                });
+                let mut note = app
+                    .image_records
+                    .get(&name)
+                    .map(|r| r.note.clone())
+                    .unwrap_or_default();
+                ui.label("备注:");
+                let response = ui.add(egui::TextEdit::multiline(&mut note).desired_rows(2));
+                if response.changed() {
+                    app.set_image_note(&name, note);
+                }
+                // 编辑结束时才写入文件，避免每次按键都重写状态记录
+                if response.lost_focus() {
+                    app.save_image_records();
+                }
+            }
+
+            ui.separator();
+            ui.heading("图片标签");
+            image_tags_section(app, ui);
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.heading("框属性");
+                if ui.small_button("属性定义").clicked() {
+                    app.show_attribute_dialog = true;
+                }
+            });
+            attribute_inspector(app, ui);
 
             ui.separator();
-            ui.heading("所有图片标注统计");
+            ui.horizontal(|ui| {
+                ui.heading("所有图片标注统计");
+                if app.totals_job.is_some() {
+                    ui.spinner().on_hover_text("正在后台重新统计");
+                } else if ui
+                    .small_button("重新统计")
+                    .on_hover_text("重新读取所有标签文件，保存标注时统计会自动更新")
+                    .clicked()
+                {
+                    app.update_total_statistics();
+                }
+            });
             // 获取并排序类别
             let mut classes: Vec<_> = app.statistics.total_class_counts.keys().collect();
             classes.sort();
@@ -28,6 +106,22 @@ pub fn statistics_panel(app: &mut AnnotationApp, ctx: &egui::Context) {
                     ui.label(format!("类别 {}: {} 个", class, count));
                 }
             }
+            shape_counts(ui, &app.statistics.total_shape_counts);
+            if ui
+                .button("数据集分布")
+                .on_hover_text("框尺寸、宽高比、每图框数、各类别图片数和位置热力图")
+                .clicked()
+            {
+                app.show_dataset_stats = true;
+            }
+            if ui
+                .button("标注进度")
+                .on_hover_text("按会话日志统计每天完成数量、平均耗时和预计完成时间")
+                .clicked()
+            {
+                app.refresh_progress();
+                app.show_progress_dialog = true;
+            }
 
             if !app.bounding_boxes.is_empty() {
                 ui.separator();
@@ -41,36 +135,282 @@ pub fn statistics_panel(app: &mut AnnotationApp, ctx: &egui::Context) {
                         ui.label(format!("类别 {}: {} 个", class, count));
                     }
                 }
+                shape_counts(ui, &app.statistics.current_shape_counts);
+            }
+
+            ui.separator();
+            ui.heading("预标注");
+            ui.horizontal(|ui| {
+                ui.label("置信度阈值:");
+                ui.add(egui::Slider::new(&mut app.confidence_threshold, 0.0..=1.0));
+            });
+            if ui
+                .checkbox(&mut app.hide_low_confidence, "隐藏低置信度框（否则半透明显示）")
+                .changed()
+                && app.hide_low_confidence
+            {
+                // 被隐藏的框不能保持选中状态
+                if let Some(idx) = app.selected_box
+                    && app
+                        .bounding_boxes
+                        .get(idx)
+                        .is_some_and(|b| b.is_low_confidence(app.confidence_threshold))
+                {
+                    app.selected_box = None;
+                }
+            }
+            let pending = app
+                .bounding_boxes
+                .iter()
+                .filter(|b| b.confidence.is_some())
+                .count();
+            ui.label(format!(
+                "当前图片: 未确认 {} 个，其中低置信度 {} 个",
+                pending,
+                app.low_confidence_count()
+            ));
+            ui.horizontal(|ui| {
+                if ui
+                    .button("确认选中框")
+                    .on_hover_text(format!(
+                        "快捷键: {}",
+                        app.keymap.display(Action::PromoteBox)
+                    ))
+                    .clicked()
+                {
+                    app.promote_selected_box();
+                }
+                if ui.button("全部确认").clicked() {
+                    app.promote_all_boxes();
+                }
+            });
+            if ui
+                .button("跳到低置信度框最多的图片")
+                .on_hover_text(format!(
+                    "快捷键: {}",
+                    app.keymap.display(Action::NextLowConfidence)
+                ))
+                .clicked()
+            {
+                app.switch_to_low_confidence_image();
+            }
+
+            if app.compare_dir.is_some() {
+                ui.separator();
+                ui.heading("标签对比");
+                ui.horizontal(|ui| {
+                    ui.label("匹配 IoU:");
+                    if ui
+                        .add(egui::Slider::new(&mut app.diff_iou_threshold, 0.1..=0.9))
+                        .changed()
+                    {
+                        app.update_diff();
+                        app.dataset_diff = None;
+                    }
+                });
+
+                let summary = DiffSummary::from_diffs(&app.box_diffs);
+                ui.label("当前图片:");
+                ui.horizontal_wrapped(|ui| {
+                    for kind in DiffKind::ALL {
+                        ui.colored_label(
+                            kind.color(),
+                            format!("{} {}", kind.label(), summary.count(kind)),
+                        );
+                    }
+                });
+
+                let diffs: Vec<BoxDiff> = app
+                    .box_diffs
+                    .iter()
+                    .filter(|d| d.kind != DiffKind::Unchanged)
+                    .cloned()
+                    .collect();
+                // 采用后框索引和差异列表都会变化，点击的操作在循环结束后再执行
+                let mut accepted = None;
+                for diff in &diffs {
+                    ui.horizontal(|ui| {
+                        let our_class = diff
+                            .ours
+                            .and_then(|i| app.bounding_boxes.get(i))
+                            .map(|b| b.class);
+                        let class = diff
+                            .theirs
+                            .and_then(|j| app.compare_boxes.get(j))
+                            .map(|b| b.class)
+                            .or(our_class)
+                            .unwrap_or_default();
+                        let text = match diff.kind {
+                            DiffKind::Reclassified => format!(
+                                "{}: {} → {}",
+                                diff.kind.label(),
+                                app.project.class_name(our_class.unwrap_or_default()),
+                                app.project.class_name(class)
+                            ),
+                            DiffKind::Moved => format!(
+                                "{}: {} (IoU {:.2})",
+                                diff.kind.label(),
+                                app.project.class_name(class),
+                                diff.iou
+                            ),
+                            _ => format!("{}: {}", diff.kind.label(), app.project.class_name(class)),
+                        };
+                        let response = ui.colored_label(diff.kind.color(), text);
+                        if let Some(ours) = diff.ours
+                            && response.interact(egui::Sense::click()).clicked()
+                        {
+                            app.selected_box = Some(ours);
+                        }
+                        let accept_text = if diff.kind == DiffKind::Removed {
+                            "删除"
+                        } else {
+                            "采用"
+                        };
+                        if ui
+                            .small_button(accept_text)
+                            .on_hover_text("采用对比目录一侧的结果")
+                            .clicked()
+                        {
+                            accepted = Some(diff.clone());
+                        }
+                    });
+                }
+                if let Some(diff) = accepted {
+                    app.accept_diff(&diff);
+                }
+                ui.horizontal(|ui| {
+                    if !diffs.is_empty() && ui.button("全部采用对比结果").clicked() {
+                        app.accept_all_diffs();
+                    }
+                    if ui.button("关闭对比").clicked() {
+                        app.close_compare_dir();
+                    }
+                });
+
+                ui.label("整个数据集:");
+                if ui.button("统计数据集差异").clicked() {
+                    app.update_dataset_diff();
+                }
+                if let Some(dataset) = &app.dataset_diff {
+                    ui.label(format!(
+                        "有差异的图片: {} / {}",
+                        dataset.images_with_changes, dataset.images
+                    ));
+                    ui.horizontal_wrapped(|ui| {
+                        for kind in DiffKind::ALL {
+                            ui.colored_label(
+                                kind.color(),
+                                format!("{} {}", kind.label(), dataset.count(kind)),
+                            );
+                        }
+                    });
+                }
+            }
+
+            ui.separator();
+            ui.heading("类别图例");
+            ui.horizontal(|ui| {
+                if ui.small_button("全部显示").clicked() {
+                    app.hidden_classes.clear();
+                }
+                if ui.small_button("全部隐藏").clicked() {
+                    app.hidden_classes = app.legend_classes().into_iter().collect();
+                    app.selected_box = None;
+                }
+            });
+            for class in app.legend_classes() {
+                ui.horizontal(|ui| {
+                    let [r, g, b, _] = app.class_color(class).to_array();
+                    let mut rgb = [r, g, b];
+                    if ui.color_edit_button_srgb(&mut rgb).changed() {
+                        app.project.class_colors.insert(class, rgb);
+                        app.class_colors_dirty = true;
+                    }
+                    if app.project.class_colors.contains_key(&class)
+                        && ui.small_button("↺").on_hover_text("恢复自动配色").clicked()
+                    {
+                        app.project.class_colors.remove(&class);
+                        app.save_project();
+                    }
+
+                    let visible = app.is_class_visible(class);
+                    let count = app.statistics.total_class_counts.get(&class).copied().unwrap_or(0);
+                    let text = egui::RichText::new(format!(
+                        "{}: {} ({})",
+                        class,
+                        app.project.class_name(class),
+                        count
+                    ))
+                    .color(if visible {
+                        app.class_color(class)
+                    } else {
+                        egui::Color32::GRAY
+                    });
+                    if ui
+                        .selectable_label(visible, text)
+                        .on_hover_text("点击切换该类别在画布上的显示")
+                        .clicked()
+                    {
+                        app.toggle_class_visibility(class);
+                    }
+                });
+            }
+            // 拖动取色时每帧都会修改颜色，等取色弹窗关闭后再写入项目文件
+            if app.class_colors_dirty && !ui.memory(|m| m.any_popup_open()) {
+                app.class_colors_dirty = false;
+                app.save_project();
             }
 
             ui.separator();
             ui.heading("导出功能");
             
-            if ui.button("导出已修改的文件").clicked() {
-                if app.modified_images.is_empty() {
-                    app.show_status("没有已修改的文件可导出");
-                } else if app.image_dir.is_none() || app.label_dir.is_none() {
-                    app.show_status("请先选择图片和标签目录");
-                } else {
-                    if let Some(export_dir) = rfd::FileDialog::new().pick_folder() {
-                        // 将导出结果保存到临时变量，用于在弹窗中显示
-                        let export_result = app.export_modified_files(export_dir.clone());
-                        let export_dir_display = export_dir.display().to_string();
-                        let modified_count = app.modified_images.len();
-                        
-                        // 设置弹窗标志
-                        app.show_export_result_dialog = true;
-                        app.export_result_info = match export_result {
-                            Ok(count) => {
-                                format!("实际已修改数量 {}, 成功导出 {} 个文件到 {}", 
-                                    modified_count, count, export_dir_display)
-                            },
-                            Err(e) => {
-                                format!("导出失败: {}", e)
-                            }
-                        };
+            ui.checkbox(&mut app.export_as_boxes, "多边形/旋转框导出为外接水平框");
+            ui.horizontal_wrapped(|ui| {
+                let hint = "导出左侧文件列表中的图片（可先按状态或标签过滤），包含框属性";
+                if ui.button("导出 COCO").on_hover_text(hint).clicked()
+                    && let Some(path) = rfd::FileDialog::new()
+                        .add_filter("JSON", &["json"])
+                        .set_file_name("annotations.json")
+                        .save_file()
+                {
+                    match app.export_coco(&path) {
+                        Ok(count) => {
+                            app.show_status(&format!("已导出 {} 张图片的 COCO 标注", count))
+                        }
+                        Err(e) => app.show_status(&format!("导出失败: {}", e)),
+                    }
+                }
+                if ui.button("划分数据集").on_hover_text(hint).clicked() {
+                    app.show_split_dialog = true;
+                }
+                if ui.button("导出裁剪图").on_hover_text(hint).clicked() {
+                    app.show_crop_dialog = true;
+                }
+                if ui
+                    .button("类别重映射")
+                    .on_hover_text("合并、改编号或删除类别，可在导出时应用或直接修改标签")
+                    .clicked()
+                {
+                    app.show_remap_dialog = true;
+                }
+                if ui.button("导出 VOC").on_hover_text(hint).clicked()
+                    && let Some(dir) = rfd::FileDialog::new().pick_folder()
+                {
+                    match app.export_voc(&dir) {
+                        Ok(count) => app.show_status(&format!("已导出 {} 个 VOC 标注文件", count)),
+                        Err(e) => app.show_status(&format!("导出失败: {}", e)),
                     }
                 }
+            });
+            if ui
+                .button("按方案导出图片/标签")
+                .on_hover_text("选择导出范围、内容、写出方式和重名处理，可先预览")
+                .clicked()
+            {
+                if app.project.export_profiles.is_empty() {
+                    app.project.export_profiles = ExportProfile::presets();
+                }
+                app.show_export_dialog = true;
             }
             
             // 显示导出结果弹窗
@@ -119,45 +459,22 @@ pub fn statistics_panel(app: &mut AnnotationApp, ctx: &egui::Context) {
                         ui.label("你确定要删除当前图片及标签吗？");
                         ui.horizontal(|ui| {
                             if ui.button("确定").clicked() {
-                                if let (Some(image_path), Some(label_dir)) =
-                                    (&app.current_image_path, &app.label_dir)
-                                {
-                                    // 获取标签文件路径
-                                    let label_path = label_dir
-                                        .join(image_path.file_stem().unwrap())
-                                        .with_extension("txt");
-
-                                    // 删除标签文件
-                                    if label_path.exists() {
-                                        if let Err(e) = std::fs::remove_file(&label_path) {
-                                            app.show_status(&format!("删除标签文件失败: {}", e));
-                                            return;
+                                if let Some(image_path) = app.current_image_path.clone() {
+                                    match app.delete_image_files(&image_path) {
+                                        Ok(()) => {
+                                            // 更新文件列表并切换到下一张图片
+                                            app.update_file_list();
+                                            if let Some(prev_path) = app.history.pop() {
+                                                app.load_image(&prev_path);
+                                            } else if let Some(next_path) =
+                                                app.cached_image_files.first().cloned()
+                                            {
+                                                app.load_image(&next_path);
+                                            }
+                                            app.show_status("已删除当前图片及标签");
                                         }
+                                        Err(e) => app.show_status(&e),
                                     }
-
-                                    // 删除图片文件
-                                    if let Err(e) = std::fs::remove_file(image_path) {
-                                        app.show_status(&format!("删除图片文件失败: {}", e));
-                                        return;
-                                    }
-
-                                    // 从缓存中移除
-                                    if let Some(name) = &app.current_image_name {
-                                        app.modified_images.remove(name);
-                                    }
-                                    app.image_cache.remove(image_path);
-
-                                    // 更新文件列表并切换到下一张图片
-                                    app.update_file_list();
-                                    app.update_total_statistics();
-                                    if let Some(prev_path) = app.history.pop() {
-                                        app.load_image(&prev_path);
-                                    } else if let Some(next_path) =
-                                        app.cached_image_files.first().cloned()
-                                    {
-                                        app.load_image(&next_path);
-                                    }
-                                    app.show_status("已删除当前图片及标签");
                                 }
                                 app.show_delete_confirmation = false; // 关闭确认对话框
                             }
@@ -170,6 +487,111 @@ pub fn statistics_panel(app: &mut AnnotationApp, ctx: &egui::Context) {
 
             ui.separator();
             ui.heading("操作模式");
+            ui.horizontal(|ui| {
+                ui.label("标注模式:");
+                let previous = app.project.annotation_mode;
+                egui::ComboBox::from_id_salt("annotation_mode")
+                    .selected_text(app.project.annotation_mode.label())
+                    .show_ui(ui, |ui| {
+                        for mode in AnnotationMode::ALL {
+                            ui.selectable_value(
+                                &mut app.project.annotation_mode,
+                                mode,
+                                mode.label(),
+                            );
+                        }
+                    });
+                if app.project.annotation_mode != previous {
+                    app.polygon_draft.clear();
+                    app.save_project();
+                }
+            });
+            if app.project.annotation_mode == AnnotationMode::Segment {
+                ui.small("绘制：单击添加顶点，单击首个顶点、双击或按 Enter 闭合");
+                ui.small("编辑：拖动顶点移动，双击边插入顶点，Alt+单击删除顶点");
+            }
+            if app.project.annotation_mode == AnnotationMode::Pose {
+                ui.small("画完框后依次单击放置关键点；拖动关键点移动，Alt+单击切换可见/遮挡");
+                if ui.small_button("关键点模板").clicked() {
+                    app.keypoint_schema_draft = KeypointSchemaDraft::new(
+                        app.selected_class,
+                        app.project.keypoint_schemas.get(&app.selected_class),
+                    );
+                    app.show_keypoint_schema_dialog = true;
+                }
+                keypoint_list(app, ui);
+            }
+            ui.horizontal(|ui| {
+                if ui.small_button("选中框转旋转框").clicked() {
+                    app.convert_selected_shape(true);
+                }
+                if ui.small_button("选中框转水平框").clicked() {
+                    app.convert_selected_shape(false);
+                }
+            });
+            ui.horizontal(|ui| {
+                if ui.small_button("本图全部转旋转框").clicked() {
+                    app.convert_current_shapes(true);
+                }
+                if ui.small_button("本图全部转水平框").clicked() {
+                    app.convert_current_shapes(false);
+                }
+            });
+            ui.horizontal(|ui| {
+                for (oriented, text) in [
+                    (true, "数据集全部转旋转框"),
+                    (false, "数据集全部转水平框"),
+                ] {
+                    if ui
+                        .small_button(text)
+                        .on_hover_text(
+                            "直接修改所有图片的标签文件，多边形和带关键点的框保持不变",
+                        )
+                        .clicked()
+                    {
+                        app.confirm_shape_convert = Some(oriented);
+                    }
+                }
+            });
+            if let Some(oriented) = app.confirm_shape_convert {
+                ui.colored_label(
+                    egui::Color32::LIGHT_RED,
+                    format!(
+                        "将把 {} 张图片中的框转为{}，无法撤销，确定吗？",
+                        app.cached_image_files.len(),
+                        if oriented { "旋转框" } else { "水平框" }
+                    ),
+                );
+                ui.horizontal(|ui| {
+                    if ui.button("确定").clicked() {
+                        app.confirm_shape_convert = None;
+                        match app.convert_dataset_shapes(oriented) {
+                            Ok(report) => {
+                                let mut info = format!(
+                                    "已修改 {} 个标签文件，转换 {} 个框",
+                                    report.files, report.boxes
+                                );
+                                if report.skipped > 0 {
+                                    info.push_str(&format!(
+                                        "，{} 个框不能转换，保持不变",
+                                        report.skipped
+                                    ));
+                                }
+                                for failure in report.failures.iter().take(10) {
+                                    info.push('\n');
+                                    info.push_str(failure);
+                                }
+                                app.export_result_info = info;
+                                app.show_export_result_dialog = true;
+                            }
+                            Err(e) => app.show_status(&e),
+                        }
+                    }
+                    if ui.button("取消").clicked() {
+                        app.confirm_shape_convert = None;
+                    }
+                });
+            }
             ui.horizontal(|ui| {
                 if ui
                     .button(if app.is_drawing {
@@ -182,6 +604,8 @@ pub fn statistics_panel(app: &mut AnnotationApp, ctx: &egui::Context) {
                     app.is_drawing = !app.is_drawing;
                     app.drawing_start = None;
                     app.selected_box = None;
+                    app.polygon_draft.clear();
+                    app.active_keypoint = None;
                     app.show_status(if app.is_drawing {
                         "已进入绘制模式"
                     } else {
@@ -225,13 +649,21 @@ pub fn statistics_panel(app: &mut AnnotationApp, ctx: &egui::Context) {
                 ui.heading("添加边界框");
                 // 添加类型选择按钮
                 ui.horizontal_wrapped(|ui| {
-                    for class in 0..10 {
+                    for class in 0..CLASS_HOTKEY_COUNT {
                         let text = if class == app.selected_class {
                             format!("【类别 {}】", class)
                         } else {
                             format!("类别 {}", class)
                         };
-                        if ui.button(text).clicked() {
+                        if ui
+                            .button(egui::RichText::new(text).color(app.class_color(class)))
+                            .on_hover_text(format!(
+                                "{} 快捷键: {}",
+                                app.project.class_name(class),
+                                app.keymap.display(Action::SelectClass(class))
+                            ))
+                            .clicked()
+                        {
                             app.selected_class = class;
                         }
                     }
@@ -253,4 +685,156 @@ pub fn statistics_panel(app: &mut AnnotationApp, ctx: &egui::Context) {
                 }
             }
         });
-}
\ No newline at end of file
+}
+
+/// 按形状统计的数量，只有一种形状时不显示
+fn shape_counts(ui: &mut egui::Ui, counts: &HashMap<ShapeKind, usize>) {
+    if counts.len() < 2 {
+        return;
+    }
+    ui.horizontal(|ui| {
+        for kind in ShapeKind::ALL {
+            ui.label(format!(
+                "{}: {} 个",
+                kind.label(),
+                counts.get(&kind).copied().unwrap_or(0)
+            ));
+        }
+    });
+}
+
+/// 选中框的关键点列表：状态、放置、切换可见性和清除
+fn keypoint_list(app: &mut AnnotationApp, ui: &mut egui::Ui) {
+    let Some(bbox) = app.selected_box.and_then(|idx| app.bounding_boxes.get(idx)) else {
+        return;
+    };
+    let Some(schema) = app.keypoint_schema(bbox.class) else {
+        ui.small("选中框的类别没有关键点模板");
+        return;
+    };
+    let names = schema.names.clone();
+    let keypoints = bbox.keypoints.clone();
+
+    egui::Grid::new("keypoint_list").striped(true).show(ui, |ui| {
+        for (index, name) in names.iter().enumerate() {
+            let visibility = keypoints.get(index).map_or(0, |kp| kp.visibility);
+            let active = app.active_keypoint == Some(index);
+            let text = egui::RichText::new(format!("{}. {}", index, name));
+            ui.label(if active { text.strong() } else { text });
+            ui.label(match visibility {
+                0 => "未标注",
+                1 => "遮挡",
+                _ => "可见",
+            });
+            if ui.small_button("放置").clicked() {
+                app.active_keypoint = Some(index);
+                app.show_status(&format!("单击画布放置关键点 {}", name));
+            }
+            if ui
+                .add_enabled(visibility > 0, egui::Button::new("可见/遮挡").small())
+                .clicked()
+            {
+                app.toggle_keypoint_visibility(index);
+            }
+            if ui
+                .add_enabled(visibility > 0, egui::Button::new("清除").small())
+                .clicked()
+            {
+                app.clear_keypoint(index);
+            }
+            ui.end_row();
+        }
+    });
+}
+
+/// 图片标签：当前图片的标签开关、标签定义管理、分布统计和导出
+fn image_tags_section(app: &mut AnnotationApp, ui: &mut egui::Ui) {
+    let current = app.current_tags();
+    let mut toggle = None;
+    let mut remove = None;
+    for (index, definition) in app.project.image_tags.iter().enumerate() {
+        ui.horizontal(|ui| {
+            let mut checked = current.contains(&definition.name);
+            let text = if definition.group.is_empty() {
+                definition.name.clone()
+            } else {
+                format!("{}（{}）", definition.name, definition.group)
+            };
+            let mut response = ui.add_enabled(
+                app.current_image_name.is_some(),
+                egui::Checkbox::new(&mut checked, text),
+            );
+            if index < TAG_HOTKEY_COUNT {
+                response = response.on_hover_text(format!(
+                    "快捷键: {}",
+                    app.keymap.display(Action::ToggleTag(index))
+                ));
+            }
+            if response.changed() {
+                toggle = Some(index);
+            }
+            if ui.small_button("删除").clicked() {
+                remove = Some(index);
+            }
+        });
+    }
+    if let Some(index) = toggle {
+        app.toggle_current_tag(index);
+    }
+    if let Some(index) = remove {
+        app.remove_tag_definition(index);
+    }
+
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::TextEdit::singleline(&mut app.new_tag_name)
+                .hint_text("标签名")
+                .desired_width(80.0),
+        );
+        ui.add(
+            egui::TextEdit::singleline(&mut app.new_tag_group)
+                .hint_text("互斥分组（可选）")
+                .desired_width(100.0),
+        );
+        if ui.small_button("添加").clicked() {
+            app.add_tag_definition();
+        }
+    });
+
+    if app.project.image_tags.is_empty() {
+        return;
+    }
+    let images = app.image_names();
+    let counts = tags::tag_counts(&app.image_tags, &images);
+    let total = images.len().max(1);
+    for definition in &app.project.image_tags {
+        let count = counts.get(&definition.name).copied().unwrap_or(0);
+        ui.label(format!(
+            "{}: {} 张 ({:.1}%)",
+            definition.name,
+            count,
+            count as f64 * 100.0 / total as f64
+        ));
+    }
+    let untagged = images
+        .iter()
+        .filter(|name| app.image_tags.get(*name).is_none_or(|t| t.is_empty()))
+        .count();
+    ui.label(format!("未打标签: {} 张", untagged));
+
+    ui.horizontal(|ui| {
+        for (csv, text, extension) in [(true, "导出 CSV", "csv"), (false, "导出 JSON", "json")] {
+            if ui.small_button(text).clicked()
+                && let Some(path) = rfd::FileDialog::new()
+                    .add_filter(extension.to_uppercase(), &[extension])
+                    .set_file_name(format!("image_tags.{}", extension))
+                    .save_file()
+            {
+                match app.export_image_tags(&path, csv) {
+                    Ok(()) => app.show_status("已导出图片标签"),
+                    Err(e) => app.show_status(&format!("导出失败: {}", e)),
+                }
+            }
+        }
+    });
+}
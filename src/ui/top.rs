@@ -11,6 +11,43 @@ pub fn top_panel(app: &mut AnnotationApp, ctx: &egui::Context) {
             if ui.button("选择标签文件夹").clicked() {
                 app.select_label_dir();
             }
+            if ui.button("选择对比目录").clicked() {
+                app.select_compare_dir();
+            }
+            if ui
+                .selectable_label(app.show_metrics_panel, "评估指标")
+                .clicked()
+            {
+                app.show_metrics_panel = !app.show_metrics_panel;
+            }
+            if ui.button("自动标注").clicked() {
+                app.show_auto_label_dialog = true;
+            }
+            if ui.button("合并项目").clicked() {
+                app.show_merge_dialog = true;
+            }
+            if ui.button("重复图片").clicked() {
+                app.show_dedup_dialog = true;
+            }
+            if ui.button("快捷键设置").clicked() {
+                app.show_keymap_dialog = true;
+            }
+            if ui
+                .selectable_label(app.review_mode, "审核模式")
+                .clicked()
+            {
+                app.toggle_review_mode();
+            }
+            ui.label("标注员:");
+            let default_name = app.project.annotator_name();
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut app.project.annotator)
+                    .desired_width(80.0)
+                    .hint_text(default_name),
+            );
+            if response.lost_focus() {
+                app.save_project();
+            }
 
             if let Some(image_dir) = &app.image_dir {
                 ui.label(format!("图片目录: {}", image_dir.display()));
@@ -18,6 +55,9 @@ pub fn top_panel(app: &mut AnnotationApp, ctx: &egui::Context) {
             if let Some(label_dir) = &app.label_dir {
                 ui.label(format!("标签目录: {}", label_dir.display()));
             }
+            if let Some(compare_dir) = &app.compare_dir {
+                ui.label(format!("对比目录: {}", compare_dir.display()));
+            }
         });
     });
 }
\ No newline at end of file
@@ -1,4 +1,7 @@
+use eframe::egui;
 use image::DynamicImage;
+use std::fs;
+use std::path::{Path, PathBuf};
 
 pub fn resize_to_limit(img: &DynamicImage, max_width: u32, max_height: u32) -> DynamicImage {
     let width = img.width();
@@ -14,4 +17,82 @@ pub fn resize_to_limit(img: &DynamicImage, max_width: u32, max_height: u32) -> D
     let new_height = (height as f32 * ratio) as u32;
 
     img.resize(new_width, new_height, image::imageops::FilterType::Triangle)
-}
\ No newline at end of file
+}
+
+/// 按类别编号生成稳定的自动配色（黄金角分布色相，相邻类别颜色差异明显）
+pub fn auto_class_color(class: i32) -> egui::Color32 {
+    let hue = (class.rem_euclid(360) as f32 * 0.618_034).fract();
+    egui::ecolor::Hsva::new(hue, 0.85, 0.95, 1.0).into()
+}
+
+/// 当前 Unix 时间戳（秒）
+pub fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// 将 Unix 时间戳格式化为本地时间
+pub fn format_timestamp(secs: u64) -> String {
+    match chrono::DateTime::from_timestamp(secs as i64, 0) {
+        Some(time) => time
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string(),
+        None => "-".to_string(),
+    }
+}
+
+/// 含逗号、引号或换行的字段加引号
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// 转义 HTML 文本和属性值中的特殊字符
+pub fn html_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// 标准 Base64 编码（带填充），用于在 HTML 中内嵌图片
+pub fn base64_encode(data: &[u8]) -> String {
+    const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(TABLE[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// 列出目录中指定扩展名的文件（已排序）
+pub fn list_files(dir: &Path, extensions: &[&str]) -> Result<Vec<PathBuf>, String> {
+    let entries =
+        fs::read_dir(dir).map_err(|e| format!("读取目录失败 {}: {}", dir.display(), e))?;
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| extensions.contains(&ext))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}